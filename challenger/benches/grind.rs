@@ -0,0 +1,60 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::{CanObserve, DuplexChallenger, GrindingChallenger};
+use p3_field::PrimeField64;
+use p3_goldilocks::{Goldilocks, Poseidon2Goldilocks};
+use p3_koala_bear::{KoalaBear, Poseidon2KoalaBear};
+use p3_mersenne_31::{Mersenne31, Poseidon2Mersenne31};
+use p3_symmetric::CryptographicPermutation;
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+const BITS: [usize; 2] = [20, 22];
+
+fn bench_grind<F, P, const WIDTH: usize, const RATE: usize>(c: &mut Criterion, name: &str, perm: P)
+where
+    F: PrimeField64,
+    P: CryptographicPermutation<[F; WIDTH]> + CryptographicPermutation<[F::Packing; WIDTH]>,
+{
+    let mut challenger = DuplexChallenger::<F, P, WIDTH, RATE>::new(perm);
+    challenger.observe(F::ONE);
+
+    let mut group = c.benchmark_group(format!("grind/{name}"));
+    group.sample_size(10);
+    for bits in BITS {
+        group.bench_with_input(BenchmarkId::new("packed", bits), &bits, |b, &bits| {
+            b.iter(|| challenger.clone().grind_packed(bits));
+        });
+        group.bench_with_input(BenchmarkId::new("scalar", bits), &bits, |b, &bits| {
+            b.iter(|| challenger.clone().grind(bits));
+        });
+    }
+    group.finish();
+}
+
+fn bench_all(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    bench_grind::<BabyBear, _, 16, 8>(
+        c,
+        "baby-bear",
+        Poseidon2BabyBear::<16>::new_from_rng_128(&mut rng),
+    );
+    bench_grind::<KoalaBear, _, 16, 8>(
+        c,
+        "koala-bear",
+        Poseidon2KoalaBear::<16>::new_from_rng_128(&mut rng),
+    );
+    bench_grind::<Mersenne31, _, 16, 8>(
+        c,
+        "mersenne-31",
+        Poseidon2Mersenne31::<16>::new_from_rng_128(&mut rng),
+    );
+    bench_grind::<Goldilocks, _, 8, 4>(
+        c,
+        "goldilocks",
+        Poseidon2Goldilocks::<8>::new_from_rng_128(&mut rng),
+    );
+}
+
+criterion_group!(benches, bench_all);
+criterion_main!(benches);
@@ -2,9 +2,10 @@ use alloc::vec;
 use alloc::vec::Vec;
 
 use p3_field::{BasedVectorSpace, Field, PrimeField64};
-use p3_symmetric::{CryptographicPermutation, Hash};
+use p3_symmetric::{CryptographicPermutation, Hash, MerkleCap};
+use serde::{Deserialize, Serialize};
 
-use crate::{CanObserve, CanSample, CanSampleBits, FieldChallenger};
+use crate::{CanObserve, CanSample, CanSampleBits, ChallengerStateError, FieldChallenger};
 
 #[derive(Clone, Debug)]
 pub struct DuplexChallenger<F, P, const WIDTH: usize, const RATE: usize>
@@ -35,6 +36,54 @@ where
         }
     }
 
+    /// Returns a snapshot of the transcript so far, which can be persisted and later resumed with
+    /// `restore`.
+    pub fn state(&self) -> DuplexChallengerState<F> {
+        DuplexChallengerState {
+            sponge_state: self.sponge_state.to_vec(),
+            input_buffer: self.input_buffer.clone(),
+            output_buffer: self.output_buffer.clone(),
+        }
+    }
+
+    /// Resumes a transcript from a snapshot taken with `state`.
+    ///
+    /// Returns an error if the snapshot doesn't fit a challenger with this `WIDTH` and `RATE`.
+    pub fn restore(
+        permutation: P,
+        state: DuplexChallengerState<F>,
+    ) -> Result<Self, ChallengerStateError> {
+        let DuplexChallengerState {
+            sponge_state,
+            input_buffer,
+            output_buffer,
+        } = state;
+        if input_buffer.len() >= RATE {
+            return Err(ChallengerStateError::InputBufferTooLong {
+                len: input_buffer.len(),
+                limit: RATE,
+            });
+        }
+        if output_buffer.len() > RATE {
+            return Err(ChallengerStateError::OutputBufferTooLong {
+                len: output_buffer.len(),
+                max: RATE,
+            });
+        }
+        let sponge_state = sponge_state.try_into().map_err(|sponge_state: Vec<F>| {
+            ChallengerStateError::WrongSpongeStateLength {
+                expected: WIDTH,
+                actual: sponge_state.len(),
+            }
+        })?;
+        Ok(Self {
+            sponge_state,
+            input_buffer,
+            output_buffer,
+            permutation,
+        })
+    }
+
     fn duplexing(&mut self) {
         assert!(self.input_buffer.len() <= RATE);
 
@@ -51,6 +100,14 @@ where
     }
 }
 
+/// A serializable snapshot of a `DuplexChallenger`'s transcript, without its permutation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplexChallengerState<F> {
+    pub sponge_state: Vec<F>,
+    pub input_buffer: Vec<F>,
+    pub output_buffer: Vec<F>,
+}
+
 impl<F, P, const WIDTH: usize, const RATE: usize> FieldChallenger<F>
     for DuplexChallenger<F, P, WIDTH, RATE>
 where
@@ -103,6 +160,19 @@ where
     }
 }
 
+impl<F, P, const N: usize, const WIDTH: usize, const RATE: usize> CanObserve<MerkleCap<F, F, N>>
+    for DuplexChallenger<F, P, WIDTH, RATE>
+where
+    F: Copy,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    fn observe(&mut self, cap: MerkleCap<F, F, N>) {
+        for digest in cap {
+            self.observe(digest);
+        }
+    }
+}
+
 // for TrivialPcs
 impl<F, P, const WIDTH: usize, const RATE: usize> CanObserve<Vec<Vec<F>>>
     for DuplexChallenger<F, P, WIDTH, RATE>
@@ -160,10 +230,12 @@ where
 mod tests {
     use core::iter;
 
-    use p3_baby_bear::BabyBear;
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
     use p3_field::PrimeCharacteristicRing;
     use p3_goldilocks::Goldilocks;
     use p3_symmetric::Permutation;
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
 
     use super::*;
     use crate::grinding_challenger::GrindingChallenger;
@@ -243,4 +315,81 @@ mod tests {
         let witness = duplex_challenger.grind(too_many_bits);
         assert!(duplex_challenger.check_witness(too_many_bits, witness));
     }
+
+    #[test]
+    fn test_duplex_challenger_restore() {
+        type Chal = DuplexChallenger<G, TestPermutation, WIDTH, RATE>;
+        let mut challenger = Chal::new(TestPermutation {});
+
+        // A shared preamble, leaving both buffers non-empty.
+        (0..20).for_each(|element| challenger.observe(G::from_u8(element)));
+        let _: G = challenger.sample();
+        challenger.observe(G::from_u8(42));
+
+        let bytes = postcard::to_allocvec(&challenger.state()).unwrap();
+        let state = postcard::from_bytes(&bytes).unwrap();
+        let mut resumed = Chal::restore(TestPermutation {}, state).unwrap();
+
+        for chal in [&mut challenger, &mut resumed] {
+            (0..30).for_each(|element| chal.observe(G::from_u8(element)));
+        }
+        assert_eq!(
+            <Chal as CanSample<G>>::sample_vec(&mut challenger, 20),
+            <Chal as CanSample<G>>::sample_vec(&mut resumed, 20)
+        );
+        assert_eq!(challenger.sample_bits(10), resumed.sample_bits(10));
+    }
+
+    #[test]
+    fn test_duplex_challenger_restore_rejects_malformed_state() {
+        type Chal = DuplexChallenger<G, TestPermutation, WIDTH, RATE>;
+        let state = Chal::new(TestPermutation {}).state();
+
+        let mut short_sponge = state.clone();
+        short_sponge.sponge_state.pop();
+        let mut full_input = state.clone();
+        full_input.input_buffer = vec![G::ONE; RATE];
+        let mut long_output = state;
+        long_output.output_buffer = vec![G::ONE; RATE + 1];
+
+        let cases = [
+            (
+                short_sponge,
+                ChallengerStateError::WrongSpongeStateLength {
+                    expected: WIDTH,
+                    actual: WIDTH - 1,
+                },
+            ),
+            (
+                full_input,
+                ChallengerStateError::InputBufferTooLong {
+                    len: RATE,
+                    limit: RATE,
+                },
+            ),
+            (
+                long_output,
+                ChallengerStateError::OutputBufferTooLong {
+                    len: RATE + 1,
+                    max: RATE,
+                },
+            ),
+        ];
+        for (state, error) in cases {
+            assert_eq!(Chal::restore(TestPermutation {}, state).err(), Some(error));
+        }
+    }
+
+    #[test]
+    fn test_duplex_challenger_packed_grind() {
+        type Perm = Poseidon2BabyBear<16>;
+        let perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+        let mut challenger = DuplexChallenger::<BB, Perm, 16, 8>::new(perm);
+
+        // Leave some inputs buffered, so the witness is absorbed at a non-zero offset.
+        (0..11).for_each(|element| challenger.observe(BB::from_u8(element)));
+
+        let witness = challenger.clone().grind_packed(12);
+        assert!(challenger.check_witness(12, witness));
+    }
 }
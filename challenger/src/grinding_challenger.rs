@@ -1,9 +1,11 @@
-use p3_field::{Field, PrimeField, PrimeField32, PrimeField64};
+use p3_field::{Field, PackedValue, PrimeField, PrimeField32, PrimeField64};
 use p3_maybe_rayon::prelude::*;
 use p3_symmetric::CryptographicPermutation;
 use tracing::instrument;
 
-use crate::{CanObserve, CanSampleBits, DuplexChallenger, MultiField32Challenger};
+use crate::{
+    CanObserve, CanSampleBits, DuplexChallenger, MultiField32Challenger, MultiField64Challenger,
+};
 
 pub trait GrindingChallenger:
     CanObserve<Self::Witness> + CanSampleBits<usize> + Sync + Clone
@@ -31,7 +33,6 @@ where
     fn grind(&mut self, bits: usize) -> Self::Witness {
         assert!(bits < (usize::BITS as usize));
         assert!((1 << bits) < F::ORDER_U64);
-
         let witness = (0..F::ORDER_U64)
             .into_par_iter()
             .map(|i| unsafe {
@@ -45,6 +46,50 @@ where
     }
 }
 
+impl<F, P, const WIDTH: usize, const RATE: usize> DuplexChallenger<F, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    P: CryptographicPermutation<[F; WIDTH]> + CryptographicPermutation<[F::Packing; WIDTH]>,
+{
+    /// Like `GrindingChallenger::grind`, but tests `F::Packing::WIDTH` candidate witnesses per
+    /// permutation call, for permutations which can also act on packed states.
+    ///
+    /// Observing a witness and then sampling performs exactly one duplexing, whose state is the
+    /// current sponge state with the buffered inputs and the witness written over its prefix. So
+    /// each lane of a packed state can absorb a different candidate, and the sample for each
+    /// candidate is the last rate element of its lane.
+    #[instrument(name = "grind for proof-of-work witness over packed lanes", skip_all)]
+    pub fn grind_packed(&mut self, bits: usize) -> F {
+        assert!(bits < (usize::BITS as usize));
+        assert!((1 << bits) < F::ORDER_U64);
+
+        let mut state = self.sponge_state;
+        state[..self.input_buffer.len()].copy_from_slice(&self.input_buffer);
+        let packed_state = state.map(F::Packing::from);
+        let witness_index = self.input_buffer.len();
+        let mask = (1 << bits) - 1;
+
+        let lanes = F::Packing::WIDTH as u64;
+        let witness = (0..F::ORDER_U64.div_ceil(lanes))
+            .into_par_iter()
+            .find_map_any(|chunk| {
+                let start = chunk * lanes;
+                let mut state = packed_state;
+                // The last chunk may wrap around past the modulus, which only repeats candidates.
+                state[witness_index] = F::Packing::from_fn(|lane| F::from_u64(start + lane as u64));
+                self.permutation.permute_mut(&mut state);
+                state[RATE - 1]
+                    .as_slice()
+                    .iter()
+                    .position(|sample| (sample.as_canonical_u64() as usize) & mask == 0)
+                    .map(|lane| F::from_u64(start + lane as u64))
+            })
+            .expect("failed to find witness");
+        assert!(self.check_witness(bits, witness));
+        witness
+    }
+}
+
 impl<F, PF, P, const WIDTH: usize, const RATE: usize> GrindingChallenger
     for MultiField32Challenger<F, PF, P, WIDTH, RATE>
 where
@@ -70,3 +115,29 @@ where
         witness
     }
 }
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> GrindingChallenger
+    for MultiField64Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    type Witness = F;
+
+    #[instrument(name = "grind for proof-of-work witness", skip_all)]
+    fn grind(&mut self, bits: usize) -> Self::Witness {
+        assert!(bits < (usize::BITS as usize));
+        assert!((1 << bits) < F::ORDER_U64);
+        let witness = (0..F::ORDER_U64)
+            .into_par_iter()
+            .map(|i| unsafe {
+                // i < F::ORDER_U64 by construction so this is safe.
+                F::from_canonical_unchecked(i)
+            })
+            .find_any(|witness| self.clone().check_witness(bits, *witness))
+            .expect("failed to find witness");
+        assert!(self.check_witness(bits, witness));
+        witness
+    }
+}
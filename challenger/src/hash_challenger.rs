@@ -2,8 +2,9 @@ use alloc::vec;
 use alloc::vec::Vec;
 
 use p3_symmetric::CryptographicHasher;
+use serde::{Deserialize, Serialize};
 
-use crate::{CanObserve, CanSample};
+use crate::{CanObserve, CanSample, ChallengerStateError};
 
 /// A generic challenger that uses a cryptographic hash function to generate challenges.
 #[derive(Clone, Debug)]
@@ -33,6 +34,32 @@ where
         }
     }
 
+    /// Returns a snapshot of the transcript so far, which can be persisted and later resumed with
+    /// `restore`.
+    pub fn state(&self) -> HashChallengerState<T> {
+        HashChallengerState {
+            input_buffer: self.input_buffer.clone(),
+            output_buffer: self.output_buffer.clone(),
+        }
+    }
+
+    /// Resumes a transcript from a snapshot taken with `state`.
+    ///
+    /// Returns an error if the snapshot's output buffer is longer than a single hash output.
+    pub fn restore(hasher: H, state: HashChallengerState<T>) -> Result<Self, ChallengerStateError> {
+        if state.output_buffer.len() > OUT_LEN {
+            return Err(ChallengerStateError::OutputBufferTooLong {
+                len: state.output_buffer.len(),
+                max: OUT_LEN,
+            });
+        }
+        Ok(Self {
+            input_buffer: state.input_buffer,
+            output_buffer: state.output_buffer,
+            hasher,
+        })
+    }
+
     fn flush(&mut self) {
         let inputs = self.input_buffer.drain(..);
         let output = self.hasher.hash_iter(inputs);
@@ -44,6 +71,13 @@ where
     }
 }
 
+/// A serializable snapshot of a `HashChallenger`'s transcript, without its hasher.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashChallengerState<T> {
+    pub input_buffer: Vec<T>,
+    pub output_buffer: Vec<T>,
+}
+
 impl<T, H, const OUT_LEN: usize> CanObserve<T> for HashChallenger<T, H, OUT_LEN>
 where
     T: Clone,
@@ -335,4 +369,37 @@ mod tests {
         // Verify that the output buffer is cleared after observing
         assert!(hash_challenger.output_buffer.is_empty());
     }
+
+    #[test]
+    fn test_restore() {
+        let mut challenger =
+            HashChallenger::<F, TestHasher, OUT_LEN>::new(vec![F::ONE], TestHasher {});
+        challenger.observe(F::TWO);
+        let _ = challenger.sample();
+        challenger.observe(F::from_u8(5));
+
+        let bytes = postcard::to_allocvec(&challenger.state()).unwrap();
+        let state = postcard::from_bytes(&bytes).unwrap();
+        let mut resumed =
+            HashChallenger::<F, TestHasher, OUT_LEN>::restore(TestHasher {}, state).unwrap();
+
+        for _ in 0..5 {
+            assert_eq!(challenger.sample(), resumed.sample());
+        }
+    }
+
+    #[test]
+    fn test_restore_rejects_long_output_buffer() {
+        let state = HashChallengerState {
+            input_buffer: vec![],
+            output_buffer: vec![F::ONE; OUT_LEN + 1],
+        };
+        assert_eq!(
+            HashChallenger::<F, TestHasher, OUT_LEN>::restore(TestHasher {}, state).err(),
+            Some(ChallengerStateError::OutputBufferTooLong {
+                len: OUT_LEN + 1,
+                max: OUT_LEN,
+            })
+        );
+    }
 }
@@ -0,0 +1,249 @@
+use p3_field::{Field, PrimeField, PrimeField32, PrimeField64};
+use p3_symmetric::{CryptographicHasher, CryptographicPermutation};
+
+use crate::{
+    CanObserve, CanSample, CanSampleBits, DuplexChallenger, FieldChallenger, GrindingChallenger,
+    HashChallenger, MultiField32Challenger, MultiField64Challenger,
+};
+
+/// A challenger that can absorb a domain separator into its transcript.
+///
+/// Implementations must encode labels injectively, so that distinct sequences of labels always
+/// lead to distinct transcripts. The implementations in this crate prefix each label with its
+/// length.
+pub trait CanObserveLabel {
+    fn observe_label(&mut self, label: &[u8]);
+}
+
+/// Wraps a challenger so that observations and samples can be tagged with a label.
+///
+/// The protocol label passed to `new` is absorbed before anything else, so transcripts of
+/// different protocols never coincide. Every labelled observation or sample absorbs its label
+/// first, which separates protocol steps that would otherwise observe the same kind of data.
+///
+/// All challenger traits are forwarded to the inner challenger, so a `LabeledChallenger` can be
+/// used anywhere its inner challenger can, e.g. as a `StarkGenericConfig::Challenger`. Unlabelled
+/// observations and samples through those traits are passed through unchanged.
+#[derive(Clone, Debug)]
+pub struct LabeledChallenger<Inner> {
+    inner: Inner,
+}
+
+impl<Inner: CanObserveLabel> LabeledChallenger<Inner> {
+    pub fn new(mut inner: Inner, protocol_label: &[u8]) -> Self {
+        inner.observe_label(protocol_label);
+        Self { inner }
+    }
+
+    /// Wraps a challenger that has already absorbed its protocol label, e.g. one restored from a
+    /// snapshot of a labelled transcript.
+    pub const fn resume(inner: Inner) -> Self {
+        Self { inner }
+    }
+
+    pub const fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Absorbs a domain separator into the transcript.
+    pub fn absorb_label(&mut self, label: &[u8]) {
+        self.inner.observe_label(label);
+    }
+
+    /// Absorbs `label`, then observes `value`.
+    pub fn observe_labeled<T>(&mut self, label: &[u8], value: T)
+    where
+        Inner: CanObserve<T>,
+    {
+        self.absorb_label(label);
+        self.inner.observe(value);
+    }
+
+    /// Absorbs `label`, then samples a value.
+    pub fn sample_labeled<T>(&mut self, label: &[u8]) -> T
+    where
+        Inner: CanSample<T>,
+    {
+        self.absorb_label(label);
+        self.inner.sample()
+    }
+
+    pub fn into_inner(self) -> Inner {
+        self.inner
+    }
+}
+
+impl<Inner, T> CanObserve<T> for LabeledChallenger<Inner>
+where
+    Inner: CanObserve<T>,
+{
+    #[inline(always)]
+    fn observe(&mut self, value: T) {
+        self.inner.observe(value);
+    }
+
+    #[inline(always)]
+    fn observe_slice(&mut self, values: &[T])
+    where
+        T: Clone,
+    {
+        self.inner.observe_slice(values);
+    }
+}
+
+impl<Inner, T> CanSample<T> for LabeledChallenger<Inner>
+where
+    Inner: CanSample<T>,
+{
+    #[inline(always)]
+    fn sample(&mut self) -> T {
+        self.inner.sample()
+    }
+}
+
+impl<Inner, T> CanSampleBits<T> for LabeledChallenger<Inner>
+where
+    Inner: CanSampleBits<T>,
+{
+    #[inline(always)]
+    fn sample_bits(&mut self, bits: usize) -> T {
+        self.inner.sample_bits(bits)
+    }
+}
+
+impl<Inner, F> FieldChallenger<F> for LabeledChallenger<Inner>
+where
+    Inner: FieldChallenger<F>,
+    F: Field,
+{
+}
+
+impl<Inner> GrindingChallenger for LabeledChallenger<Inner>
+where
+    Inner: GrindingChallenger,
+{
+    type Witness = Inner::Witness;
+
+    fn grind(&mut self, bits: usize) -> Self::Witness {
+        self.inner.grind(bits)
+    }
+}
+
+impl<F, P, const WIDTH: usize, const RATE: usize> CanObserveLabel
+    for DuplexChallenger<F, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    fn observe_label(&mut self, label: &[u8]) {
+        self.observe(F::from_usize(label.len()));
+        for &byte in label {
+            self.observe(F::from_u8(byte));
+        }
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanObserveLabel
+    for MultiField32Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe_label(&mut self, label: &[u8]) {
+        self.observe(F::from_usize(label.len()));
+        for &byte in label {
+            self.observe(F::from_u8(byte));
+        }
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanObserveLabel
+    for MultiField64Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe_label(&mut self, label: &[u8]) {
+        self.observe(F::from_usize(label.len()));
+        for &byte in label {
+            self.observe(F::from_u8(byte));
+        }
+    }
+}
+
+impl<H, const OUT_LEN: usize> CanObserveLabel for HashChallenger<u8, H, OUT_LEN>
+where
+    H: CryptographicHasher<u8, [u8; OUT_LEN]>,
+{
+    fn observe_label(&mut self, label: &[u8]) {
+        self.observe_slice(&(label.len() as u64).to_le_bytes());
+        self.observe_slice(label);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_field::PrimeCharacteristicRing;
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::*;
+
+    type F = BabyBear;
+    type Perm = Poseidon2BabyBear<16>;
+    type Challenger = LabeledChallenger<DuplexChallenger<F, Perm, 16, 8>>;
+
+    fn challenger(protocol_label: &[u8]) -> Challenger {
+        let perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+        LabeledChallenger::new(DuplexChallenger::new(perm), protocol_label)
+    }
+
+    #[test]
+    fn labels_separate_transcripts() {
+        let sample = |labels: &[&[u8]]| {
+            let mut challenger = challenger(b"test");
+            for label in labels {
+                challenger.observe_labeled(label, F::ONE);
+            }
+            challenger.sample_labeled::<F>(b"challenge")
+        };
+
+        assert_eq!(sample(&[b"a", b"b"]), sample(&[b"a", b"b"]));
+        assert_ne!(sample(&[b"a", b"b"]), sample(&[b"b", b"a"]));
+        // Length prefixes keep label boundaries unambiguous.
+        assert_ne!(sample(&[b"ab", b""]), sample(&[b"a", b"b"]));
+    }
+
+    #[test]
+    fn protocol_label_separates_transcripts() {
+        let mut first = challenger(b"first");
+        let mut second = challenger(b"second");
+        assert_ne!(
+            CanSample::<F>::sample(&mut first),
+            CanSample::<F>::sample(&mut second)
+        );
+    }
+
+    #[test]
+    fn resumed_transcript_samples_identically() {
+        let mut challenger = challenger(b"test");
+        challenger.observe_labeled(b"preamble", F::ONE);
+
+        let bytes = postcard::to_allocvec(&challenger.inner().state()).unwrap();
+        let state = postcard::from_bytes(&bytes).unwrap();
+        let perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+        let mut resumed =
+            LabeledChallenger::resume(DuplexChallenger::restore(perm, state).unwrap());
+
+        for chal in [&mut challenger, &mut resumed] {
+            chal.observe_labeled(b"tail", F::TWO);
+        }
+        assert_eq!(
+            challenger.sample_labeled::<F>(b"challenge"),
+            resumed.sample_labeled::<F>(b"challenge")
+        );
+    }
+}
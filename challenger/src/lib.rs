@@ -7,8 +7,11 @@ extern crate alloc;
 mod duplex_challenger;
 mod grinding_challenger;
 mod hash_challenger;
+mod labeled_challenger;
 mod multi_field_challenger;
+mod recording_challenger;
 mod serializing_challenger;
+mod state_error;
 
 use alloc::vec::Vec;
 use core::array;
@@ -16,9 +19,12 @@ use core::array;
 pub use duplex_challenger::*;
 pub use grinding_challenger::*;
 pub use hash_challenger::*;
+pub use labeled_challenger::*;
 pub use multi_field_challenger::*;
 use p3_field::{BasedVectorSpace, Field};
+pub use recording_challenger::*;
 pub use serializing_challenger::*;
+pub use state_error::*;
 
 pub trait CanObserve<T> {
     fn observe(&mut self, value: T);
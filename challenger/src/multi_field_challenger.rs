@@ -2,10 +2,14 @@ use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 
-use p3_field::{BasedVectorSpace, Field, PrimeField, PrimeField32, reduce_32, split_32};
-use p3_symmetric::{CryptographicPermutation, Hash};
+use p3_field::{
+    BasedVectorSpace, Field, PrimeField, PrimeField32, PrimeField64, reduce_32, reduce_64,
+    split_32, split_64,
+};
+use p3_symmetric::{CryptographicPermutation, Hash, MerkleCap};
+use serde::{Deserialize, Serialize};
 
-use crate::{CanObserve, CanSample, CanSampleBits, FieldChallenger};
+use crate::{CanObserve, CanSample, CanSampleBits, ChallengerStateError, FieldChallenger};
 
 /// A challenger that operates natively on PF but produces challenges of F: PrimeField32.
 ///
@@ -46,6 +50,63 @@ where
             num_f_elms,
         })
     }
+
+    /// Returns a snapshot of the transcript so far, which can be persisted and later resumed with
+    /// `restore`.
+    pub fn state(&self) -> MultiField32ChallengerState<F, PF> {
+        MultiField32ChallengerState {
+            sponge_state: self.sponge_state.to_vec(),
+            input_buffer: self.input_buffer.clone(),
+            output_buffer: self.output_buffer.clone(),
+        }
+    }
+
+    /// Resumes a transcript from a snapshot taken with `state`.
+    ///
+    /// Returns an error if the snapshot doesn't fit a challenger with this `WIDTH` and `RATE`.
+    pub fn restore(
+        permutation: P,
+        state: MultiField32ChallengerState<F, PF>,
+    ) -> Result<Self, ChallengerStateError> {
+        let mut challenger =
+            Self::new(permutation).map_err(ChallengerStateError::UnsupportedFields)?;
+        let MultiField32ChallengerState {
+            sponge_state,
+            input_buffer,
+            output_buffer,
+        } = state;
+        let max_input_len = challenger.num_f_elms * RATE;
+        if input_buffer.len() >= max_input_len {
+            return Err(ChallengerStateError::InputBufferTooLong {
+                len: input_buffer.len(),
+                limit: max_input_len,
+            });
+        }
+        let max_output_len = challenger.num_f_elms * WIDTH;
+        if output_buffer.len() > max_output_len {
+            return Err(ChallengerStateError::OutputBufferTooLong {
+                len: output_buffer.len(),
+                max: max_output_len,
+            });
+        }
+        challenger.sponge_state = sponge_state.try_into().map_err(|sponge_state: Vec<PF>| {
+            ChallengerStateError::WrongSpongeStateLength {
+                expected: WIDTH,
+                actual: sponge_state.len(),
+            }
+        })?;
+        challenger.input_buffer = input_buffer;
+        challenger.output_buffer = output_buffer;
+        Ok(challenger)
+    }
+}
+
+/// A serializable snapshot of a `MultiField32Challenger`'s transcript, without its permutation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiField32ChallengerState<F, PF> {
+    pub sponge_state: Vec<PF>,
+    pub input_buffer: Vec<F>,
+    pub output_buffer: Vec<F>,
 }
 
 impl<F, PF, P, const WIDTH: usize, const RATE: usize> MultiField32Challenger<F, PF, P, WIDTH, RATE>
@@ -134,6 +195,20 @@ where
     }
 }
 
+impl<F, PF, const N: usize, P, const WIDTH: usize, const RATE: usize>
+    CanObserve<MerkleCap<F, PF, N>> for MultiField32Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, cap: MerkleCap<F, PF, N>) {
+        for digest in cap {
+            self.observe(digest);
+        }
+    }
+}
+
 // for TrivialPcs
 impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanObserve<Vec<Vec<F>>>
     for MultiField32Challenger<F, PF, P, WIDTH, RATE>
@@ -189,3 +264,327 @@ where
         rand_usize & ((1 << bits) - 1)
     }
 }
+
+/// A challenger that operates natively on PF but produces challenges of F: PrimeField64.
+///
+/// Used for verifying STARKs over 64-bit fields such as Goldilocks in SNARKs, e.g. with a BN254
+/// Poseidon2 transcript. Each PF element absorbs as many F elements as fit injectively in a
+/// 2^64-base decomposition, and squeezes `F` elements from 2^128-base digits, so PF must have more
+/// than 128 bits.
+///
+/// SAFETY: Samples are actually random in [0, 2^128) and then reduced to be in F, so each one has
+/// a bias of roughly 1/2^64.
+#[derive(Clone, Debug)]
+pub struct MultiField64Challenger<F, PF, P, const WIDTH: usize, const RATE: usize>
+where
+    F: PrimeField64,
+    PF: Field,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    sponge_state: [PF; WIDTH],
+    input_buffer: Vec<F>,
+    output_buffer: Vec<F>,
+    permutation: P,
+    /// The number of F elements absorbed into each PF element.
+    num_f_elms: usize,
+    /// The number of F elements squeezed from each PF element.
+    num_samples: usize,
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> MultiField64Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    PF: Field,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    pub fn new(permutation: P) -> Result<Self, String> {
+        if F::order() >= PF::order() {
+            return Err(String::from("F::order() must be less than PF::order()"));
+        }
+        if PF::bits() <= 128 {
+            return Err(String::from("PF must have more than 128 bits"));
+        }
+        Ok(Self {
+            sponge_state: [PF::default(); WIDTH],
+            input_buffer: vec![],
+            output_buffer: vec![],
+            permutation,
+            num_f_elms: (PF::bits() - 1) / 64,
+            num_samples: (PF::bits() - 1) / 128,
+        })
+    }
+
+    /// Returns a snapshot of the transcript so far, which can be persisted and later resumed with
+    /// `restore`.
+    pub fn state(&self) -> MultiField64ChallengerState<F, PF> {
+        MultiField64ChallengerState {
+            sponge_state: self.sponge_state.to_vec(),
+            input_buffer: self.input_buffer.clone(),
+            output_buffer: self.output_buffer.clone(),
+        }
+    }
+
+    /// Resumes a transcript from a snapshot taken with `state`.
+    ///
+    /// Returns an error if the snapshot doesn't fit a challenger with this `WIDTH` and `RATE`.
+    pub fn restore(
+        permutation: P,
+        state: MultiField64ChallengerState<F, PF>,
+    ) -> Result<Self, ChallengerStateError> {
+        let mut challenger =
+            Self::new(permutation).map_err(ChallengerStateError::UnsupportedFields)?;
+        let MultiField64ChallengerState {
+            sponge_state,
+            input_buffer,
+            output_buffer,
+        } = state;
+        let max_input_len = challenger.num_f_elms * RATE;
+        if input_buffer.len() >= max_input_len {
+            return Err(ChallengerStateError::InputBufferTooLong {
+                len: input_buffer.len(),
+                limit: max_input_len,
+            });
+        }
+        let max_output_len = challenger.num_samples * RATE;
+        if output_buffer.len() > max_output_len {
+            return Err(ChallengerStateError::OutputBufferTooLong {
+                len: output_buffer.len(),
+                max: max_output_len,
+            });
+        }
+        challenger.sponge_state = sponge_state.try_into().map_err(|sponge_state: Vec<PF>| {
+            ChallengerStateError::WrongSpongeStateLength {
+                expected: WIDTH,
+                actual: sponge_state.len(),
+            }
+        })?;
+        challenger.input_buffer = input_buffer;
+        challenger.output_buffer = output_buffer;
+        Ok(challenger)
+    }
+}
+
+/// A serializable snapshot of a `MultiField64Challenger`'s transcript, without its permutation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiField64ChallengerState<F, PF> {
+    pub sponge_state: Vec<PF>,
+    pub input_buffer: Vec<F>,
+    pub output_buffer: Vec<F>,
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> MultiField64Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn duplexing(&mut self) {
+        assert!(self.input_buffer.len() <= self.num_f_elms * RATE);
+
+        for (i, f_chunk) in self.input_buffer.chunks(self.num_f_elms).enumerate() {
+            self.sponge_state[i] = reduce_64(f_chunk);
+        }
+        self.input_buffer.clear();
+
+        // Apply the permutation.
+        self.permutation.permute_mut(&mut self.sponge_state);
+
+        self.output_buffer.clear();
+        for &pf_val in &self.sponge_state[..RATE] {
+            self.output_buffer
+                .extend(split_64::<PF, F>(pf_val, self.num_samples));
+        }
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> FieldChallenger<F>
+    for MultiField64Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanObserve<F>
+    for MultiField64Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, value: F) {
+        // Any buffered output is now invalid.
+        self.output_buffer.clear();
+
+        self.input_buffer.push(value);
+
+        if self.input_buffer.len() == self.num_f_elms * RATE {
+            self.duplexing();
+        }
+    }
+}
+
+impl<F, PF, const N: usize, P, const WIDTH: usize, const RATE: usize> CanObserve<[F; N]>
+    for MultiField64Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, values: [F; N]) {
+        for value in values {
+            self.observe(value);
+        }
+    }
+}
+
+impl<F, PF, const N: usize, P, const WIDTH: usize, const RATE: usize> CanObserve<Hash<F, PF, N>>
+    for MultiField64Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, values: Hash<F, PF, N>) {
+        // Observe each digest element as 32-bit limbs, which F holds exactly, so that the whole
+        // digest is bound to the transcript.
+        let num_limbs = PF::bits().div_ceil(32);
+        for pf_val in values {
+            let mut limbs = pf_val.as_canonical_biguint().to_u32_digits();
+            limbs.resize(num_limbs, 0);
+            for limb in limbs {
+                self.observe(F::from_u32(limb));
+            }
+        }
+    }
+}
+
+impl<F, PF, const N: usize, P, const WIDTH: usize, const RATE: usize>
+    CanObserve<MerkleCap<F, PF, N>> for MultiField64Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, cap: MerkleCap<F, PF, N>) {
+        for digest in cap {
+            self.observe(digest);
+        }
+    }
+}
+
+// for TrivialPcs
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanObserve<Vec<Vec<F>>>
+    for MultiField64Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, valuess: Vec<Vec<F>>) {
+        for values in valuess {
+            for value in values {
+                self.observe(value);
+            }
+        }
+    }
+}
+
+impl<F, EF, PF, P, const WIDTH: usize, const RATE: usize> CanSample<EF>
+    for MultiField64Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    EF: BasedVectorSpace<F>,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn sample(&mut self) -> EF {
+        EF::from_basis_coefficients_fn(|_| {
+            // If we have buffered inputs, we must perform a duplexing so that the challenge will
+            // reflect them. Or if we've run out of outputs, we must perform a duplexing to get more.
+            if !self.input_buffer.is_empty() || self.output_buffer.is_empty() {
+                self.duplexing();
+            }
+
+            self.output_buffer
+                .pop()
+                .expect("Output buffer should be non-empty")
+        })
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanSampleBits<usize>
+    for MultiField64Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn sample_bits(&mut self, bits: usize) -> usize {
+        assert!(bits < (usize::BITS as usize));
+        assert!((1 << bits) < F::ORDER_U64);
+        let rand_f: F = self.sample();
+        let rand_usize = rand_f.as_canonical_u64() as usize;
+        rand_usize & ((1 << bits) - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::PrimeCharacteristicRing;
+    use p3_goldilocks::{Goldilocks, Poseidon2Goldilocks};
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::*;
+
+    type F = BabyBear;
+    type PF = Goldilocks;
+    type Perm = Poseidon2Goldilocks<8>;
+    type Challenger = MultiField32Challenger<F, PF, Perm, 8, 4>;
+
+    fn perm() -> Perm {
+        Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1))
+    }
+
+    #[test]
+    fn resumed_transcript_samples_identically() {
+        let mut challenger = Challenger::new(perm()).unwrap();
+
+        // A shared preamble, leaving both buffers non-empty.
+        (0..7).for_each(|element| challenger.observe(F::from_u8(element)));
+        let _: F = challenger.sample();
+        challenger.observe(F::from_u8(42));
+
+        let bytes = postcard::to_allocvec(&challenger.state()).unwrap();
+        let state = postcard::from_bytes(&bytes).unwrap();
+        let mut resumed = Challenger::restore(perm(), state).unwrap();
+
+        for chal in [&mut challenger, &mut resumed] {
+            (0..10).for_each(|element| chal.observe(F::from_u8(element)));
+        }
+        assert_eq!(
+            CanSample::<F>::sample_vec(&mut challenger, 10),
+            CanSample::<F>::sample_vec(&mut resumed, 10)
+        );
+        assert_eq!(challenger.sample_bits(10), resumed.sample_bits(10));
+    }
+
+    #[test]
+    fn restore_rejects_malformed_state() {
+        let state = Challenger::new(perm()).unwrap().state();
+
+        let mut short_sponge = state.clone();
+        short_sponge.sponge_state.pop();
+        let mut full_input = state.clone();
+        full_input.input_buffer = vec![F::ONE; 4];
+        let mut long_output = state;
+        long_output.output_buffer = vec![F::ONE; 9];
+
+        for state in [short_sponge, full_input, long_output] {
+            assert!(Challenger::restore(perm(), state).is_err());
+        }
+    }
+}
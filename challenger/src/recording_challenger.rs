@@ -0,0 +1,312 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::any::type_name;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::iter::zip;
+use core::panic::Location;
+
+use p3_field::{BasedVectorSpace, Field};
+
+use crate::{
+    CanObserve, CanObserveLabel, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger,
+};
+
+/// The kind of a recorded transcript operation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptOp {
+    Observe,
+    Sample,
+    SampleBits,
+    /// Producing or checking a proof-of-work witness. Both are recorded the same way, so that a
+    /// prover's `grind` lines up with the verifier's `check_witness`.
+    ProofOfWork,
+    Label,
+}
+
+/// One operation on a recorded transcript.
+#[derive(Clone, Debug)]
+pub struct TranscriptEvent {
+    pub op: TranscriptOp,
+    /// The type that was observed or sampled.
+    pub type_name: &'static str,
+    /// The `Debug` representation of the observed or sampled value.
+    pub value: String,
+    /// Where the operation was invoked.
+    pub location: &'static Location<'static>,
+}
+
+impl TranscriptEvent {
+    /// Whether two events performed the same operation on the same value, regardless of where
+    /// they were invoked.
+    pub fn matches(&self, other: &Self) -> bool {
+        self.op == other.op && self.type_name == other.type_name && self.value == other.value
+    }
+}
+
+impl Display for TranscriptEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} {}: {} at {}",
+            self.op, self.type_name, self.value, self.location
+        )
+    }
+}
+
+/// Wraps a challenger and records every operation on it, for debugging transcripts that diverge
+/// between a prover and a verifier.
+///
+/// Record both sides, then compare the logs with [`diff_transcripts`] to find the first operation
+/// where they disagree.
+#[derive(Clone, Debug)]
+pub struct RecordingChallenger<Inner> {
+    inner: Inner,
+    log: Vec<TranscriptEvent>,
+}
+
+impl<Inner> RecordingChallenger<Inner> {
+    pub const fn new(inner: Inner) -> Self {
+        Self {
+            inner,
+            log: Vec::new(),
+        }
+    }
+
+    pub fn log(&self) -> &[TranscriptEvent] {
+        &self.log
+    }
+
+    pub fn into_parts(self) -> (Inner, Vec<TranscriptEvent>) {
+        (self.inner, self.log)
+    }
+
+    #[track_caller]
+    fn record<T: Debug>(&mut self, op: TranscriptOp, value: &T) {
+        self.log.push(TranscriptEvent {
+            op,
+            type_name: type_name::<T>(),
+            value: format!("{value:?}"),
+            location: Location::caller(),
+        });
+    }
+}
+
+impl<Inner, T> CanObserve<T> for RecordingChallenger<Inner>
+where
+    Inner: CanObserve<T>,
+    T: Debug,
+{
+    #[track_caller]
+    fn observe(&mut self, value: T) {
+        self.record(TranscriptOp::Observe, &value);
+        self.inner.observe(value);
+    }
+
+    #[track_caller]
+    fn observe_slice(&mut self, values: &[T])
+    where
+        T: Clone,
+    {
+        for value in values {
+            self.record(TranscriptOp::Observe, value);
+        }
+        self.inner.observe_slice(values);
+    }
+}
+
+impl<Inner, T> CanSample<T> for RecordingChallenger<Inner>
+where
+    Inner: CanSample<T>,
+    T: Debug,
+{
+    #[track_caller]
+    fn sample(&mut self) -> T {
+        let value = self.inner.sample();
+        self.record(TranscriptOp::Sample, &value);
+        value
+    }
+
+    #[track_caller]
+    fn sample_array<const N: usize>(&mut self) -> [T; N] {
+        core::array::from_fn(|_| self.sample())
+    }
+
+    #[track_caller]
+    fn sample_vec(&mut self, n: usize) -> Vec<T> {
+        (0..n).map(|_| self.sample()).collect()
+    }
+}
+
+impl<Inner, T> CanSampleBits<T> for RecordingChallenger<Inner>
+where
+    Inner: CanSampleBits<T>,
+    T: Debug,
+{
+    #[track_caller]
+    fn sample_bits(&mut self, bits: usize) -> T {
+        let value = self.inner.sample_bits(bits);
+        self.record(TranscriptOp::SampleBits, &(bits, &value));
+        value
+    }
+}
+
+impl<Inner, F> FieldChallenger<F> for RecordingChallenger<Inner>
+where
+    Inner: FieldChallenger<F>,
+    F: Field,
+{
+    #[track_caller]
+    fn observe_algebra_element<A: BasedVectorSpace<F>>(&mut self, alg_elem: A) {
+        self.observe_slice(alg_elem.as_basis_coefficients_slice());
+    }
+
+    #[track_caller]
+    fn sample_algebra_element<A: BasedVectorSpace<F>>(&mut self) -> A {
+        let vec = self.sample_vec(A::DIMENSION);
+        A::from_basis_coefficients_slice(&vec)
+    }
+}
+
+impl<Inner> GrindingChallenger for RecordingChallenger<Inner>
+where
+    Inner: GrindingChallenger,
+{
+    type Witness = Inner::Witness;
+
+    #[track_caller]
+    fn grind(&mut self, bits: usize) -> Self::Witness {
+        let witness = self.inner.grind(bits);
+        self.record(TranscriptOp::ProofOfWork, &(bits, witness));
+        witness
+    }
+
+    #[track_caller]
+    fn check_witness(&mut self, bits: usize, witness: Self::Witness) -> bool {
+        self.record(TranscriptOp::ProofOfWork, &(bits, witness));
+        self.inner.check_witness(bits, witness)
+    }
+}
+
+impl<Inner: CanObserveLabel> CanObserveLabel for RecordingChallenger<Inner> {
+    #[track_caller]
+    fn observe_label(&mut self, label: &[u8]) {
+        self.record(TranscriptOp::Label, &String::from_utf8_lossy(label));
+        self.inner.observe_label(label);
+    }
+}
+
+/// The first point at which two transcripts disagree.
+#[derive(Clone, Debug)]
+pub struct TranscriptDivergence {
+    /// The index of the first differing operation.
+    pub index: usize,
+    /// The operation in the first transcript, or `None` if it ended before `index`.
+    pub left: Option<TranscriptEvent>,
+    /// The operation in the second transcript, or `None` if it ended before `index`.
+    pub right: Option<TranscriptEvent>,
+}
+
+impl Display for TranscriptDivergence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "transcripts diverge at operation {}:", self.index)?;
+        match &self.left {
+            Some(event) => writeln!(f, "  left:  {event}")?,
+            None => writeln!(f, "  left:  <end of transcript>")?,
+        }
+        match &self.right {
+            Some(event) => write!(f, "  right: {event}"),
+            None => write!(f, "  right: <end of transcript>"),
+        }
+    }
+}
+
+/// Replays two recorded transcripts side by side and returns the first operation where they
+/// differ, or `None` if they are identical.
+///
+/// Call sites are not compared, since a prover and a verifier reach the same operation from
+/// different places; they are reported so the divergence can be located.
+pub fn diff_transcripts(
+    left: &[TranscriptEvent],
+    right: &[TranscriptEvent],
+) -> Option<TranscriptDivergence> {
+    if let Some(index) = zip(left, right).position(|(l, r)| !l.matches(r)) {
+        return Some(TranscriptDivergence {
+            index,
+            left: Some(left[index].clone()),
+            right: Some(right[index].clone()),
+        });
+    }
+    let index = left.len().min(right.len());
+    (left.len() != right.len()).then(|| TranscriptDivergence {
+        index,
+        left: left.get(index).cloned(),
+        right: right.get(index).cloned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_field::PrimeCharacteristicRing;
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::*;
+    use crate::DuplexChallenger;
+
+    type F = BabyBear;
+    type Perm = Poseidon2BabyBear<16>;
+    type Challenger = RecordingChallenger<DuplexChallenger<F, Perm, 16, 8>>;
+
+    fn challenger() -> Challenger {
+        let perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+        RecordingChallenger::new(DuplexChallenger::new(perm))
+    }
+
+    #[test]
+    fn identical_transcripts_do_not_diverge() {
+        let mut prover = challenger();
+        prover.observe(F::ONE);
+        let witness = prover.grind(4);
+        let zeta: F = prover.sample();
+
+        let mut verifier = challenger();
+        verifier.observe(F::ONE);
+        assert!(verifier.check_witness(4, witness));
+        assert_eq!(CanSample::<F>::sample(&mut verifier), zeta);
+
+        assert!(diff_transcripts(prover.log(), verifier.log()).is_none());
+    }
+
+    #[test]
+    fn reports_first_divergence() {
+        let mut prover = challenger();
+        prover.observe(F::ONE);
+        let _: F = prover.sample();
+
+        let mut verifier = challenger();
+        verifier.observe(F::TWO);
+        let _: F = verifier.sample();
+
+        let divergence = diff_transcripts(prover.log(), verifier.log()).unwrap();
+        assert_eq!(divergence.index, 0);
+        assert_eq!(divergence.left.unwrap().value, format!("{:?}", F::ONE));
+        assert_eq!(divergence.right.unwrap().location.file(), file!());
+    }
+
+    #[test]
+    fn reports_truncated_transcript() {
+        let mut prover = challenger();
+        prover.observe(F::ONE);
+        let _: F = prover.sample();
+
+        let mut verifier = challenger();
+        verifier.observe(F::ONE);
+
+        let divergence = diff_transcripts(prover.log(), verifier.log()).unwrap();
+        assert_eq!(divergence.index, 1);
+        assert_eq!(divergence.left.unwrap().op, TranscriptOp::Sample);
+        assert!(divergence.right.is_none());
+    }
+}
@@ -3,12 +3,13 @@ use core::marker::PhantomData;
 
 use p3_field::{BasedVectorSpace, PrimeField32, PrimeField64};
 use p3_maybe_rayon::prelude::*;
-use p3_symmetric::{CryptographicHasher, Hash};
+use p3_symmetric::{CryptographicHasher, Hash, MerkleCap};
 use p3_util::log2_ceil_u64;
 use tracing::instrument;
 
 use crate::{
-    CanObserve, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger, HashChallenger,
+    CanObserve, CanObserveLabel, CanSample, CanSampleBits, ChallengerStateError, FieldChallenger,
+    GrindingChallenger, HashChallenger, HashChallengerState,
 };
 
 /// Given a challenger that can observe and sample bytes, produces a challenger that is able to
@@ -58,6 +59,22 @@ where
     pub const fn from_hasher(initial_state: Vec<u8>, hasher: H) -> Self {
         Self::new(HashChallenger::new(initial_state, hasher))
     }
+
+    /// Returns a snapshot of the transcript so far, which can be persisted and later resumed with
+    /// `restore`.
+    pub fn state(&self) -> HashChallengerState<u8> {
+        self.inner.state()
+    }
+
+    /// Resumes a transcript from a snapshot taken with `state`.
+    ///
+    /// Returns an error if the snapshot doesn't fit the inner `HashChallenger`.
+    pub fn restore(
+        hasher: H,
+        state: HashChallengerState<u8>,
+    ) -> Result<Self, ChallengerStateError> {
+        HashChallenger::restore(hasher, state).map(Self::new)
+    }
 }
 
 impl<F: PrimeField32, Inner: CanObserve<u8>> CanObserve<F> for SerializingChallenger32<F, Inner> {
@@ -87,6 +104,18 @@ impl<F: PrimeField32, const N: usize, Inner: CanObserve<u8>> CanObserve<Hash<F,
     }
 }
 
+impl<F: PrimeField32, W, const N: usize, Inner: CanObserve<u8>> CanObserve<MerkleCap<F, W, N>>
+    for SerializingChallenger32<F, Inner>
+where
+    Self: CanObserve<Hash<F, W, N>>,
+{
+    fn observe(&mut self, cap: MerkleCap<F, W, N>) {
+        for digest in cap {
+            self.observe(digest);
+        }
+    }
+}
+
 impl<F, EF, Inner> CanSample<EF> for SerializingChallenger32<F, Inner>
 where
     F: PrimeField32,
@@ -151,6 +180,14 @@ where
     }
 }
 
+impl<F: PrimeField32, Inner: CanObserve<u8>> CanObserveLabel for SerializingChallenger32<F, Inner> {
+    fn observe_label(&mut self, label: &[u8]) {
+        self.inner
+            .observe_slice(&(label.len() as u64).to_le_bytes());
+        self.inner.observe_slice(label);
+    }
+}
+
 impl<F, Inner> FieldChallenger<F> for SerializingChallenger32<F, Inner>
 where
     F: PrimeField32,
@@ -175,6 +212,22 @@ where
     pub const fn from_hasher(initial_state: Vec<u8>, hasher: H) -> Self {
         Self::new(HashChallenger::new(initial_state, hasher))
     }
+
+    /// Returns a snapshot of the transcript so far, which can be persisted and later resumed with
+    /// `restore`.
+    pub fn state(&self) -> HashChallengerState<u8> {
+        self.inner.state()
+    }
+
+    /// Resumes a transcript from a snapshot taken with `state`.
+    ///
+    /// Returns an error if the snapshot doesn't fit the inner `HashChallenger`.
+    pub fn restore(
+        hasher: H,
+        state: HashChallengerState<u8>,
+    ) -> Result<Self, ChallengerStateError> {
+        HashChallenger::restore(hasher, state).map(Self::new)
+    }
 }
 
 impl<F: PrimeField64, Inner: CanObserve<u8>> CanObserve<F> for SerializingChallenger64<F, Inner> {
@@ -204,6 +257,18 @@ impl<F: PrimeField64, const N: usize, Inner: CanObserve<u8>> CanObserve<Hash<F,
     }
 }
 
+impl<F: PrimeField64, W, const N: usize, Inner: CanObserve<u8>> CanObserve<MerkleCap<F, W, N>>
+    for SerializingChallenger64<F, Inner>
+where
+    Self: CanObserve<Hash<F, W, N>>,
+{
+    fn observe(&mut self, cap: MerkleCap<F, W, N>) {
+        for digest in cap {
+            self.observe(digest);
+        }
+    }
+}
+
 impl<F, EF, Inner> CanSample<EF> for SerializingChallenger64<F, Inner>
 where
     F: PrimeField64,
@@ -269,9 +334,96 @@ where
     }
 }
 
+impl<F: PrimeField64, Inner: CanObserve<u8>> CanObserveLabel for SerializingChallenger64<F, Inner> {
+    fn observe_label(&mut self, label: &[u8]) {
+        self.inner
+            .observe_slice(&(label.len() as u64).to_le_bytes());
+        self.inner.observe_slice(label);
+    }
+}
+
 impl<F, Inner> FieldChallenger<F> for SerializingChallenger64<F, Inner>
 where
     F: PrimeField64,
     Inner: CanSample<u8> + CanObserve<u8> + Clone + Send + Sync,
 {
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::PrimeCharacteristicRing;
+    use p3_goldilocks::Goldilocks;
+
+    use super::*;
+
+    /// A simple, non-cryptographic byte hasher which mixes every input byte into its output.
+    #[derive(Clone)]
+    struct TestHasher;
+
+    impl CryptographicHasher<u8, [u8; 32]> for TestHasher {
+        fn hash_iter<I>(&self, input: I) -> [u8; 32]
+        where
+            I: IntoIterator<Item = u8>,
+        {
+            let mut state = [0u8; 32];
+            for byte in input {
+                let carry = state[31];
+                state.copy_within(..31, 1);
+                state[0] = carry.rotate_left(3) ^ byte;
+                state[7] = state[7].wrapping_add(carry);
+            }
+            state
+        }
+    }
+
+    #[test]
+    fn resumed_transcript_samples_identically_32() {
+        type Challenger = SerializingChallenger32<BabyBear, HashChallenger<u8, TestHasher, 32>>;
+        let mut challenger = Challenger::from_hasher(vec![], TestHasher);
+
+        // A shared preamble, leaving both buffers non-empty.
+        (0..7).for_each(|element| challenger.observe(BabyBear::from_u8(element)));
+        let _: BabyBear = challenger.sample();
+        challenger.observe(BabyBear::from_u8(42));
+
+        let bytes = postcard::to_allocvec(&challenger.state()).unwrap();
+        let state = postcard::from_bytes(&bytes).unwrap();
+        let mut resumed = Challenger::restore(TestHasher, state).unwrap();
+
+        for chal in [&mut challenger, &mut resumed] {
+            (0..10).for_each(|element| chal.observe(BabyBear::from_u8(element)));
+        }
+        assert_eq!(
+            CanSample::<BabyBear>::sample_vec(&mut challenger, 10),
+            CanSample::<BabyBear>::sample_vec(&mut resumed, 10)
+        );
+        assert_eq!(challenger.sample_bits(10), resumed.sample_bits(10));
+    }
+
+    #[test]
+    fn resumed_transcript_samples_identically_64() {
+        type Challenger = SerializingChallenger64<Goldilocks, HashChallenger<u8, TestHasher, 32>>;
+        let mut challenger = Challenger::from_hasher(vec![], TestHasher);
+
+        // A shared preamble, leaving both buffers non-empty.
+        (0..7).for_each(|element| challenger.observe(Goldilocks::from_u8(element)));
+        let _: Goldilocks = challenger.sample();
+        challenger.observe(Goldilocks::from_u8(42));
+
+        let bytes = postcard::to_allocvec(&challenger.state()).unwrap();
+        let state = postcard::from_bytes(&bytes).unwrap();
+        let mut resumed = Challenger::restore(TestHasher, state).unwrap();
+
+        for chal in [&mut challenger, &mut resumed] {
+            (0..10).for_each(|element| chal.observe(Goldilocks::from_u8(element)));
+        }
+        assert_eq!(
+            CanSample::<Goldilocks>::sample_vec(&mut challenger, 10),
+            CanSample::<Goldilocks>::sample_vec(&mut resumed, 10)
+        );
+        assert_eq!(challenger.sample_bits(10), resumed.sample_bits(10));
+    }
+}
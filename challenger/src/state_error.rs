@@ -0,0 +1,40 @@
+use alloc::string::String;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+
+/// Why a challenger couldn't resume a transcript from a snapshot of its state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChallengerStateError {
+    /// The input buffer holds `len` elements, but must hold fewer than `limit`, as a full buffer
+    /// is absorbed as soon as it fills up.
+    InputBufferTooLong { len: usize, limit: usize },
+    /// The output buffer holds `len` elements, more than the `max` that a single squeeze
+    /// produces.
+    OutputBufferTooLong { len: usize, max: usize },
+    /// The sponge state doesn't have one element for each lane of the permutation.
+    WrongSpongeStateLength { expected: usize, actual: usize },
+    /// The challenger can't be built over its fields at all, for the given reason.
+    UnsupportedFields(String),
+}
+
+impl Display for ChallengerStateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InputBufferTooLong { len, limit } => write!(
+                f,
+                "input buffer has {len} elements, but must have fewer than {limit}"
+            ),
+            Self::OutputBufferTooLong { len, max } => write!(
+                f,
+                "output buffer has {len} elements, but must have at most {max}"
+            ),
+            Self::WrongSpongeStateLength { expected, actual } => write!(
+                f,
+                "sponge state has {actual} elements, but must have {expected}"
+            ),
+            Self::UnsupportedFields(reason) => write!(f, "unsupported fields: {reason}"),
+        }
+    }
+}
+
+impl Error for ChallengerStateError {}
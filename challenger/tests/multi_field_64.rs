@@ -0,0 +1,133 @@
+use p3_bn254_fr::{Bn254Fr, Poseidon2Bn254};
+use p3_challenger::{
+    CanObserve, CanSample, CanSampleBits, GrindingChallenger, MultiField64Challenger,
+};
+use p3_commit::Mmcs;
+use p3_field::{PrimeCharacteristicRing, PrimeField, PrimeField64, reduce_64, split_64};
+use p3_goldilocks::Goldilocks;
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{
+    CryptographicHasher, MultiField64PaddingFreeSponge, Permutation, TruncatedPermutation,
+};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+type F = Goldilocks;
+type PF = Bn254Fr;
+type Perm = Poseidon2Bn254<3>;
+type Challenger = MultiField64Challenger<F, PF, Perm, 3, 2>;
+type Sponge = MultiField64PaddingFreeSponge<F, PF, Perm, 3, 2, 1>;
+type Compress = TruncatedPermutation<Perm, 2, 1, 3>;
+type ValMmcs = MerkleTreeMmcs<F, PF, Sponge, Compress, 1>;
+
+fn perm() -> Perm {
+    // We hard code the round numbers for Bn254Fr.
+    Perm::new_from_rng(8, 22, &mut SmallRng::seed_from_u64(1))
+}
+
+#[test]
+fn reduce_64_is_injective() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let vals: [F; 3] = rng.random();
+
+    let packed: PF = reduce_64(&vals);
+    let digits = packed.as_canonical_biguint().to_u64_digits();
+    let expected = vals.map(|val| val.as_canonical_u64());
+    assert_eq!(digits, expected);
+
+    // A value below 2^64 is its own low 2^128-base digit.
+    assert_eq!(split_64::<PF, F>(reduce_64(&vals[..1]), 1), vec![vals[0]]);
+}
+
+#[test]
+fn resumed_transcript_samples_identically() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut challenger = Challenger::new(perm()).unwrap();
+
+    // A shared preamble, leaving both buffers non-empty.
+    challenger.observe_slice(&rng.random::<[F; 7]>());
+    let _: F = challenger.sample();
+    challenger.observe(rng.random::<F>());
+
+    let bytes = postcard::to_allocvec(&challenger.state()).unwrap();
+    let state = postcard::from_bytes(&bytes).unwrap();
+    let mut resumed = Challenger::restore(perm(), state).unwrap();
+
+    let tail: [F; 10] = rng.random();
+    challenger.observe_slice(&tail);
+    resumed.observe_slice(&tail);
+    assert_eq!(
+        CanSample::<F>::sample_vec(&mut challenger, 10),
+        CanSample::<F>::sample_vec(&mut resumed, 10)
+    );
+}
+
+#[test]
+fn restore_rejects_malformed_state() {
+    let state = Challenger::new(perm()).unwrap().state();
+
+    // Each of the 2 rate cells absorbs 3 elements and yields 1 sample.
+    let mut short_sponge = state.clone();
+    short_sponge.sponge_state.pop();
+    let mut full_input = state.clone();
+    full_input.input_buffer = vec![F::ONE; 6];
+    let mut long_output = state;
+    long_output.output_buffer = vec![F::ONE; 3];
+
+    for state in [short_sponge, full_input, long_output] {
+        assert!(Challenger::restore(perm(), state).is_err());
+    }
+}
+
+#[test]
+fn grinding_witness_verifies() {
+    let mut challenger = Challenger::new(perm()).unwrap();
+    challenger.observe(F::from_u8(3));
+
+    let witness = challenger.clone().grind(8);
+    assert!(challenger.check_witness(8, witness));
+}
+
+#[test]
+fn sponge_absorbs_three_elements_per_cell() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let input: [F; 7] = rng.random();
+    let sponge = Sponge::new(perm()).unwrap();
+
+    // Six elements fill the rate of two cells; the seventh starts a second block.
+    let mut state = [PF::default(); 3];
+    state[0] = reduce_64(&input[..3]);
+    state[1] = reduce_64(&input[3..6]);
+    perm().permute_mut(&mut state);
+    state[0] = reduce_64(&input[6..]);
+    perm().permute_mut(&mut state);
+
+    assert_eq!(sponge.hash_iter(input), [state[0]]);
+}
+
+#[test]
+fn merkle_commitment_round_trip() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mmcs = ValMmcs::new(Sponge::new(perm()).unwrap(), Compress::new(perm()));
+
+    let mats = vec![
+        RowMajorMatrix::<F>::rand(&mut rng, 16, 5),
+        RowMajorMatrix::<F>::rand(&mut rng, 8, 3),
+    ];
+    let dims = mats.iter().map(|mat| mat.dimensions()).collect::<Vec<_>>();
+    let (commit, prover_data) = mmcs.commit(mats);
+
+    let mut p_challenger = Challenger::new(perm()).unwrap();
+    p_challenger.observe(commit.clone());
+    let index = p_challenger.sample_bits(4);
+
+    let mut v_challenger = Challenger::new(perm()).unwrap();
+    v_challenger.observe(commit.clone());
+    assert_eq!(v_challenger.sample_bits(4), index);
+
+    let (opened_values, proof) = mmcs.open_batch(index, &prover_data);
+    mmcs.verify_batch(&commit, &dims, index, &opened_values, &proof)
+        .expect("expected verification to succeed");
+}
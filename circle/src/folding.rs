@@ -5,7 +5,7 @@ use core::marker::PhantomData;
 use itertools::Itertools;
 use p3_commit::Mmcs;
 use p3_field::extension::ComplexExtendable;
-use p3_field::{ExtensionField, batch_multiplicative_inverse};
+use p3_field::{ExtensionField, Field, batch_multiplicative_inverse};
 use p3_fri::FriGenericConfig;
 use p3_matrix::Matrix;
 use p3_util::{log2_strict_usize, reverse_bits_len};
@@ -127,6 +127,23 @@ pub(crate) fn fold_x_row<F: ComplexExtendable, EF: ExtensionField<F>>(
     (sum + beta * diff).halve()
 }
 
+/// Returns the x-coordinate of the point at which the `index`-th evaluation of a circle FRI layer
+/// of size `2^log_height` is taken. Consecutive evaluations are folded together, and lie at
+/// opposite x-coordinates.
+pub(crate) fn fri_layer_x<F: ComplexExtendable>(index: usize, log_height: usize) -> F {
+    let t = CircleDomain::<F>::standard(log_height + 1)
+        .nth_x_twiddle(reverse_bits_len(index >> 1, log_height - 1));
+    if index & 1 == 0 { t } else { -t }
+}
+
+/// Evaluates the polynomial with coefficients `coeffs` at `x`.
+pub(crate) fn evaluate_poly<F: Field, EF: ExtensionField<F>>(coeffs: &[EF], x: F) -> EF {
+    coeffs
+        .iter()
+        .rev()
+        .fold(EF::ZERO, |acc, &coeff| acc * x + coeff)
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::iproduct;
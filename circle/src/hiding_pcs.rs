@@ -0,0 +1,361 @@
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::{
+    CommitRandomness, Mmcs, OpenedValues, Pcs, RngRandomness, SeededRandomness, StoredMatrix,
+};
+use p3_field::ExtensionField;
+use p3_field::extension::ComplexExtendable;
+use p3_fri::verifier::FriError;
+use p3_fri::{FriConfig, add_random_cols};
+use p3_matrix::Matrix;
+use p3_matrix::dense::{DenseMatrix, RowMajorMatrix};
+use p3_matrix::horizontally_truncated::HorizontallyTruncated;
+use p3_matrix::row_index_mapped::RowIndexMappedView;
+use rand::Rng;
+use rand::distr::{Distribution, StandardUniform};
+use rand_chacha::ChaCha20Rng;
+
+use crate::{CfftPerm, CircleDomain, CirclePcs, CirclePcsProof, InputError};
+
+/// A hiding circle PCS, the circle counterpart of `HidingFriPcs`. Both MMCSs must also be hiding,
+/// e.g. `MerkleTreeHidingMmcs`; this is not enforced at compile time so it's the user's
+/// responsibility to configure.
+///
+/// Each committed matrix is extended with `num_random_codewords` random columns, whose openings
+/// are kept out of the opened values and moved into the proof. The random codewords are drawn
+/// from the RNG `R` when built with `new`, or from a `ChaCha20Rng` derived from a secret seed for
+/// each commitment when built with `new_deterministic`. `S` is the source of these RNGs.
+#[derive(Debug)]
+pub struct HidingCirclePcs<Val: ComplexExtendable, InputMmcs, FriMmcs, R, S = RngRandomness<R>> {
+    inner: CirclePcs<Val, InputMmcs, FriMmcs>,
+    num_random_codewords: usize,
+    randomness: S,
+    _phantom: PhantomData<R>,
+}
+
+impl<Val: ComplexExtendable, InputMmcs, FriMmcs, R> HidingCirclePcs<Val, InputMmcs, FriMmcs, R> {
+    pub const fn new(
+        mmcs: InputMmcs,
+        fri_config: FriConfig<FriMmcs>,
+        num_random_codewords: usize,
+        rng: R,
+    ) -> Self {
+        Self::new_with_randomness(
+            mmcs,
+            fri_config,
+            num_random_codewords,
+            RngRandomness::new(rng),
+        )
+    }
+}
+
+impl<Val: ComplexExtendable, InputMmcs, FriMmcs>
+    HidingCirclePcs<Val, InputMmcs, FriMmcs, ChaCha20Rng, SeededRandomness>
+{
+    /// Like `new`, but derives the random codewords of each commitment from `seed` and the index
+    /// of the commitment. See `SeededRandomness`.
+    ///
+    /// The seed must be secret, independent of any seeds used by the MMCSs, and not reused for
+    /// another proof: proofs made with the same seed share their random codewords, which breaks
+    /// hiding.
+    pub fn new_deterministic(
+        mmcs: InputMmcs,
+        fri_config: FriConfig<FriMmcs>,
+        num_random_codewords: usize,
+        seed: [u8; 32],
+    ) -> Self {
+        Self::new_with_randomness(
+            mmcs,
+            fri_config,
+            num_random_codewords,
+            SeededRandomness::new(seed),
+        )
+    }
+}
+
+impl<Val: ComplexExtendable, InputMmcs, FriMmcs, R, S>
+    HidingCirclePcs<Val, InputMmcs, FriMmcs, R, S>
+{
+    pub const fn new_with_randomness(
+        mmcs: InputMmcs,
+        fri_config: FriConfig<FriMmcs>,
+        num_random_codewords: usize,
+        randomness: S,
+    ) -> Self {
+        Self {
+            inner: CirclePcs::new(mmcs, fri_config),
+            num_random_codewords,
+            randomness,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<Val, InputMmcs, FriMmcs, Challenge, Challenger, R, S> Pcs<Challenge, Challenger>
+    for HidingCirclePcs<Val, InputMmcs, FriMmcs, R, S>
+where
+    Val: ComplexExtendable,
+    StandardUniform: Distribution<Val>,
+    Challenge: ExtensionField<Val>,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<FriMmcs::Commitment>,
+    R: Rng + Send + Sync,
+    S: CommitRandomness<Rng = R>,
+{
+    type Domain = CircleDomain<Val>;
+    type Commitment = InputMmcs::Commitment;
+    type ProverData = InputMmcs::ProverData<StoredMatrix<Val, InputMmcs>>;
+    type EvaluationsOnDomain<'a> =
+        HorizontallyTruncated<Val, RowIndexMappedView<CfftPerm, DenseMatrix<Val, Cow<'a, [Val]>>>>;
+    /// The first item contains the openings of the random polynomials added by this wrapper.
+    /// The second item is the usual `CirclePcs` proof.
+    type Proof = (
+        OpenedValues<Challenge>,
+        CirclePcsProof<Val, Challenge, InputMmcs, FriMmcs, Challenger::Witness>,
+    );
+    type Error = FriError<FriMmcs::Error, InputError<InputMmcs::Error, FriMmcs::Error>>;
+
+    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
+        <CirclePcs<Val, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+            &self.inner, degree)
+    }
+
+    fn commit(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let randomized_evaluations = self.randomness.with_next_rng(|rng| {
+            evaluations
+                .into_iter()
+                .map(|(domain, mat)| {
+                    (
+                        domain,
+                        add_random_cols(mat, self.num_random_codewords, &mut *rng),
+                    )
+                })
+                .collect()
+        });
+        <CirclePcs<Val, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::commit(
+            &self.inner,
+            randomized_evaluations,
+        )
+    }
+
+    fn commit_coeffs(
+        &self,
+        coeffs: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        // Random columns are as random in coefficient form as in evaluation form.
+        let randomized_coeffs = self.randomness.with_next_rng(|rng| {
+            coeffs
+                .into_iter()
+                .map(|(domain, mat)| {
+                    (
+                        domain,
+                        add_random_cols(mat, self.num_random_codewords, &mut *rng),
+                    )
+                })
+                .collect()
+        });
+        <CirclePcs<Val, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::commit_coeffs(
+            &self.inner,
+            randomized_coeffs,
+        )
+    }
+
+    fn get_evaluations_on_domain<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+        domain: Self::Domain,
+    ) -> Self::EvaluationsOnDomain<'a> {
+        let inner_evals = <CirclePcs<Val, InputMmcs, FriMmcs> as Pcs<
+            Challenge,
+            Challenger,
+        >>::get_evaluations_on_domain(
+            &self.inner, prover_data, idx, domain
+        );
+        let inner_width = inner_evals.width();
+        // Truncate off the columns representing random codewords we added in `commit` above.
+        HorizontallyTruncated::new(inner_evals, inner_width - self.num_random_codewords)
+    }
+
+    fn open(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<
+                // points to open
+                Vec<Challenge>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        let (mut inner_opened_values, inner_proof) = self.inner.open(rounds, challenger);
+
+        // inner_opened_values includes opened values for the random codewords. Those should be
+        // hidden from our caller, so we split them off and store them in the proof.
+        let opened_values_rand = inner_opened_values
+            .iter_mut()
+            .map(|opened_values_for_round| {
+                opened_values_for_round
+                    .iter_mut()
+                    .map(|opened_values_for_mat| {
+                        opened_values_for_mat
+                            .iter_mut()
+                            .map(|opened_values_for_point| {
+                                let split =
+                                    opened_values_for_point.len() - self.num_random_codewords;
+                                opened_values_for_point.drain(split..).collect()
+                            })
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        (inner_opened_values, (opened_values_rand, inner_proof))
+    }
+
+    fn verify(
+        &self,
+        // For each round:
+        mut rounds: Vec<(
+            Self::Commitment,
+            // for each matrix:
+            Vec<(
+                // its domain,
+                Self::Domain,
+                // for each point:
+                Vec<(
+                    // the point,
+                    Challenge,
+                    // values at the point
+                    Vec<Challenge>,
+                )>,
+            )>,
+        )>,
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error> {
+        let (opened_values_for_rand_cws, inner_proof) = proof;
+        // Now we merge `opened_values_for_rand_cws` into the opened values in `rounds`, undoing
+        // the split that we did in `open`, to get a complete set of opened values for the inner PCS
+        // to check.
+        for (round, rand_round) in rounds.iter_mut().zip(opened_values_for_rand_cws) {
+            for (mat, rand_mat) in round.1.iter_mut().zip(rand_round) {
+                for (point, rand_point) in mat.1.iter_mut().zip(rand_mat) {
+                    point.1.extend(rand_point);
+                }
+            }
+        }
+        self.inner.verify(rounds, inner_proof, challenger)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_challenger::{HashChallenger, SerializingChallenger32};
+    use p3_commit::ExtensionMmcs;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_fri::create_test_fri_config;
+    use p3_keccak::Keccak256Hash;
+    use p3_merkle_tree::MerkleTreeHidingMmcs;
+    use p3_mersenne_31::Mersenne31;
+    use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::CircleEvaluations;
+
+    #[test]
+    fn hiding_circle_pcs() {
+        // Very simple pcs test. More rigorous tests in p3_fri/tests/pcs.
+
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+
+        type Val = Mersenne31;
+        type Challenge = BinomialExtensionField<Mersenne31, 3>;
+
+        type ByteHash = Keccak256Hash;
+        type FieldHash = SerializingHasher32<ByteHash>;
+        let byte_hash = ByteHash {};
+        let field_hash = FieldHash::new(byte_hash);
+
+        type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+        let compress = MyCompress::new(byte_hash);
+
+        type ValMmcs = MerkleTreeHidingMmcs<Val, u8, FieldHash, MyCompress, ChaCha20Rng, 32, 4>;
+        let val_mmcs = ValMmcs::new(field_hash, compress, ChaCha20Rng::seed_from_u64(1));
+
+        type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+        type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+
+        type Pcs = HidingCirclePcs<Val, ValMmcs, ChallengeMmcs, ChaCha20Rng>;
+
+        let log_n = 8;
+
+        for log_final_poly_len in 0..3 {
+            let fri_config = create_test_fri_config(challenge_mmcs.clone(), log_final_poly_len);
+            let pcs = Pcs::new(
+                val_mmcs.clone(),
+                fri_config,
+                2,
+                ChaCha20Rng::seed_from_u64(2),
+            );
+
+            let d = <Pcs as p3_commit::Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                &pcs,
+                1 << log_n,
+            );
+
+            let evals = RowMajorMatrix::rand(&mut rng, 1 << log_n, 3);
+
+            let (comm, data) = <Pcs as p3_commit::Pcs<Challenge, Challenger>>::commit(
+                &pcs,
+                vec![(d, evals.clone())],
+            );
+
+            // The random codewords are hidden from the caller.
+            let lde_domain = CircleDomain::standard(log_n + 1);
+            let on_domain =
+                <Pcs as p3_commit::Pcs<Challenge, Challenger>>::get_evaluations_on_domain(
+                    &pcs, &data, 0, lde_domain,
+                );
+            let expected = CircleEvaluations::from_natural_order(d, evals)
+                .extrapolate(lde_domain)
+                .to_natural_order();
+            assert_eq!(
+                on_domain.to_row_major_matrix(),
+                expected.to_row_major_matrix()
+            );
+
+            let zeta: Challenge = rng.random();
+
+            let mut chal = Challenger::from_hasher(vec![], byte_hash);
+            let (values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut chal);
+            assert_eq!(values[0][0][0].len(), 3);
+            assert_eq!(proof.0[0][0][0].len(), 2);
+
+            let mut chal = Challenger::from_hasher(vec![], byte_hash);
+            pcs.verify(
+                vec![(comm, vec![(d, vec![(zeta, values[0][0][0].clone())])])],
+                &proof,
+                &mut chal,
+            )
+            .expect("verify err");
+        }
+    }
+}
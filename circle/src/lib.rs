@@ -9,6 +9,7 @@ mod cfft;
 mod deep_quotient;
 mod domain;
 mod folding;
+mod hiding_pcs;
 mod ordering;
 mod pcs;
 mod point;
@@ -18,6 +19,7 @@ mod verifier;
 
 pub use cfft::*;
 pub use domain::*;
+pub use hiding_pcs::*;
 pub use ordering::*;
 pub use pcs::*;
 pub use proof::*;
@@ -2,15 +2,17 @@ use alloc::borrow::Cow;
 use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
 use core::marker::PhantomData;
 
 use itertools::{Itertools, izip};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
-use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace};
+use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace, StoredMatrix};
 use p3_field::extension::ComplexExtendable;
 use p3_field::{ExtensionField, Field};
-use p3_fri::FriConfig;
 use p3_fri::verifier::FriError;
+use p3_fri::{FriConfig, batch_index, claimed_batch_dims};
 use p3_matrix::dense::{DenseMatrix, RowMajorMatrix};
 use p3_matrix::row_index_mapped::RowIndexMappedView;
 use p3_matrix::{Dimensions, Matrix};
@@ -49,7 +51,8 @@ impl<Val: Field, InputMmcs, FriMmcs> CirclePcs<Val, InputMmcs, FriMmcs> {
 #[serde(bound = "")]
 pub struct BatchOpening<Val: Field, InputMmcs: Mmcs<Val>> {
     pub(crate) opened_values: Vec<Vec<Val>>,
-    pub(crate) opening_proof: <InputMmcs as Mmcs<Val>>::Proof,
+    /// Absent if the openings of all queries are proven together by a multiproof.
+    pub(crate) opening_proof: Option<<InputMmcs as Mmcs<Val>>::Proof>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -62,14 +65,54 @@ pub struct CircleInputProof<
 > {
     input_openings: Vec<BatchOpening<Val, InputMmcs>>,
     first_layer_siblings: Vec<Challenge>,
-    first_layer_proof: FriMmcs::Proof,
+    /// Absent if the first layer openings of all queries are proven together by a multiproof.
+    first_layer_proof: Option<FriMmcs::Proof>,
 }
 
+/// Why the inputs of a `CirclePcs` query were rejected.
 #[derive(Debug)]
 pub enum InputError<InputMmcsError, FriMmcsError> {
-    InputMmcsError(InputMmcsError),
+    /// The opening of the batch committed in the given round failed to verify.
+    InputMmcsError { round: usize, error: InputMmcsError },
+    /// The opening of the first, bivariate folding layer failed to verify.
     FirstLayerMmcsError(FriMmcsError),
-    InputShapeError,
+    /// The openings don't match the committed batches, or the matrices of the given round.
+    InputShapeError { round: Option<usize> },
+}
+
+impl<InputMmcsError: Display, FriMmcsError: Display> Display
+    for InputError<InputMmcsError, FriMmcsError>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InputMmcsError { round, error } => {
+                write!(f, "invalid opening of the batch of round {round}: {error}")
+            }
+            Self::FirstLayerMmcsError(error) => {
+                write!(f, "invalid opening of the first folding layer: {error}")
+            }
+            Self::InputShapeError { round: Some(round) } => {
+                write!(f, "openings don't match the matrices of round {round}")
+            }
+            Self::InputShapeError { round: None } => {
+                write!(f, "openings don't match the committed batches")
+            }
+        }
+    }
+}
+
+impl<InputMmcsError, FriMmcsError> Error for InputError<InputMmcsError, FriMmcsError>
+where
+    InputMmcsError: Error + 'static,
+    FriMmcsError: Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InputMmcsError { error, .. } => Some(error),
+            Self::FirstLayerMmcsError(error) => Some(error),
+            Self::InputShapeError { .. } => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -92,6 +135,18 @@ pub struct CirclePcsProof<
         Witness,
         CircleInputProof<Val, Challenge, InputMmcs, FriMmcs>,
     >,
+    /// When `FriConfig::use_multiproofs` is set, a multiproof for each committed batch which
+    /// covers the input openings of all queries. Otherwise, this is empty.
+    input_multi_proofs: Vec<Vec<InputMmcs::Proof>>,
+    /// When `FriConfig::use_multiproofs` is set, a multiproof covering the first layer openings
+    /// of all queries.
+    first_layer_multi_proof: Option<Vec<FriMmcs::Proof>>,
+    /// When `FriConfig::commit_proof_of_work_bits` is nonzero, the proof of work witness ground
+    /// before the batching challenge. Otherwise, this is `None`.
+    alpha_pow_witness: Option<Witness>,
+    /// When `FriConfig::commit_proof_of_work_bits` is nonzero, the proof of work witness ground
+    /// before the folding challenge of the first layer. Otherwise, this is `None`.
+    bivariate_beta_pow_witness: Option<Witness>,
 }
 
 impl<Val, InputMmcs, FriMmcs, Challenge, Challenger> Pcs<Challenge, Challenger>
@@ -105,7 +160,7 @@ where
 {
     type Domain = CircleDomain<Val>;
     type Commitment = InputMmcs::Commitment;
-    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
+    type ProverData = InputMmcs::ProverData<StoredMatrix<Val, InputMmcs>>;
     type EvaluationsOnDomain<'a> = RowIndexMappedView<CfftPerm, DenseMatrix<Val, Cow<'a, [Val]>>>;
     type Proof = CirclePcsProof<Val, Challenge, InputMmcs, FriMmcs, Challenger::Witness>;
     type Error = FriError<FriMmcs::Error, InputError<InputMmcs::Error, FriMmcs::Error>>;
@@ -126,17 +181,41 @@ where
                     "CirclePcs cannot commit to a matrix with fewer than 4 rows.",
                     // (because we bivariate fold one bit, and fri needs one more bit)
                 );
-                CircleEvaluations::from_natural_order(domain, evals)
+                let lde = CircleEvaluations::from_natural_order(domain, evals)
                     .extrapolate(CircleDomain::standard(
                         domain.log_n + self.fri_config.log_blowup,
                     ))
-                    .to_cfft_order()
+                    .to_cfft_order();
+                self.mmcs.store_matrix(lde)
             })
             .collect_vec();
         let (comm, mmcs_data) = self.mmcs.commit(ldes);
         (comm, mmcs_data)
     }
 
+    fn commit_coeffs(
+        &self,
+        coeffs: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let ldes = coeffs
+            .into_iter()
+            .map(|(domain, coeffs)| {
+                assert!(
+                    domain.log_n >= 2,
+                    "CirclePcs cannot commit to a matrix with fewer than 4 rows.",
+                );
+                assert_eq!(domain.size(), coeffs.height());
+                let lde = CircleEvaluations::evaluate(
+                    CircleDomain::standard(domain.log_n + self.fri_config.log_blowup),
+                    coeffs,
+                )
+                .to_cfft_order();
+                self.mmcs.store_matrix(lde)
+            })
+            .collect_vec();
+        self.mmcs.commit(ldes)
+    }
+
     fn get_evaluations_on_domain<'a>(
         &self,
         data: &'a Self::ProverData,
@@ -206,6 +285,8 @@ where
             .collect();
 
         // Batch combination challenge
+        let pow_bits = self.fri_config.commit_proof_of_work_bits;
+        let alpha_pow_witness = (pow_bits > 0).then(|| challenger.grind(pow_bits));
         let alpha: Challenge = challenger.sample_algebra_element();
 
         /*
@@ -288,6 +369,7 @@ where
         let (first_layer_commitment, first_layer_data) =
             self.fri_config.mmcs.commit(first_layer_mats);
         challenger.observe(first_layer_commitment.clone());
+        let bivariate_beta_pow_witness = (pow_bits > 0).then(|| challenger.grind(pow_bits));
         let bivariate_beta: Challenge = challenger.sample_algebra_element();
 
         // Fold all first layers at bivariate_beta.
@@ -305,52 +387,118 @@ where
         let g: CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> =
             CircleFriGenericConfig(PhantomData);
 
-        let fri_proof = prove(&g, &self.fri_config, fri_input, challenger, |index| {
-            // CircleFriFolder asks for an extra query index bit, so we use that here to index
-            // the first layer fold.
-
-            // Open the input (big opening, lots of columns) at the full index...
-            let input_openings = rounds
-                .iter()
-                .map(|(data, _)| {
-                    let log_max_batch_height = log2_strict_usize(self.mmcs.get_max_height(data));
-                    let reduced_index = index >> (log_max_height - log_max_batch_height);
-                    let (opened_values, opening_proof) = self.mmcs.open_batch(reduced_index, data);
-                    BatchOpening {
-                        opened_values,
-                        opening_proof,
-                    }
-                })
-                .collect();
-
-            // We committed to first_layer in pairs, so open the reduced index and include the sibling
-            // as part of the input proof.
-            let (first_layer_values, first_layer_proof) = self
-                .fri_config
-                .mmcs
-                .open_batch(index >> 1, &first_layer_data);
-            let first_layer_siblings = izip!(&first_layer_values, &log_heights)
+        // We committed to first_layer in pairs, so the sibling of each reduced index is included
+        // as part of the input proof.
+        let first_layer_siblings = |index: usize, first_layer_values: &[Vec<Challenge>]| {
+            izip!(first_layer_values, &log_heights)
                 .map(|(v, log_height)| {
                     let reduced_index = index >> (log_max_height - log_height);
                     let sibling_index = (reduced_index & 1) ^ 1;
                     v[sibling_index]
                 })
-                .collect();
-            CircleInputProof {
-                input_openings,
-                first_layer_siblings,
-                first_layer_proof,
-            }
-        });
+                .collect()
+        };
+        let reduced_indices = |data: &&Self::ProverData, indices: &[usize]| {
+            let log_max_batch_height = log2_strict_usize(self.mmcs.get_max_height(*data));
+            indices
+                .iter()
+                .map(|&index| index >> (log_max_height - log_max_batch_height))
+                .collect_vec()
+        };
+
+        if !self.fri_config.use_multiproofs {
+            let fri_proof = prove(&g, &self.fri_config, fri_input, challenger, |index| {
+                // CircleFriFolder asks for an extra query index bit, so we use that here to index
+                // the first layer fold.
+
+                // Open the input (big opening, lots of columns) at the full index...
+                let input_openings = rounds
+                    .iter()
+                    .map(|(data, _)| {
+                        let reduced_index = reduced_indices(data, &[index])[0];
+                        let (opened_values, opening_proof) =
+                            self.mmcs.open_batch(reduced_index, data);
+                        BatchOpening {
+                            opened_values,
+                            opening_proof: Some(opening_proof),
+                        }
+                    })
+                    .collect();
 
-        (
-            values,
-            CirclePcsProof {
+                let (first_layer_values, first_layer_proof) = self
+                    .fri_config
+                    .mmcs
+                    .open_batch(index >> 1, &first_layer_data);
+                CircleInputProof {
+                    input_openings,
+                    first_layer_siblings: first_layer_siblings(index, &first_layer_values),
+                    first_layer_proof: Some(first_layer_proof),
+                }
+            });
+
+            let proof = CirclePcsProof {
                 first_layer_commitment,
                 lambdas,
                 fri_proof,
-            },
-        )
+                input_multi_proofs: vec![],
+                first_layer_multi_proof: None,
+                alpha_pow_witness,
+                bivariate_beta_pow_witness,
+            };
+            return (values, proof);
+        }
+
+        // The inputs and the first layer are opened at all query indices at once, after FRI has
+        // sampled them.
+        let mut indices = vec![];
+        let mut fri_proof = prove(&g, &self.fri_config, fri_input, challenger, |index| {
+            indices.push(index);
+            CircleInputProof {
+                input_openings: vec![],
+                first_layer_siblings: vec![],
+                first_layer_proof: None,
+            }
+        });
+
+        let input_multi_proofs = rounds
+            .iter()
+            .map(|(data, _)| {
+                let (opened_values, multi_proof) =
+                    self.mmcs.open_multi(&reduced_indices(data, &indices), data);
+                for (query_proof, opened_values) in
+                    izip!(&mut fri_proof.query_proofs, opened_values)
+                {
+                    query_proof.input_proof.input_openings.push(BatchOpening {
+                        opened_values,
+                        opening_proof: None,
+                    });
+                }
+                multi_proof
+            })
+            .collect();
+
+        let first_layer_indices = indices.iter().map(|&index| index >> 1).collect_vec();
+        let (first_layer_values, first_layer_multi_proof) = self
+            .fri_config
+            .mmcs
+            .open_multi(&first_layer_indices, &first_layer_data);
+        for (query_proof, &index, first_layer_values) in
+            izip!(&mut fri_proof.query_proofs, &indices, first_layer_values)
+        {
+            query_proof.input_proof.first_layer_siblings =
+                first_layer_siblings(index, &first_layer_values);
+        }
+
+        let proof = CirclePcsProof {
+            first_layer_commitment,
+            lambdas,
+            fri_proof,
+            input_multi_proofs,
+            first_layer_multi_proof: Some(first_layer_multi_proof),
+            alpha_pow_witness,
+            bivariate_beta_pow_witness,
+        };
+        (values, proof)
     }
 
     fn verify(
@@ -385,18 +533,70 @@ where
             }
         }
 
+        // Both challenges sampled here are preceded by a PoW, if the config calls for one.
+        let pow_bits = self.fri_config.commit_proof_of_work_bits;
+        if proof.alpha_pow_witness.is_some() != (pow_bits > 0)
+            || proof.bivariate_beta_pow_witness.is_some() != (pow_bits > 0)
+        {
+            return Err(FriError::InvalidProofShape);
+        }
+
         // Batch combination challenge
+        let pow_ok = proof
+            .alpha_pow_witness
+            .is_none_or(|witness| challenger.check_witness(pow_bits, witness));
+        if !pow_ok {
+            return Err(FriError::InvalidPcsPowWitness);
+        }
         let alpha: Challenge = challenger.sample_algebra_element();
         challenger.observe(proof.first_layer_commitment.clone());
+        let pow_ok = proof
+            .bivariate_beta_pow_witness
+            .is_none_or(|witness| challenger.check_witness(pow_bits, witness));
+        if !pow_ok {
+            return Err(FriError::InvalidPcsPowWitness);
+        }
         let bivariate_beta: Challenge = challenger.sample_algebra_element();
 
-        // +1 to account for first layer
-        let log_global_max_height =
-            proof.fri_proof.commit_phase_commits.len() + self.fri_config.log_blowup + 1;
+        // With higher folding arities the number of commit phase rounds no longer determines the
+        // height of the largest input, so we derive it from the opened domains instead.
+        let log_global_max_height = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .map(|(domain, _)| domain.log_n + self.fri_config.log_blowup)
+            .max()
+            .unwrap_or(self.fri_config.log_blowup + self.fri_config.log_final_poly_len + 1);
+
+        // Every input proof must open each batch, with proofs of its own unless the openings are
+        // covered by the multiproofs. Each matrix must be opened at a point at least, as only the
+        // values claimed there fix its width, and the values claimed at each of its points must
+        // agree on that width.
+        let use_multiproofs = self.fri_config.use_multiproofs;
+        let num_multi_proofs = if use_multiproofs { rounds.len() } else { 0 };
+        let well_shaped = rounds.iter().flat_map(|(_, mats)| mats).all(|(_, points)| {
+            !points.is_empty() && points.iter().map(|(_, values)| values.len()).all_equal()
+        }) && proof.input_multi_proofs.len() == num_multi_proofs
+            && proof.first_layer_multi_proof.is_some() == use_multiproofs
+            && proof.fri_proof.query_proofs.iter().all(|query_proof| {
+                let input_proof = &query_proof.input_proof;
+                input_proof.input_openings.len() == rounds.len()
+                    && input_proof.first_layer_proof.is_some() != use_multiproofs
+                    && input_proof.input_openings.iter().all(|batch_opening| {
+                        batch_opening.opening_proof.is_some() != use_multiproofs
+                    })
+            });
+        if !well_shaped {
+            return Err(FriError::InvalidProofShape);
+        }
 
         let g: CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> =
             CircleFriGenericConfig(PhantomData);
 
+        // With multiproofs, the queried indices and the first layer leaves they open, which are
+        // checked once FRI has been verified.
+        let mut indices = vec![];
+        let mut first_layer_openings = vec![];
+
         verify(
             &g,
             &self.fri_config,
@@ -412,45 +612,31 @@ where
                     first_layer_proof,
                 } = input_proof;
 
-                for (batch_opening, (batch_commit, mats)) in
-                    zip_eq(input_openings, &rounds, InputError::InputShapeError)?
+                for (round, (batch_opening, (batch_commit, mats))) in zip_eq(
+                    input_openings,
+                    &rounds,
+                    InputError::InputShapeError { round: None },
+                )?
+                .enumerate()
                 {
-                    let batch_heights: Vec<usize> = mats
-                        .iter()
-                        .map(|(domain, _)| (domain.size() << self.fri_config.log_blowup))
-                        .collect_vec();
-                    let batch_dims: Vec<Dimensions> = batch_heights
-                        .iter()
-                        // todo: mmcs doesn't really need width
-                        .map(|&height| Dimensions { width: 0, height })
-                        .collect_vec();
-
-                    let (dims, idx) = if let Some(log_batch_max_height) =
-                        batch_heights.iter().max().map(|x| log2_strict_usize(*x))
-                    {
-                        (
-                            &batch_dims[..],
-                            index >> (log_global_max_height - log_batch_max_height),
-                        )
-                    } else {
-                        // Empty batch?
-                        (&[][..], 0)
-                    };
-
-                    self.mmcs
-                        .verify_batch(
-                            batch_commit,
-                            dims,
-                            idx,
-                            &batch_opening.opened_values,
-                            &batch_opening.opening_proof,
-                        )
-                        .map_err(InputError::InputMmcsError)?;
+                    if let Some(opening_proof) = &batch_opening.opening_proof {
+                        let dims = self.batch_dims(mats);
+                        let idx = batch_index(&dims, log_global_max_height, index);
+                        self.mmcs
+                            .verify_batch(
+                                batch_commit,
+                                &dims,
+                                idx,
+                                &batch_opening.opened_values,
+                                opening_proof,
+                            )
+                            .map_err(|error| InputError::InputMmcsError { round, error })?;
+                    }
 
                     for (ps_at_x, (mat_domain, mat_points_and_values)) in zip_eq(
                         &batch_opening.opened_values,
                         mats,
-                        InputError::InputShapeError,
+                        InputError::InputShapeError { round: Some(round) },
                     )? {
                         let log_height = mat_domain.log_n + self.fri_config.log_blowup;
                         let bits_reduced = log_global_max_height - log_height;
@@ -481,10 +667,10 @@ where
                     zip_eq(
                         reduced_openings,
                         first_layer_siblings,
-                        InputError::InputShapeError,
+                        InputError::InputShapeError { round: None },
                     )?,
                     &proof.lambdas,
-                    InputError::InputShapeError,
+                    InputError::InputShapeError { round: None },
                 )?
                 .map(|(((log_height, (_, ro)), &fl_sib), &lambda)| {
                     assert!(log_height > 0);
@@ -514,7 +700,7 @@ where
                     );
 
                     let fl_dims = Dimensions {
-                        width: 0,
+                        width: 2,
                         height: 1 << (log_height - 1),
                     };
 
@@ -525,20 +711,103 @@ where
                 // sort descending
                 fri_input.reverse();
 
-                self.fri_config
-                    .mmcs
-                    .verify_batch(
-                        &proof.first_layer_commitment,
-                        &fl_dims,
-                        index >> 1,
-                        &fl_leaves,
-                        first_layer_proof,
-                    )
-                    .map_err(InputError::FirstLayerMmcsError)?;
+                match first_layer_proof {
+                    Some(first_layer_proof) => self
+                        .fri_config
+                        .mmcs
+                        .verify_batch(
+                            &proof.first_layer_commitment,
+                            &fl_dims,
+                            index >> 1,
+                            &fl_leaves,
+                            first_layer_proof,
+                        )
+                        .map_err(InputError::FirstLayerMmcsError)?,
+                    None => {
+                        indices.push(index);
+                        first_layer_openings.push((fl_dims, fl_leaves));
+                    }
+                }
 
                 Ok(fri_input)
             },
-        )
+        )?;
+
+        if let Some(first_layer_multi_proof) = &proof.first_layer_multi_proof {
+            for (batch, ((batch_commit, mats), multi_proof)) in
+                izip!(&rounds, &proof.input_multi_proofs).enumerate()
+            {
+                let opened_values = proof
+                    .fri_proof
+                    .query_proofs
+                    .iter()
+                    .map(|query_proof| {
+                        query_proof.input_proof.input_openings[batch]
+                            .opened_values
+                            .clone()
+                    })
+                    .collect_vec();
+                let dims = self.batch_dims(mats);
+                let reduced_indices = indices
+                    .iter()
+                    .map(|&index| batch_index(&dims, log_global_max_height, index))
+                    .collect_vec();
+                self.mmcs
+                    .verify_multi(
+                        batch_commit,
+                        &dims,
+                        &reduced_indices,
+                        &opened_values,
+                        multi_proof,
+                    )
+                    .map_err(|error| FriError::InputError {
+                        query: None,
+                        error: InputError::InputMmcsError {
+                            round: batch,
+                            error,
+                        },
+                    })?;
+            }
+
+            // Every query opens the first layer at all heights, so the dimensions agree.
+            let fl_dims = first_layer_openings
+                .first()
+                .map_or(vec![], |(fl_dims, _)| fl_dims.clone());
+            let (fl_indices, fl_leaves): (Vec<_>, Vec<_>) = izip!(&indices, first_layer_openings)
+                .map(|(&index, (_, leaves))| (index >> 1, leaves))
+                .unzip();
+            self.fri_config
+                .mmcs
+                .verify_multi(
+                    &proof.first_layer_commitment,
+                    &fl_dims,
+                    &fl_indices,
+                    &fl_leaves,
+                    first_layer_multi_proof,
+                )
+                .map_err(|error| FriError::InputError {
+                    query: None,
+                    error: InputError::FirstLayerMmcsError(error),
+                })?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<Val: ComplexExtendable, InputMmcs, FriMmcs> CirclePcs<Val, InputMmcs, FriMmcs> {
+    /// Returns the dimensions of the LDEs of the matrices of a committed batch.
+    #[allow(clippy::type_complexity)]
+    fn batch_dims<Challenge>(
+        &self,
+        mats: &[(CircleDomain<Val>, Vec<(Challenge, Vec<Challenge>)>)],
+    ) -> Vec<Dimensions> {
+        claimed_batch_dims(mats.iter().map(|(domain, points_and_values)| {
+            (
+                domain.size() << self.fri_config.log_blowup,
+                &points_and_values[..],
+            )
+        }))
     }
 }
 
@@ -582,38 +851,41 @@ mod tests {
 
         type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
 
-        let fri_config = create_test_fri_config(challenge_mmcs, 0);
-
         type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
-        let pcs = Pcs {
-            mmcs: val_mmcs,
-            fri_config,
-            _phantom: PhantomData,
-        };
 
         let log_n = 10;
 
-        let d = <Pcs as p3_commit::Pcs<Challenge, Challenger>>::natural_domain_for_degree(
-            &pcs,
-            1 << log_n,
-        );
-
-        let evals = RowMajorMatrix::rand(&mut rng, 1 << log_n, 1);
-
-        let (comm, data) =
-            <Pcs as p3_commit::Pcs<Challenge, Challenger>>::commit(&pcs, vec![(d, evals)]);
-
-        let zeta: Challenge = rng.random();
-
-        let mut chal = Challenger::from_hasher(vec![], byte_hash);
-        let (values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut chal);
-
-        let mut chal = Challenger::from_hasher(vec![], byte_hash);
-        pcs.verify(
-            vec![(comm, vec![(d, vec![(zeta, values[0][0][0].clone())])])],
-            &proof,
-            &mut chal,
-        )
-        .expect("verify err");
+        for log_final_poly_len in 0..5 {
+            let fri_config = create_test_fri_config(challenge_mmcs.clone(), log_final_poly_len);
+            let pcs = Pcs {
+                mmcs: val_mmcs.clone(),
+                fri_config,
+                _phantom: PhantomData,
+            };
+
+            let d = <Pcs as p3_commit::Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                &pcs,
+                1 << log_n,
+            );
+
+            let evals = RowMajorMatrix::rand(&mut rng, 1 << log_n, 1);
+
+            let (comm, data) =
+                <Pcs as p3_commit::Pcs<Challenge, Challenger>>::commit(&pcs, vec![(d, evals)]);
+
+            let zeta: Challenge = rng.random();
+
+            let mut chal = Challenger::from_hasher(vec![], byte_hash);
+            let (values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut chal);
+            assert_eq!(proof.fri_proof.final_poly.len(), 1 << log_final_poly_len);
+
+            let mut chal = Challenger::from_hasher(vec![], byte_hash);
+            pcs.verify(
+                vec![(comm, vec![(d, vec![(zeta, values[0][0][0].clone())])])],
+                &proof,
+                &mut chal,
+            )
+            .expect("verify err");
+        }
     }
 }
@@ -5,12 +5,14 @@ use core::iter;
 use itertools::{Itertools, izip};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
+use p3_field::extension::ComplexExtendable;
 use p3_field::{ExtensionField, Field};
 use p3_fri::{FriConfig, FriGenericConfig};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_util::log2_strict_usize;
 use tracing::{info_span, instrument};
 
+use crate::folding::{evaluate_poly, fri_layer_x};
 use crate::{CircleCommitPhaseProofStep, CircleFriProof, CircleQueryProof};
 
 #[instrument(name = "FRI prover", skip_all)]
@@ -19,10 +21,10 @@ pub fn prove<G, Val, Challenge, M, Challenger>(
     config: &FriConfig<M>,
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
-    open_input: impl Fn(usize) -> G::InputProof,
+    mut open_input: impl FnMut(usize) -> G::InputProof,
 ) -> CircleFriProof<Challenge, M, Challenger::Witness, G::InputProof>
 where
-    Val: Field,
+    Val: ComplexExtendable,
     Challenge: ExtensionField<Val>,
     M: Mmcs<Challenge>,
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
@@ -37,37 +39,76 @@ where
     );
 
     let log_max_height = log2_strict_usize(inputs[0].len());
+    let log_min_height = log2_strict_usize(inputs.last().unwrap().len());
+    assert!(
+        log_min_height >= config.log_final_poly_len + config.log_blowup,
+        "Inputs must be at least as large as the final domain"
+    );
 
     let commit_phase_result = commit_phase(g, config, inputs, challenger);
 
     let pow_witness = challenger.grind(config.proof_of_work_bits);
 
-    let query_proofs = info_span!("query phase").in_scope(|| {
-        iter::repeat_with(|| challenger.sample_bits(log_max_height + g.extra_query_index_bits()))
-            .take(config.num_queries)
-            .map(|index| CircleQueryProof {
+    let (query_proofs, commit_phase_multi_proofs) = info_span!("query phase").in_scope(|| {
+        let indices = iter::repeat_with(|| {
+            challenger.sample_bits(log_max_height + g.extra_query_index_bits())
+        })
+        .take(config.num_queries)
+        .collect_vec();
+        let folded_indices = indices
+            .iter()
+            .map(|&index| index >> g.extra_query_index_bits())
+            .collect_vec();
+
+        let (commit_phase_openings, commit_phase_multi_proofs) = if config.use_multiproofs {
+            answer_queries_multi(
+                config,
+                &commit_phase_result.data,
+                &commit_phase_result.log_arities,
+                &folded_indices,
+            )
+        } else {
+            let openings = folded_indices
+                .iter()
+                .map(|&index| {
+                    answer_query(
+                        config,
+                        &commit_phase_result.data,
+                        &commit_phase_result.log_arities,
+                        index,
+                    )
+                })
+                .collect();
+            (openings, vec![])
+        };
+
+        let query_proofs = izip!(indices, commit_phase_openings)
+            .map(|(index, commit_phase_openings)| CircleQueryProof {
                 input_proof: open_input(index),
-                commit_phase_openings: answer_query(
-                    config,
-                    &commit_phase_result.data,
-                    index >> g.extra_query_index_bits(),
-                ),
+                commit_phase_openings,
             })
-            .collect()
+            .collect();
+        (query_proofs, commit_phase_multi_proofs)
     });
 
     CircleFriProof {
         commit_phase_commits: commit_phase_result.commits,
         query_proofs,
+        commit_phase_multi_proofs,
         final_poly: commit_phase_result.final_poly,
         pow_witness,
+        commit_pow_witnesses: commit_phase_result.pow_witnesses,
     }
 }
 
-struct CommitPhaseResult<F: Field, M: Mmcs<F>> {
+struct CommitPhaseResult<F: Field, M: Mmcs<F>, Witness> {
     commits: Vec<M::Commitment>,
     data: Vec<M::ProverData<RowMajorMatrix<F>>>,
-    final_poly: F,
+    /// The log of the folding arity used by each round.
+    log_arities: Vec<usize>,
+    /// The proof of work witness of each round, if there is commit phase grinding.
+    pow_witnesses: Vec<Witness>,
+    final_poly: Vec<F>,
 }
 
 #[instrument(name = "commit phase", skip_all)]
@@ -76,79 +117,198 @@ fn commit_phase<G, Val, Challenge, M, Challenger>(
     config: &FriConfig<M>,
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
-) -> CommitPhaseResult<Challenge, M>
+) -> CommitPhaseResult<Challenge, M, Challenger::Witness>
 where
-    Val: Field,
+    Val: ComplexExtendable,
     Challenge: ExtensionField<Val>,
     M: Mmcs<Challenge>,
-    Challenger: FieldChallenger<Val> + CanObserve<M::Commitment>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
     G: FriGenericConfig<Challenge>,
 {
     let mut inputs_iter = inputs.into_iter().peekable();
     let mut folded = inputs_iter.next().unwrap();
     let mut commits = vec![];
     let mut data = vec![];
+    let mut log_arities = vec![];
+    let mut pow_witnesses = vec![];
 
-    while folded.len() > config.blowup() {
-        let leaves = RowMajorMatrix::new(folded, 2);
+    let log_final_height = config.log_blowup + config.log_final_poly_len;
+    while folded.len() > config.blowup() * config.final_poly_len() {
+        let log_arity = config.log_arity_for_round(
+            log2_strict_usize(folded.len()),
+            inputs_iter.peek().map(|v| log2_strict_usize(v.len())),
+            log_final_height,
+        );
+        let leaves = RowMajorMatrix::new(folded, 1 << log_arity);
         let (commit, prover_data) = config.mmcs.commit_matrix(leaves);
         challenger.observe(commit.clone());
 
+        if config.commit_proof_of_work_bits > 0 {
+            pow_witnesses.push(challenger.grind(config.commit_proof_of_work_bits));
+        }
         let beta: Challenge = challenger.sample_algebra_element();
         // We passed ownership of `current` to the MMCS, so get a reference to it
         let leaves = config.mmcs.get_matrices(&prover_data).pop().unwrap();
-        folded = g.fold_matrix(beta, leaves.as_view());
+        folded = g.fold_matrix_with_arity(beta, log_arity, &leaves.values);
 
         commits.push(commit);
         data.push(prover_data);
+        log_arities.push(log_arity);
 
         if let Some(v) = inputs_iter.next_if(|v| v.len() == folded.len()) {
             izip!(&mut folded, v).for_each(|(c, x)| *c += x);
         }
     }
 
-    // We should be left with `blowup` evaluations of a constant polynomial.
-    assert_eq!(folded.len(), config.blowup());
-    let final_poly = folded[0];
-    for x in folded {
-        assert_eq!(x, final_poly);
+    // We should be left with `blowup * final_poly_len` evaluations of a polynomial in the
+    // x-coordinate of degree less than `final_poly_len`.
+    assert_eq!(folded.len(), config.blowup() * config.final_poly_len());
+    let final_poly = if config.log_final_poly_len == 0 {
+        for &x in &folded {
+            assert_eq!(x, folded[0]);
+        }
+        vec![folded[0]]
+    } else {
+        let xs = (0..folded.len())
+            .map(|index| fri_layer_x::<Val>(index, log_final_height))
+            .collect_vec();
+        let final_poly_len = config.final_poly_len();
+        let final_poly = interpolate(&xs[..final_poly_len], &folded[..final_poly_len]);
+        debug_assert!(
+            izip!(&xs, &folded).all(|(&x, &y)| evaluate_poly(&final_poly, x) == y),
+            "Folded evaluations are not those of a polynomial of degree < final_poly_len"
+        );
+        final_poly
+    };
+
+    // Observe all coefficients of the final polynomial.
+    for &x in &final_poly {
+        challenger.observe_algebra_element(x);
     }
-    challenger.observe_algebra_element(final_poly);
 
     CommitPhaseResult {
         commits,
         data,
+        log_arities,
+        pow_witnesses,
         final_poly,
     }
 }
 
+/// Returns the coefficients of the polynomial of degree less than `xs.len()` which takes the
+/// value `ys[i]` at `xs[i]`, using Lagrange interpolation.
+fn interpolate<F: Field, EF: ExtensionField<F>>(xs: &[F], ys: &[EF]) -> Vec<EF> {
+    let n = xs.len();
+
+    // The coefficients of the vanishing polynomial of `xs`.
+    let mut vanishing = vec![F::ONE];
+    for &x in xs {
+        vanishing.push(F::ZERO);
+        for i in (1..vanishing.len()).rev() {
+            vanishing[i] = vanishing[i - 1] - x * vanishing[i];
+        }
+        vanishing[0] = -x * vanishing[0];
+    }
+
+    let mut coeffs = vec![EF::ZERO; n];
+    for (&xi, &yi) in izip!(xs, ys) {
+        // Divide the vanishing polynomial by `X - xi` to get the unnormalized Lagrange basis
+        // polynomial of `xi`.
+        let mut basis = vec![F::ZERO; n];
+        let mut acc = F::ZERO;
+        for k in (0..n).rev() {
+            acc = vanishing[k + 1] + xi * acc;
+            basis[k] = acc;
+        }
+        let scale = yi * evaluate_poly(&basis, xi).inverse();
+        izip!(&mut coeffs, basis).for_each(|(c, b)| *c += scale * b);
+    }
+    coeffs
+}
+
 fn answer_query<F, M>(
     config: &FriConfig<M>,
     commit_phase_commits: &[M::ProverData<RowMajorMatrix<F>>],
-    index: usize,
+    log_arities: &[usize],
+    mut index: usize,
 ) -> Vec<CircleCommitPhaseProofStep<F, M>>
 where
     F: Field,
     M: Mmcs<F>,
 {
-    commit_phase_commits
-        .iter()
-        .enumerate()
-        .map(|(i, commit)| {
-            let index_i = index >> i;
-            let index_i_sibling = index_i ^ 1;
-            let index_pair = index_i >> 1;
-
-            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index_pair, commit);
+    izip!(commit_phase_commits, log_arities)
+        .map(|(commit, &log_arity)| {
+            let index_in_row = index & ((1 << log_arity) - 1);
+            let index_row = index >> log_arity;
+
+            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index_row, commit);
             assert_eq!(opened_rows.len(), 1);
-            let opened_row = opened_rows.pop().unwrap();
-            assert_eq!(opened_row.len(), 2, "Committed data should be in pairs");
-            let sibling_value = opened_row[index_i_sibling % 2];
+            let mut opened_row = opened_rows.pop().unwrap();
+            assert_eq!(
+                opened_row.len(),
+                1 << log_arity,
+                "Committed data should be in rows of the folding arity"
+            );
+            opened_row.remove(index_in_row);
+
+            index = index_row;
 
             CircleCommitPhaseProofStep {
-                sibling_value,
-                opening_proof,
+                sibling_values: opened_row,
+                opening_proof: Some(opening_proof),
             }
         })
         .collect()
 }
+
+/// Like `answer_query` for each of `indices`, but proves the openings of each round with a single
+/// multiproof, which is returned alongside the openings of each query.
+#[allow(clippy::type_complexity)]
+fn answer_queries_multi<F, M>(
+    config: &FriConfig<M>,
+    commit_phase_commits: &[M::ProverData<RowMajorMatrix<F>>],
+    log_arities: &[usize],
+    indices: &[usize],
+) -> (
+    Vec<Vec<CircleCommitPhaseProofStep<F, M>>>,
+    Vec<Vec<M::Proof>>,
+)
+where
+    F: Field,
+    M: Mmcs<F>,
+{
+    let mut indices = indices.to_vec();
+    let mut openings = (0..indices.len())
+        .map(|_| Vec::with_capacity(log_arities.len()))
+        .collect_vec();
+    let multi_proofs = izip!(commit_phase_commits, log_arities)
+        .map(|(commit, &log_arity)| {
+            let index_rows = indices
+                .iter()
+                .map(|&index| index >> log_arity)
+                .collect_vec();
+            let (opened_rows, multi_proof) = config.mmcs.open_multi(&index_rows, commit);
+
+            for (index, index_row, mut opened_rows, query_openings) in
+                izip!(&mut indices, index_rows, opened_rows, &mut openings)
+            {
+                assert_eq!(opened_rows.len(), 1);
+                let mut opened_row = opened_rows.pop().unwrap();
+                assert_eq!(
+                    opened_row.len(),
+                    1 << log_arity,
+                    "Committed data should be in rows of the folding arity"
+                );
+                opened_row.remove(*index & ((1 << log_arity) - 1));
+                *index = index_row;
+
+                query_openings.push(CircleCommitPhaseProofStep {
+                    sibling_values: opened_row,
+                    opening_proof: None,
+                });
+            }
+            multi_proof
+        })
+        .collect();
+    (openings, multi_proofs)
+}
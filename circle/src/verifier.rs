@@ -1,15 +1,18 @@
 use alloc::vec;
 use alloc::vec::Vec;
 
-use itertools::Itertools;
+use itertools::{Itertools, izip};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
+use p3_field::extension::ComplexExtendable;
 use p3_field::{ExtensionField, Field};
 use p3_fri::verifier::FriError;
 use p3_fri::{FriConfig, FriGenericConfig};
 use p3_matrix::Dimensions;
+use p3_util::log2_strict_usize;
 use p3_util::zip_eq::zip_eq;
 
+use crate::folding::{evaluate_poly, fri_layer_x};
 use crate::{CircleCommitPhaseProofStep, CircleFriProof};
 
 pub fn verify<G, Val, Challenge, M, Challenger>(
@@ -17,42 +20,105 @@ pub fn verify<G, Val, Challenge, M, Challenger>(
     config: &FriConfig<M>,
     proof: &CircleFriProof<Challenge, M, Challenger::Witness, G::InputProof>,
     challenger: &mut Challenger,
-    open_input: impl Fn(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
+    mut open_input: impl FnMut(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
 ) -> Result<(), FriError<M::Error, G::InputError>>
 where
-    Val: Field,
+    Val: ComplexExtendable,
     Challenge: ExtensionField<Val>,
     M: Mmcs<Challenge>,
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
     G: FriGenericConfig<Challenge>,
 {
+    let num_commit_pow_witnesses = if config.commit_proof_of_work_bits > 0 {
+        proof.commit_phase_commits.len()
+    } else {
+        0
+    };
+    if proof.commit_pow_witnesses.len() != num_commit_pow_witnesses {
+        return Err(FriError::InvalidProofShape);
+    }
+
     let betas: Vec<Challenge> = proof
         .commit_phase_commits
         .iter()
-        .map(|comm| {
+        .enumerate()
+        .map(|(round, comm)| {
             challenger.observe(comm.clone());
-            challenger.sample_algebra_element()
+            // Check the round's PoW, if any.
+            let pow_ok = proof
+                .commit_pow_witnesses
+                .get(round)
+                .is_none_or(|&witness| {
+                    challenger.check_witness(config.commit_proof_of_work_bits, witness)
+                });
+            if !pow_ok {
+                return Err(FriError::InvalidPowWitness { round: Some(round) });
+            }
+            Ok(challenger.sample_algebra_element())
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
+
+    if proof.final_poly.len() != config.final_poly_len() {
+        return Err(FriError::InvalidProofShape);
+    }
 
-    // Observe the claimed final polynomial.
-    challenger.observe_algebra_element(proof.final_poly);
+    // Observe all coefficients of the final polynomial.
+    proof
+        .final_poly
+        .iter()
+        .for_each(|x| challenger.observe_algebra_element(*x));
 
     if proof.query_proofs.len() != config.num_queries {
         return Err(FriError::InvalidProofShape);
     }
+    let num_multi_proofs = if config.use_multiproofs {
+        proof.commit_phase_commits.len()
+    } else {
+        0
+    };
+    if proof.commit_phase_multi_proofs.len() != num_multi_proofs {
+        return Err(FriError::InvalidProofShape);
+    }
 
     // Check PoW.
     if !challenger.check_witness(config.proof_of_work_bits, proof.pow_witness) {
-        return Err(FriError::InvalidPowWitness);
+        return Err(FriError::InvalidPowWitness { round: None });
     }
 
-    // The log of the maximum domain size.
-    let log_max_height = proof.commit_phase_commits.len() + config.log_blowup;
+    // The log of the maximum domain size. The arity of each round depends on the heights of the
+    // inputs, so it is read off the shape of the proof here and checked in `verify_query`.
+    let log_arities = match proof.query_proofs.first() {
+        Some(qp) => qp
+            .commit_phase_openings
+            .iter()
+            .enumerate()
+            .map(|(round, opening)| {
+                let arity = opening.sibling_values.len() + 1;
+                if arity > 1 && arity.is_power_of_two() {
+                    Ok(log2_strict_usize(arity))
+                } else {
+                    Err(FriError::InvalidQueryShape {
+                        query: 0,
+                        round: Some(round),
+                    })
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![],
+    };
+    let log_final_height = config.log_blowup + config.log_final_poly_len;
+    let log_max_height = log_arities.iter().sum::<usize>() + log_final_height;
+
+    // With multiproofs, the row indices and opened rows of each round are collected from all
+    // queries, and checked together once every query has been folded.
+    let mut multi_openings = vec![(vec![], vec![]); num_multi_proofs];
 
-    for qp in &proof.query_proofs {
+    for (query, qp) in proof.query_proofs.iter().enumerate() {
         let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
-        let ro = open_input(index, &qp.input_proof).map_err(FriError::InputError)?;
+        let ro = open_input(index, &qp.input_proof).map_err(|error| FriError::InputError {
+            query: Some(query),
+            error,
+        })?;
 
         debug_assert!(
             ro.iter().tuple_windows().all(|((l, _), (r, _))| l > r),
@@ -66,6 +132,7 @@ where
         let folded_eval = verify_query(
             g,
             config,
+            query,
             index >> g.extra_query_index_bits(),
             zip_eq(
                 zip_eq(
@@ -74,22 +141,60 @@ where
                     FriError::InvalidProofShape,
                 )?,
                 &qp.commit_phase_openings,
-                FriError::InvalidProofShape,
+                FriError::InvalidQueryShape { query, round: None },
             )?,
             ro,
             log_max_height,
+            &mut multi_openings,
         )?;
 
-        // As we fold until the polynomial is constant, proof.final_poly should be a constant value and
-        // we do not need to do any polynomial evaluations.
-        if folded_eval != proof.final_poly {
-            return Err(FriError::FinalPolyMismatch);
+        // Evaluate the final polynomial at the x-coordinate of the point we folded down to. If the
+        // final polynomial is constant, we do not need to do any polynomial evaluations.
+        let final_poly_eval = if let [constant] = proof.final_poly[..] {
+            constant
+        } else {
+            let final_index =
+                (index >> g.extra_query_index_bits()) >> (log_max_height - log_final_height);
+            evaluate_poly(
+                &proof.final_poly,
+                fri_layer_x::<Val>(final_index, log_final_height),
+            )
+        };
+        if folded_eval != final_poly_eval {
+            return Err(FriError::FinalPolyMismatch { query, index });
         }
     }
 
+    let mut log_height = log_max_height;
+    for (round, (comm, &log_arity, (indices, opened_rows), multi_proof)) in izip!(
+        &proof.commit_phase_commits,
+        &log_arities,
+        &multi_openings,
+        &proof.commit_phase_multi_proofs
+    )
+    .enumerate()
+    {
+        let dims = &[Dimensions {
+            width: 1 << log_arity,
+            height: 1 << (log_height - log_arity),
+        }];
+        config
+            .mmcs
+            .verify_multi(comm, dims, indices, opened_rows, multi_proof)
+            .map_err(|error| FriError::CommitPhaseMmcsError {
+                round,
+                query: None,
+                error,
+            })?;
+        log_height -= log_arity;
+    }
+
     Ok(())
 }
 
+/// For each commit phase round, the row indices of the queries and the rows opened there.
+type MultiOpenings<F> = Vec<(Vec<usize>, Vec<Vec<Vec<F>>>)>;
+
 type CommitStep<'a, F, M> = (
     (
         &'a F, // The challenge point beta used for the next fold of Circle-FRI evaluations.
@@ -103,64 +208,118 @@ type CommitStep<'a, F, M> = (
 /// Given an initial `index` corresponding to a point in the initial domain
 /// and a series of `reduced_openings` corresponding to evaluations of
 /// polynomials to be added in at specific domain sizes, perform the standard
-/// sequence of Circle-FRI folds, checking at each step that the pair of sibling evaluations
-/// match the commitment.
+/// sequence of Circle-FRI folds, checking at each step that the row of sibling evaluations
+/// matches the commitment.
+#[allow(clippy::too_many_arguments)]
 fn verify_query<'a, G, F, M>(
     g: &G,
     config: &FriConfig<M>,
+    query: usize,
     mut index: usize,
     steps: impl ExactSizeIterator<Item = CommitStep<'a, F, M>>,
     reduced_openings: Vec<(usize, F)>,
     log_max_height: usize,
+    multi_openings: &mut MultiOpenings<F>,
 ) -> Result<F, FriError<M::Error, G::InputError>>
 where
     F: Field,
     M: Mmcs<F> + 'a,
     G: FriGenericConfig<F>,
 {
+    if reduced_openings
+        .first()
+        .is_some_and(|&(lh, _)| lh != log_max_height)
+    {
+        return Err(FriError::InvalidQueryShape { query, round: None });
+    }
+
     let mut folded_eval = F::ZERO;
     let mut ro_iter = reduced_openings.into_iter().peekable();
 
     // We start with evaluations over a domain of size (1 << log_max_height). We fold
-    // using FRI until the domain size reaches (1 << log_final_height). This is equal to 1 << log_blowup
-    // currently as we have not yet implemented early stopping.
-    for (log_folded_height, ((&beta, comm), opening)) in zip_eq(
-        (config.log_blowup..log_max_height).rev(),
-        steps,
-        FriError::InvalidProofShape,
-    )? {
+    // using FRI until the domain size reaches (1 << log_final_height).
+    let log_final_height = config.log_blowup + config.log_final_poly_len;
+    let mut log_height = log_max_height;
+    for (round, ((&beta, comm), opening)) in steps.enumerate() {
         // If there are new polynomials to roll in at this height, do so.
-        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
+        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_height) {
             folded_eval += ro;
         }
+        if log_height <= log_final_height || ro_iter.peek().is_some_and(|(lh, _)| *lh >= log_height)
+        {
+            return Err(FriError::InvalidQueryShape {
+                query,
+                round: Some(round),
+            });
+        }
 
-        // Get the index of the other sibling of the current fri node.
-        let index_sibling = index ^ 1;
+        // Each round folds down to the height of the next polynomials to roll in, if that is
+        // closer than the maximum folding arity allows.
+        let log_arity = config.log_arity_for_round(
+            log_height,
+            ro_iter.peek().map(|(lh, _)| *lh),
+            log_final_height,
+        );
+        if opening.sibling_values.len() + 1 != 1 << log_arity {
+            return Err(FriError::InvalidQueryShape {
+                query,
+                round: Some(round),
+            });
+        }
+        let log_folded_height = log_height - log_arity;
 
-        let mut evals = vec![folded_eval; 2];
-        evals[index_sibling % 2] = opening.sibling_value;
+        // Insert the current evaluation among its siblings.
+        let index_in_row = index & ((1 << log_arity) - 1);
+        let mut evals = opening.sibling_values.clone();
+        evals.insert(index_in_row, folded_eval);
 
         let dims = &[Dimensions {
-            width: 2,
+            width: 1 << log_arity,
             height: 1 << log_folded_height,
         }];
 
         // Replace index with the index of the parent fri node.
-        index >>= 1;
+        index >>= log_arity;
 
-        // Verify the commitment to the evaluations of the sibling nodes.
-        config
-            .mmcs
-            .verify_batch(comm, dims, index, &[evals.clone()], &opening.opening_proof)
-            .map_err(FriError::CommitPhaseMmcsError)?;
+        // Verify the commitment to the evaluations of the sibling nodes, or defer it to the
+        // round's multiproof.
+        match (&opening.opening_proof, multi_openings.get_mut(round)) {
+            (Some(opening_proof), None) => config
+                .mmcs
+                .verify_batch(comm, dims, index, &[evals.clone()], opening_proof)
+                .map_err(|error| FriError::CommitPhaseMmcsError {
+                    round,
+                    query: Some(query),
+                    error,
+                })?,
+            (None, Some((indices, opened_rows))) => {
+                indices.push(index);
+                opened_rows.push(vec![evals.clone()]);
+            }
+            _ => {
+                return Err(FriError::InvalidQueryShape {
+                    query,
+                    round: Some(round),
+                });
+            }
+        }
 
-        // Fold the pair of evaluations of sibling nodes into the evaluation of the parent fri node.
-        folded_eval = g.fold_row(index, log_folded_height, beta, evals.into_iter());
+        // Fold the row of evaluations of sibling nodes into the evaluation of the parent fri node.
+        folded_eval = g.fold_row_with_arity(index, log_folded_height, log_arity, beta, evals);
+        log_height = log_folded_height;
+    }
+
+    if log_height != log_final_height {
+        return Err(FriError::InvalidQueryShape { query, round: None });
+    }
+    // An input may have exactly the final height, in which case it is added after the last round.
+    if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_height) {
+        folded_eval += ro;
     }
 
     // If ro_iter is not empty, we failed to fold in some polynomial evaluations.
     if ro_iter.next().is_some() {
-        return Err(FriError::InvalidProofShape);
+        return Err(FriError::InvalidQueryShape { query, round: None });
     }
 
     // If we reached this point, we have verified that, starting at the initial index,
@@ -3,6 +3,7 @@ use core::marker::PhantomData;
 use core::ops::Deref;
 
 use p3_field::{ExtensionField, Field};
+use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::extension::FlatMatrixView;
 use p3_matrix::{Dimensions, Matrix};
 
@@ -33,6 +34,11 @@ where
     type Commitment = InnerMmcs::Commitment;
     type Proof = InnerMmcs::Proof;
     type Error = InnerMmcs::Error;
+    type MatrixStorage = Vec<EF>;
+
+    fn store_matrix(&self, matrix: RowMajorMatrix<EF>) -> RowMajorMatrix<EF> {
+        matrix
+    }
 
     fn commit<M: Matrix<EF>>(&self, inputs: Vec<M>) -> (Self::Commitment, Self::ProverData<M>) {
         self.inner
@@ -45,15 +51,17 @@ where
         prover_data: &Self::ProverData<M>,
     ) -> (Vec<Vec<EF>>, Self::Proof) {
         let (opened_base_values, proof) = self.inner.open_batch(index, prover_data);
-        let opened_ext_values = opened_base_values
-            .into_iter()
-            .map(|row| {
-                row.chunks(EF::DIMENSION)
-                    .map(EF::from_basis_coefficients_slice)
-                    .collect()
-            })
-            .collect();
-        (opened_ext_values, proof)
+        (to_ext_values(opened_base_values), proof)
+    }
+
+    fn open_multi<M: Matrix<EF>>(
+        &self,
+        indices: &[usize],
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<Vec<EF>>>, Vec<Self::Proof>) {
+        let (opened_base_values, proofs) = self.inner.open_multi(indices, prover_data);
+        let opened_ext_values = opened_base_values.into_iter().map(to_ext_values).collect();
+        (opened_ext_values, proofs)
     }
 
     fn get_matrices<'a, M: Matrix<EF>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M> {
@@ -72,23 +80,66 @@ where
         opened_values: &[Vec<EF>],
         proof: &Self::Proof,
     ) -> Result<(), Self::Error> {
-        let opened_base_values: Vec<Vec<F>> = opened_values
-            .iter()
-            .map(|row| {
-                row.iter()
-                    .flat_map(|el| el.as_basis_coefficients_slice())
-                    .copied()
-                    .collect()
-            })
-            .collect();
-        let base_dimensions = dimensions
-            .iter()
-            .map(|dim| Dimensions {
-                width: dim.width * EF::DIMENSION,
-                height: dim.height,
-            })
-            .collect::<Vec<_>>();
+        let opened_base_values = to_base_values(opened_values);
+        let base_dimensions = to_base_dimensions::<F, EF>(dimensions);
         self.inner
             .verify_batch(commit, &base_dimensions, index, &opened_base_values, proof)
     }
+
+    fn verify_multi(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        indices: &[usize],
+        opened_values: &[Vec<Vec<EF>>],
+        proofs: &[Self::Proof],
+    ) -> Result<(), Self::Error> {
+        let opened_base_values = opened_values
+            .iter()
+            .map(|opening| to_base_values(opening))
+            .collect::<Vec<_>>();
+        let base_dimensions = to_base_dimensions::<F, EF>(dimensions);
+        self.inner.verify_multi(
+            commit,
+            &base_dimensions,
+            indices,
+            &opened_base_values,
+            proofs,
+        )
+    }
+}
+
+fn to_ext_values<F: Field, EF: ExtensionField<F>>(opened_base_values: Vec<Vec<F>>) -> Vec<Vec<EF>> {
+    opened_base_values
+        .into_iter()
+        .map(|row| {
+            row.chunks(EF::DIMENSION)
+                .map(EF::from_basis_coefficients_slice)
+                .collect()
+        })
+        .collect()
+}
+
+fn to_base_values<F: Field, EF: ExtensionField<F>>(opened_values: &[Vec<EF>]) -> Vec<Vec<F>> {
+    opened_values
+        .iter()
+        .map(|row| {
+            row.iter()
+                .flat_map(|el| el.as_basis_coefficients_slice())
+                .copied()
+                .collect()
+        })
+        .collect()
+}
+
+fn to_base_dimensions<F: Field, EF: ExtensionField<F>>(
+    dimensions: &[Dimensions],
+) -> Vec<Dimensions> {
+    dimensions
+        .iter()
+        .map(|dim| Dimensions {
+            width: dim.width * EF::DIMENSION,
+            height: dim.height,
+        })
+        .collect()
 }
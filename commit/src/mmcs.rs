@@ -1,8 +1,10 @@
 use alloc::vec;
 use alloc::vec::Vec;
-use core::fmt::Debug;
+use core::error::Error;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::iter::zip;
 
-use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::dense::{DenseMatrix, DenseStorage, RowMajorMatrix};
 use p3_matrix::{Dimensions, Matrix};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
@@ -20,7 +22,12 @@ pub trait Mmcs<T: Send + Sync>: Clone {
     type ProverData<M>;
     type Commitment: Clone + Serialize + DeserializeOwned;
     type Proof: Clone + Serialize + DeserializeOwned;
-    type Error: Debug;
+    type Error: Debug + From<WrongOpeningCount>;
+    /// The storage in which a PCS keeps the matrices it commits to, e.g. its LDEs.
+    type MatrixStorage: DenseStorage<T> + 'static;
+
+    /// Moves `matrix` into `MatrixStorage`, which may keep it out of memory until it is opened.
+    fn store_matrix(&self, matrix: RowMajorMatrix<T>) -> StoredMatrix<T, Self>;
 
     fn commit<M: Matrix<T>>(&self, inputs: Vec<M>) -> (Self::Commitment, Self::ProverData<M>);
 
@@ -45,6 +52,25 @@ pub trait Mmcs<T: Send + Sync>: Clone {
         prover_data: &Self::ProverData<M>,
     ) -> (Vec<Vec<T>>, Self::Proof);
 
+    /// Opens a batch of rows at each of the given indices.
+    /// returns `(openings, proofs)`
+    /// where `openings[q]` is the opening at `indices[q]`, with the same layout as the openings
+    /// returned by `open_batch`, and `proofs[q]` is its proof. Indices may be repeated.
+    ///
+    /// By default this calls `open_batch` for each index. Implementations may instead leave out
+    /// of `proofs[q]` any data that the verifier can recompute from earlier openings, in which
+    /// case the proofs can only be checked together by `verify_multi`.
+    fn open_multi<M: Matrix<T>>(
+        &self,
+        indices: &[usize],
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<Vec<T>>>, Vec<Self::Proof>) {
+        indices
+            .iter()
+            .map(|&index| self.open_batch(index, prover_data))
+            .unzip()
+    }
+
     /// Get the matrices that were committed to.
     fn get_matrices<'a, M: Matrix<T>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M>;
 
@@ -79,4 +105,64 @@ pub trait Mmcs<T: Send + Sync>: Clone {
         opened_values: &[Vec<T>],
         proof: &Self::Proof,
     ) -> Result<(), Self::Error>;
+
+    /// Verify a multi-opening produced by `open_multi`.
+    /// `opened_values[q]` is the opening at `indices[q]` and `proofs[q]` is its proof, following
+    /// the same semantics as `verify_batch`.
+    ///
+    /// By default this calls `verify_batch` for each index.
+    fn verify_multi(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        indices: &[usize],
+        opened_values: &[Vec<Vec<T>>],
+        proofs: &[Self::Proof],
+    ) -> Result<(), Self::Error> {
+        check_opening_count(indices, opened_values, proofs)?;
+        zip(indices, zip(opened_values, proofs)).try_for_each(|(&index, (opening, proof))| {
+            self.verify_batch(commit, dimensions, index, opening, proof)
+        })
+    }
+}
+
+/// A matrix held in the storage that `M` provides for committed matrices.
+pub type StoredMatrix<T, M> = DenseMatrix<T, <M as Mmcs<T>>::MatrixStorage>;
+
+/// The number of openings or proofs passed to `Mmcs::verify_multi` doesn't match the number of
+/// indices.
+#[derive(Debug)]
+pub struct WrongOpeningCount {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl Display for WrongOpeningCount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected an opening and a proof for each of {} indices, got {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl Error for WrongOpeningCount {}
+
+/// Checks that there is one opening and one proof for each index of a multi-opening.
+pub fn check_opening_count<O, P>(
+    indices: &[usize],
+    opened_values: &[O],
+    proofs: &[P],
+) -> Result<(), WrongOpeningCount> {
+    match [opened_values.len(), proofs.len()]
+        .into_iter()
+        .find(|&len| len != indices.len())
+    {
+        Some(actual) => Err(WrongOpeningCount {
+            expected: indices.len(),
+            actual,
+        }),
+        None => Ok(()),
+    }
 }
@@ -0,0 +1,92 @@
+//! Traits for multilinear polynomial commitment schemes.
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use p3_field::{ExtensionField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::OpenedValues;
+
+/// A (not necessarily hiding) polynomial commitment scheme for (batches of) multilinear polynomials.
+///
+/// A committed matrix of height `2^n` holds one `n`-variate multilinear polynomial per column,
+/// given by its evaluations over the boolean hypercube `{0, 1}^n`. Row `b` contains the evaluations
+/// at the point whose `i`-th coordinate is bit `i` of `b`.
+pub trait MultilinearPcs<Challenge, Challenger>
+where
+    Challenge: ExtensionField<Self::Val>,
+{
+    /// The field the committed polynomials are defined over.
+    type Val: Field;
+
+    /// The commitment that's sent to the verifier.
+    type Commitment: Clone + Serialize + DeserializeOwned;
+
+    /// Data that the prover stores for committed polynomials, to help the prover with opening.
+    type ProverData;
+
+    /// The opening argument.
+    type Proof: Clone + Serialize + DeserializeOwned;
+
+    type Error: Debug;
+
+    /// Commits to a batch of multilinear polynomials.
+    ///
+    /// Each matrix must have a power-of-two height `2^n`, and each of its columns holds the
+    /// evaluations of one `n`-variate polynomial over the boolean hypercube. Implementations may
+    /// require every matrix in a batch to have the same height.
+    fn commit(
+        &self,
+        evaluations: Vec<RowMajorMatrix<Self::Val>>,
+    ) -> (Self::Commitment, Self::ProverData);
+
+    /// Evaluates the committed polynomials at the given points and proves the evaluations.
+    ///
+    /// Returns the opened values, indexed by round, then matrix, then point, then column, along
+    /// with a single proof covering every round. Each point must have as many coordinates as its
+    /// matrix has variables.
+    #[allow(clippy::type_complexity)]
+    fn open(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<
+                // points to open, each a vector of `n` coordinates
+                Vec<Vec<Challenge>>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof);
+
+    /// Checks that the claimed values are the evaluations of the committed polynomials.
+    ///
+    /// `rounds` must list the same commitments, matrices and points, in the same order, as the
+    /// prover passed to `open`, and `challenger` must be in the same state.
+    #[allow(clippy::type_complexity)]
+    fn verify(
+        &self,
+        // For each round:
+        rounds: Vec<(
+            Self::Commitment,
+            // for each matrix:
+            Vec<(
+                // its number of variables,
+                usize,
+                // for each point:
+                Vec<(
+                    // the point,
+                    Vec<Challenge>,
+                    // values at the point
+                    Vec<Challenge>,
+                )>,
+            )>,
+        )>,
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error>;
+}
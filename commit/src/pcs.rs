@@ -44,6 +44,18 @@ where
         evaluations: Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>,
     ) -> (Self::Commitment, Self::ProverData);
 
+    /// Commits to polynomials given by their coefficients rather than their evaluations, skipping
+    /// the interpolation that `commit` would otherwise start with.
+    ///
+    /// Each matrix has height `domain.size()`, and row `i` holds the coefficients of the `i`-th
+    /// basis polynomial: `X^i` for univariate schemes, and the `i`-th element of the circle FFT
+    /// basis for circle schemes. The result can be opened exactly as if it came from `commit`.
+    #[allow(clippy::type_complexity)]
+    fn commit_coeffs(
+        &self,
+        coeffs: Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>,
+    ) -> (Self::Commitment, Self::ProverData);
+
     fn get_evaluations_on_domain<'a>(
         &self,
         prover_data: &'a Self::ProverData,
@@ -0,0 +1,142 @@
+use alloc::sync::Arc;
+use core::cell::RefCell;
+use core::fmt::{Debug, Formatter};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// A source of the randomness used by hiding commitment schemes, such as salts and random
+/// codewords.
+pub trait CommitRandomness {
+    /// The RNG from which the randomness of a single commitment is drawn.
+    type Rng: Rng;
+
+    /// Calls `f` with an RNG which provides the randomness for the next commitment.
+    fn with_next_rng<T>(&self, f: impl FnOnce(&mut Self::Rng) -> T) -> T;
+}
+
+/// Draws the randomness of every commitment from a single RNG.
+///
+/// `R` should be an appropriately seeded cryptographically secure pseudorandom number generator
+/// (CSPRNG). The RNG is advanced through a `RefCell`, so schemes using this source are not `Sync`;
+/// use `SeededRandomness` for schemes shared between threads.
+#[derive(Clone, Debug)]
+pub struct RngRandomness<R>(RefCell<R>);
+
+impl<R> RngRandomness<R> {
+    pub const fn new(rng: R) -> Self {
+        Self(RefCell::new(rng))
+    }
+}
+
+impl<R: Rng> CommitRandomness for RngRandomness<R> {
+    type Rng = R;
+
+    fn with_next_rng<T>(&self, f: impl FnOnce(&mut R) -> T) -> T {
+        f(&mut self.0.borrow_mut())
+    }
+}
+
+/// Derives the randomness of each commitment from a secret seed and the index of the commitment,
+/// so that hiding commitments, and proofs using them, can be reproduced exactly.
+///
+/// The randomness of commitment `i` is drawn from the ChaCha20 keystream with the seed as key and
+/// `i` as stream ID. Independent sources must use independent seeds, as sources with the same seed
+/// produce the same randomness.
+///
+/// In particular, a seed must not be reused across proofs: each new source counts commitments
+/// from zero, so proofs about different witnesses made with the same seed are masked with the same
+/// salts and random codewords, and comparing them can reveal the witnesses. Only reuse a seed to
+/// reproduce the same proof.
+///
+/// Commitments are numbered in the order in which they start, and clones share the counter, so a
+/// scheme and its clones never reuse an index. The counter only guarantees uniqueness: if clones
+/// commit concurrently, which of them gets which index depends on scheduling. To reproduce a proof,
+/// make its commitments in the same order, e.g. from a single thread.
+#[derive(Clone)]
+pub struct SeededRandomness {
+    seed: [u8; 32],
+    next_index: Arc<AtomicU64>,
+}
+
+impl SeededRandomness {
+    pub fn new(seed: [u8; 32]) -> Self {
+        Self {
+            seed,
+            next_index: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Returns the RNG providing the randomness of the commitment with the given index.
+    pub fn rng_for_index(&self, index: u64) -> ChaCha20Rng {
+        let mut rng = ChaCha20Rng::from_seed(self.seed);
+        rng.set_stream(index);
+        rng
+    }
+
+    /// Returns the RNG of the next commitment, and advances the counter.
+    pub fn next_rng(&self) -> ChaCha20Rng {
+        // Nothing is synchronized through the counter, so relaxed ordering suffices for the
+        // indices to be unique.
+        let index = self.next_index.fetch_add(1, Ordering::Relaxed);
+        self.rng_for_index(index)
+    }
+}
+
+impl CommitRandomness for SeededRandomness {
+    type Rng = ChaCha20Rng;
+
+    fn with_next_rng<T>(&self, f: impl FnOnce(&mut ChaCha20Rng) -> T) -> T {
+        f(&mut self.next_rng())
+    }
+}
+
+impl Debug for SeededRandomness {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        // Don't leak the seed.
+        f.debug_struct("SeededRandomness")
+            .field("next_index", &self.next_index)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::*;
+
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn seeded_randomness_is_sync() {
+        assert_sync::<SeededRandomness>();
+    }
+
+    #[test]
+    fn seeded_randomness_is_reproducible() {
+        let first = SeededRandomness::new([7; 32]);
+        let second = SeededRandomness::new([7; 32]);
+        for _ in 0..3 {
+            assert_eq!(
+                first.with_next_rng(|rng| rng.next_u64()),
+                second.with_next_rng(|rng| rng.next_u64())
+            );
+        }
+    }
+
+    #[test]
+    fn seeded_randomness_differs_between_commitments() {
+        let randomness = SeededRandomness::new([7; 32]);
+        let clone = randomness.clone();
+        let a = randomness.next_rng().next_u64();
+        // Clones share the counter, so this is the second commitment.
+        let b = clone.next_rng().next_u64();
+        assert_ne!(a, b);
+        assert_eq!(b, randomness.rng_for_index(1).next_u64());
+
+        let other_seed = SeededRandomness::new([8; 32]);
+        assert_ne!(a, other_seed.next_rng().next_u64());
+    }
+}
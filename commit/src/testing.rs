@@ -88,6 +88,24 @@ where
         )
     }
 
+    fn commit_coeffs(
+        &self,
+        coeffs: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let coeffs: Vec<_> = coeffs
+            .into_iter()
+            .map(|(domain, coeffs)| {
+                assert!(log2_strict_usize(domain.size()) >= self.log_n);
+                assert_eq!(domain.size(), coeffs.height());
+                coeffs
+            })
+            .collect();
+        (
+            coeffs.clone().into_iter().map(|m| m.values).collect(),
+            coeffs,
+        )
+    }
+
     fn get_evaluations_on_domain<'a>(
         &self,
         prover_data: &'a Self::ProverData,
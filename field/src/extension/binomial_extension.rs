@@ -18,8 +18,8 @@ use super::{HasFrobenius, HasTwoAdicBinomialExtension, PackedBinomialExtensionFi
 use crate::extension::BinomiallyExtendable;
 use crate::field::Field;
 use crate::{
-    Algebra, BasedVectorSpace, ExtensionField, Packable, PrimeCharacteristicRing, TwoAdicField,
-    field_to_array,
+    Algebra, BasedVectorSpace, ExtensionField, Packable, Pod, PrimeCharacteristicRing,
+    TwoAdicField, field_to_array,
 };
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize, PartialOrd, Ord)]
@@ -42,6 +42,9 @@ impl<F, A, const D: usize> BinomialExtensionField<F, D, A> {
     }
 }
 
+// SAFETY: this is a repr(transparent) wrapper around an array of `Pod` values.
+unsafe impl<F: Pod, A: Pod, const D: usize> Pod for BinomialExtensionField<F, D, A> {}
+
 impl<F: Field, A: Algebra<F>, const D: usize> Default for BinomialExtensionField<F, D, A> {
     fn default() -> Self {
         Self::new(array::from_fn(|_| A::ZERO))
@@ -7,7 +7,9 @@ use num_bigint::BigUint;
 use p3_maybe_rayon::prelude::{IntoParallelRefMutIterator, ParallelIterator};
 
 use crate::field::Field;
-use crate::{PackedValue, PrimeCharacteristicRing, PrimeField, PrimeField32, TwoAdicField};
+use crate::{
+    PackedValue, PrimeCharacteristicRing, PrimeField, PrimeField32, PrimeField64, TwoAdicField,
+};
 
 /// Computes `Z_H(x)`, where `Z_H` is the vanishing polynomial of a multiplicative subgroup of order `2^log_n`.
 pub fn two_adic_subgroup_vanishing_polynomial<F: TwoAdicField>(log_n: usize, x: F) -> F {
@@ -184,6 +186,38 @@ pub fn split_32<SF: PrimeField, TF: PrimeField32>(val: SF, n: usize) -> Vec<TF>
     result
 }
 
+/// Given a slice of SF elements, reduce them to a TF element using a 2^64-base decomposition.
+///
+/// The result is injective as long as `2^(64 * vals.len())` doesn't exceed the characteristic of TF.
+pub fn reduce_64<SF: PrimeField64, TF: PrimeField>(vals: &[SF]) -> TF {
+    let po2 = TF::from_int(1u128 << 64);
+    let mut result = TF::ZERO;
+    for val in vals.iter().rev() {
+        result = result * po2 + TF::from_int(val.as_canonical_u64());
+    }
+    result
+}
+
+/// Given an SF element, split it to a vector of TF elements using a 2^128-base decomposition.
+///
+/// We use a 2^128-base decomposition for a field of size ~2^64 because then the bias will be
+/// at most ~1/2^64 for each element after the reduction.
+pub fn split_64<SF: PrimeField, TF: PrimeField64>(val: SF, n: usize) -> Vec<TF> {
+    let mask = (BigUint::from(1u8) << 128) - 1u8;
+    let mut val = val.as_canonical_biguint();
+    let mut result = Vec::with_capacity(n);
+    for _ in 0..n {
+        let digit = (&val & &mask)
+            .to_u64_digits()
+            .iter()
+            .rev()
+            .fold(0u128, |acc, &limb| (acc << 64) | limb as u128);
+        result.push(TF::from_int(digit));
+        val >>= 128;
+    }
+    result
+}
+
 /// Maximally generic dot product.
 pub fn dot_product<S, LI, RI>(li: LI, ri: RI) -> S
 where
@@ -12,9 +12,11 @@ mod field;
 mod helpers;
 pub mod integers;
 mod packed;
+mod pod;
 
 pub use array::*;
 pub use batch_inverse::*;
 pub use field::*;
 pub use helpers::*;
 pub use packed::*;
+pub use pod::*;
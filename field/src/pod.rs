@@ -0,0 +1,16 @@
+/// A plain-old-data type, whose values can be stored as raw bytes (e.g. in a file) and read back.
+///
+/// # Safety
+/// Implementors must contain no padding bytes, pointers, references or interior mutability, so
+/// that the bytes of any valid value of `Self` can be copied out and reinterpreted as the same
+/// value later. Note that the converse need not hold: arbitrary bytes may not form a valid value.
+pub unsafe trait Pod: 'static + Copy + Send + Sync {}
+
+unsafe impl Pod for u8 {}
+unsafe impl Pod for u16 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for u64 {}
+unsafe impl Pod for u128 {}
+unsafe impl Pod for usize {}
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
@@ -0,0 +1,107 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use itertools::Itertools;
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
+use p3_commit::{ExtensionMmcs, Pcs};
+use p3_dft::Radix2DitParallel;
+use p3_field::Field;
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Dft = Radix2DitParallel<Val>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+fn get_pcs(log_folding_arity: usize, use_multiproofs: bool) -> (MyPcs, Challenger) {
+    let perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(0));
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        num_queries: 100,
+        proof_of_work_bits: 16,
+        commit_proof_of_work_bits: 0,
+        log_folding_arity,
+        use_multiproofs,
+        mmcs: challenge_mmcs,
+    };
+    (
+        MyPcs::new(Dft::default(), val_mmcs, fri_config),
+        Challenger::new(perm),
+    )
+}
+
+/// Benchmarks opening a batch of polynomials for each folding arity, with and without Merkle
+/// multiproofs, and prints the size of the resulting proofs so that they can be compared against
+/// binary folding with a Merkle proof per query.
+fn bench_fri_arity(c: &mut Criterion) {
+    let log_degrees = [16, 14, 12];
+    let width = 16;
+
+    let mut group = c.benchmark_group("fri_open_by_log_arity");
+    group.sample_size(10);
+
+    for (use_multiproofs, log_folding_arity) in [false, true].into_iter().cartesian_product(1..=4) {
+        let (pcs, challenger) = get_pcs(log_folding_arity, use_multiproofs);
+        let mut rng = SmallRng::seed_from_u64(1);
+        let domains_and_polys = log_degrees
+            .iter()
+            .map(|&log_degree| {
+                let degree = 1 << log_degree;
+                (
+                    <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, degree),
+                    RowMajorMatrix::<Val>::rand(&mut rng, degree, width),
+                )
+            })
+            .collect_vec();
+        let (commit, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, domains_and_polys);
+
+        let open = || {
+            let mut challenger = challenger.clone();
+            challenger.observe(commit.clone());
+            let zeta: Challenge = challenger.sample_algebra_element();
+            pcs.open(
+                vec![(&data, vec![vec![zeta]; log_degrees.len()])],
+                &mut challenger,
+            )
+        };
+
+        let (_, proof) = open();
+        let proof_size = postcard::to_allocvec(&proof)
+            .expect("unable to serialize proof")
+            .len();
+        println!(
+            "log_folding_arity = {log_folding_arity}, use_multiproofs = {use_multiproofs}: {} commit phase rounds, proof size {proof_size} bytes",
+            proof.fri_proof.commit_phase_commits.len(),
+        );
+
+        let proof_kind = if use_multiproofs {
+            "multiproofs"
+        } else {
+            "single_proofs"
+        };
+        group.bench_function(BenchmarkId::new(proof_kind, log_folding_arity), |b| {
+            b.iter(open)
+        });
+    }
+}
+
+criterion_group!(benches, bench_fri_arity);
+criterion_main!(benches);
@@ -0,0 +1,663 @@
+//! A Basefold-style commitment scheme for multilinear polynomials.
+//!
+//! A multilinear polynomial `f(x_0, ..., x_{n-1}) = sum_c a_c prod_{i in c} x_i` is identified with
+//! the univariate polynomial `F(X) = sum_c a_c X^c`, whose Reed-Solomon encoding is committed to.
+//! Folding `F` with challenge `r` (as in FRI) yields the univariate polynomial of `f(r, x_1, ...)`,
+//! so a sumcheck for `f(z) = sum_b eq(z, b) f(b)` can share its challenges with the FRI commit
+//! phase: the final FRI polynomial is then the restriction of `f` to the sumcheck point.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use itertools::{Itertools, izip};
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::{Mmcs, MultilinearPcs, OpenedValues};
+use p3_dft::TwoAdicSubgroupDft;
+use p3_field::{ExtensionField, Field, TwoAdicField, dot_product};
+use p3_matrix::bitrev::BitReversibleMatrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::{Dimensions, Matrix};
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+use serde::{Deserialize, Serialize};
+use tracing::{info_span, instrument};
+
+use crate::verifier::{self, FriError};
+use crate::{
+    BatchOpening, FriConfig, FriProof, TwoAdicFriGenericConfig, TwoAdicFriGenericConfigForMmcs,
+    prover,
+};
+
+#[derive(Debug)]
+pub struct BasefoldPcs<Val, Dft, InputMmcs, FriMmcs> {
+    dft: Dft,
+    mmcs: InputMmcs,
+    fri: FriConfig<FriMmcs>,
+    _phantom: PhantomData<Val>,
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs> BasefoldPcs<Val, Dft, InputMmcs, FriMmcs> {
+    /// Creates a new Basefold PCS. Each FRI round must bind exactly one variable, so `fri` must
+    /// use binary folding.
+    pub const fn new(dft: Dft, mmcs: InputMmcs, fri: FriConfig<FriMmcs>) -> Self {
+        assert!(
+            fri.log_folding_arity == 1,
+            "Basefold requires a folding arity of 2"
+        );
+        Self {
+            dft,
+            mmcs,
+            fri,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+pub struct BasefoldProverData<Val: Send + Sync, InputMmcs: Mmcs<Val>> {
+    /// The committed polynomials, as evaluations over the boolean hypercube.
+    evaluations: Vec<RowMajorMatrix<Val>>,
+    /// The Reed-Solomon encodings of the committed polynomials, in bit-reversed order.
+    codewords: InputMmcs::ProverData<RowMajorMatrix<Val>>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct BasefoldProof<
+    Val: Field,
+    Challenge: Field,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+> {
+    /// Round polynomials (evaluated at 0, 1 and 2) of the sumcheck which reduces openings at
+    /// several points to a single point. Empty if every matrix is opened at the same single point.
+    pub batching_sumcheck: Vec<[Challenge; 3]>,
+    /// For each round and matrix, the values of all columns at the point output by
+    /// `batching_sumcheck`. Empty if `batching_sumcheck` is.
+    pub batched_values: OpenedValues<Challenge>,
+    /// Round polynomials (evaluated at 0, 1 and 2) of the sumcheck run alongside the FRI commit phase.
+    pub sumcheck: Vec<[Challenge; 3]>,
+    pub fri_proof: FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>,
+}
+
+#[derive(Debug)]
+pub enum BasefoldError<FriMmcsError, InputMmcsError> {
+    InvalidProofShape,
+    /// A sumcheck round polynomial is inconsistent with the running claim.
+    SumcheckMismatch,
+    /// The final FRI polynomial is inconsistent with the claimed evaluations.
+    FinalEvaluationMismatch,
+    FriError(FriError<FriMmcsError, InputMmcsError>),
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger> MultilinearPcs<Challenge, Challenger>
+    for BasefoldPcs<Val, Dft, InputMmcs, FriMmcs>
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val>,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+    Challenge: TwoAdicField + ExtensionField<Val>,
+    Challenger:
+        FieldChallenger<Val> + CanObserve<FriMmcs::Commitment> + GrindingChallenger<Witness = Val>,
+{
+    type Val = Val;
+    type Commitment = InputMmcs::Commitment;
+    type ProverData = BasefoldProverData<Val, InputMmcs>;
+    type Proof = BasefoldProof<Val, Challenge, InputMmcs, FriMmcs>;
+    type Error = BasefoldError<FriMmcs::Error, InputMmcs::Error>;
+
+    fn commit(
+        &self,
+        evaluations: Vec<RowMajorMatrix<Val>>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let codewords = evaluations
+            .iter()
+            .map(|evals| {
+                let mut coeffs = evals.clone();
+                hypercube_evals_to_coeffs(&mut coeffs);
+                // Encode on the subgroup (rather than a coset) so that folding exactly binds the
+                // variables of the multilinear polynomial, without any shift.
+                coeffs
+                    .values
+                    .resize(coeffs.values.len() << self.fri.log_blowup, Val::ZERO);
+                self.dft
+                    .dft_batch(coeffs)
+                    .bit_reverse_rows()
+                    .to_row_major_matrix()
+            })
+            .collect();
+        let (commitment, codewords) = self.mmcs.commit(codewords);
+        (
+            commitment,
+            BasefoldProverData {
+                evaluations,
+                codewords,
+            },
+        )
+    }
+
+    fn open(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<
+                // points to open
+                Vec<Vec<Challenge>>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        let mats = rounds
+            .iter()
+            .flat_map(|(data, _)| &data.evaluations)
+            .collect_vec();
+        let num_vars = log2_strict_usize(mats[0].height());
+        assert!(
+            mats.iter().all(|m| m.height() == 1 << num_vars),
+            "all polynomials opened together must have the same number of variables"
+        );
+
+        // Evaluate each polynomial at its points and write the openings to the challenger.
+        let all_opened_values = info_span!("compute opened values").in_scope(|| {
+            rounds
+                .iter()
+                .map(|(data, points)| {
+                    izip!(&data.evaluations, points)
+                        .map(|(mat, points_for_mat)| {
+                            points_for_mat
+                                .iter()
+                                .map(|point| {
+                                    assert_eq!(point.len(), num_vars);
+                                    let ys = mat.columnwise_dot_product(&eq_evals(point));
+                                    ys.iter()
+                                        .for_each(|&y| challenger.observe_algebra_element(y));
+                                    ys
+                                })
+                                .collect_vec()
+                        })
+                        .collect_vec()
+                })
+                .collect_vec()
+        });
+
+        let points = rounds.iter().map(|(_, points)| points).collect_vec();
+        let (point, batching_sumcheck, batched_values) = if let Some(point) = common_point(&points)
+        {
+            (point.clone(), vec![], vec![])
+        } else {
+            // Reduce the claims at all points to claims at a single random point, by running a
+            // sumcheck over sum_j eq(z_j, X) g_j(X), where g_j is a random linear combination of
+            // the polynomials opened at z_j.
+            let alpha: Challenge = challenger.sample_algebra_element();
+            let mut distinct_points: Vec<&Vec<Challenge>> = vec![];
+            let mut combined = vec![];
+            let mut alpha_pow_offset = Challenge::ONE;
+            for (data, points) in &rounds {
+                for (mat, points_for_mat) in izip!(&data.evaluations, points) {
+                    let mat_compressed = mat.dot_ext_powers(alpha).collect::<Vec<_>>();
+                    for point in points_for_mat {
+                        let j = distinct_points
+                            .iter()
+                            .position(|&p| p == point)
+                            .unwrap_or_else(|| {
+                                distinct_points.push(point);
+                                combined.push(Challenge::zero_vec(1 << num_vars));
+                                distinct_points.len() - 1
+                            });
+                        combined[j]
+                            .par_iter_mut()
+                            .zip(&mat_compressed)
+                            .for_each(|(acc, &x)| *acc += alpha_pow_offset * x);
+                        alpha_pow_offset *= alpha.exp_u64(mat.width() as u64);
+                    }
+                }
+            }
+
+            let mut sumcheck = EqSumcheck {
+                terms: izip!(distinct_points, combined)
+                    .map(|(point, evals)| (eq_evals(point), evals))
+                    .collect(),
+            };
+            let (batching_sumcheck, point): (Vec<_>, Vec<_>) = (0..num_vars)
+                .map(|_| {
+                    let round_poly = sumcheck.round_poly();
+                    round_poly
+                        .iter()
+                        .for_each(|&y| challenger.observe_algebra_element(y));
+                    let r: Challenge = challenger.sample_algebra_element();
+                    sumcheck.bind(r);
+                    (round_poly, r)
+                })
+                .unzip();
+
+            let eq = eq_evals(&point);
+            let batched_values = rounds
+                .iter()
+                .map(|(data, _)| {
+                    data.evaluations
+                        .iter()
+                        .map(|mat| {
+                            let ys = mat.columnwise_dot_product(&eq);
+                            ys.iter()
+                                .for_each(|&y| challenger.observe_algebra_element(y));
+                            vec![ys]
+                        })
+                        .collect_vec()
+                })
+                .collect_vec();
+            (point, batching_sumcheck, batched_values)
+        };
+
+        // Batch combination challenge
+        let gamma: Challenge = challenger.sample_algebra_element();
+
+        let mut combined_evals = Challenge::zero_vec(1 << num_vars);
+        let mut fri_input = Challenge::zero_vec(1 << (num_vars + self.fri.log_blowup));
+        let mut gamma_pow_offset = Challenge::ONE;
+        info_span!("combine polynomials").in_scope(|| {
+            for (data, _) in &rounds {
+                let codewords = self.mmcs.get_matrices(&data.codewords);
+                for (mat, codeword) in izip!(&data.evaluations, codewords) {
+                    for (acc, x) in izip!(
+                        &mut combined_evals,
+                        mat.dot_ext_powers(gamma).collect::<Vec<_>>()
+                    ) {
+                        *acc += gamma_pow_offset * x;
+                    }
+                    for (acc, x) in izip!(
+                        &mut fri_input,
+                        codeword.dot_ext_powers(gamma).collect::<Vec<_>>()
+                    ) {
+                        *acc += gamma_pow_offset * x;
+                    }
+                    gamma_pow_offset *= gamma.exp_u64(mat.width() as u64);
+                }
+            }
+        });
+
+        let mut sumcheck = EqSumcheck {
+            terms: vec![(eq_evals(&point), combined_evals)],
+        };
+        let mut sumcheck_polys = vec![];
+
+        let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
+            TwoAdicFriGenericConfig(PhantomData);
+
+        let fri_proof = prover::prove_with_folding_challenges(
+            &g,
+            &self.fri,
+            vec![fri_input],
+            challenger,
+            |_, challenger| {
+                let round_poly = sumcheck.round_poly();
+                round_poly
+                    .iter()
+                    .for_each(|&y| challenger.observe_algebra_element(y));
+                let r: Challenge = challenger.sample_algebra_element();
+                sumcheck.bind(r);
+                sumcheck_polys.push(round_poly);
+                r
+            },
+            |index| {
+                rounds
+                    .iter()
+                    .map(|(data, _)| {
+                        let (opened_values, opening_proof) =
+                            self.mmcs.open_batch(index, &data.codewords);
+                        BatchOpening {
+                            opened_values,
+                            opening_proof: Some(opening_proof),
+                        }
+                    })
+                    .collect()
+            },
+        );
+
+        (
+            all_opened_values,
+            BasefoldProof {
+                batching_sumcheck,
+                batched_values,
+                sumcheck: sumcheck_polys,
+                fri_proof,
+            },
+        )
+    }
+
+    fn verify(
+        &self,
+        // For each round:
+        rounds: Vec<(
+            Self::Commitment,
+            // for each matrix:
+            Vec<(
+                // its number of variables,
+                usize,
+                // for each point:
+                Vec<(
+                    // the point,
+                    Vec<Challenge>,
+                    // values at the point
+                    Vec<Challenge>,
+                )>,
+            )>,
+        )>,
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error> {
+        let num_vars = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .map(|(num_vars, _)| *num_vars)
+            .next()
+            .ok_or(BasefoldError::InvalidProofShape)?;
+        if rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .any(|(n, points)| *n != num_vars || points.iter().any(|(z, _)| z.len() != num_vars))
+        {
+            return Err(BasefoldError::InvalidProofShape);
+        }
+
+        // Write evaluations to challenger
+        for (_, round) in &rounds {
+            for (_, mat) in round {
+                for (_, point) in mat {
+                    point
+                        .iter()
+                        .for_each(|&opening| challenger.observe_algebra_element(opening));
+                }
+            }
+        }
+
+        let points = rounds
+            .iter()
+            .map(|(_, mats)| {
+                mats.iter()
+                    .map(|(_, points)| points.iter().map(|(z, _)| z.clone()).collect_vec())
+                    .collect_vec()
+            })
+            .collect_vec();
+        let (point, values) = if let Some(point) = common_point(&points.iter().collect_vec()) {
+            let values = rounds
+                .iter()
+                .map(|(_, mats)| {
+                    mats.iter()
+                        .map(|(_, points)| points.iter().map(|(_, ys)| ys.clone()).collect_vec())
+                        .collect_vec()
+                })
+                .collect_vec();
+            (point.clone(), values)
+        } else {
+            if proof.batching_sumcheck.len() != num_vars
+                || proof.batched_values.len() != rounds.len()
+                || izip!(&proof.batched_values, &rounds).any(|(batched, (_, mats))| {
+                    batched.len() != mats.len()
+                        || izip!(batched, mats).any(|(ys, (_, points))| {
+                            ys.len() != 1 || points.iter().any(|(_, v)| v.len() != ys[0].len())
+                        })
+                })
+            {
+                return Err(BasefoldError::InvalidProofShape);
+            }
+
+            let alpha: Challenge = challenger.sample_algebra_element();
+            let mut claim = Challenge::ZERO;
+            let mut alpha_pow_offset = Challenge::ONE;
+            for (_, mats) in &rounds {
+                for (_, points) in mats {
+                    for (_, ys) in points {
+                        claim += alpha_pow_offset
+                            * dot_product::<Challenge, _, _>(alpha.powers(), ys.iter().copied());
+                        alpha_pow_offset *= alpha.exp_u64(ys.len() as u64);
+                    }
+                }
+            }
+
+            let mut point = vec![];
+            for round_poly in &proof.batching_sumcheck {
+                if round_poly[0] + round_poly[1] != claim {
+                    return Err(BasefoldError::SumcheckMismatch);
+                }
+                round_poly
+                    .iter()
+                    .for_each(|&y| challenger.observe_algebra_element(y));
+                let r: Challenge = challenger.sample_algebra_element();
+                claim = eval_round_poly(round_poly, r);
+                point.push(r);
+            }
+
+            proof
+                .batched_values
+                .iter()
+                .flatten()
+                .flatten()
+                .flatten()
+                .for_each(|&y| challenger.observe_algebra_element(y));
+
+            let mut expected = Challenge::ZERO;
+            let mut alpha_pow_offset = Challenge::ONE;
+            for ((_, mats), batched) in izip!(&rounds, &proof.batched_values) {
+                for ((_, points), ys) in izip!(mats, batched) {
+                    let ys = &ys[0];
+                    for (z, _) in points {
+                        expected += eq_eval(z, &point)
+                            * alpha_pow_offset
+                            * dot_product::<Challenge, _, _>(alpha.powers(), ys.iter().copied());
+                        alpha_pow_offset *= alpha.exp_u64(ys.len() as u64);
+                    }
+                }
+            }
+            if expected != claim {
+                return Err(BasefoldError::SumcheckMismatch);
+            }
+
+            (point, proof.batched_values.clone())
+        };
+
+        // Batch combination challenge
+        let gamma: Challenge = challenger.sample_algebra_element();
+
+        let mut claim = Challenge::ZERO;
+        let mut gamma_pow_offset = Challenge::ONE;
+        for ys in values.iter().flatten().filter_map(|v| v.first()) {
+            claim += gamma_pow_offset
+                * dot_product::<Challenge, _, _>(gamma.powers(), ys.iter().copied());
+            gamma_pow_offset *= gamma.exp_u64(ys.len() as u64);
+        }
+
+        let num_rounds = proof.fri_proof.commit_phase_commits.len();
+        // The inputs are always opened with a proof for each query.
+        let inputs_proven = proof.fri_proof.query_proofs.iter().all(|query_proof| {
+            query_proof
+                .input_proof
+                .iter()
+                .all(|batch_opening| batch_opening.opening_proof.is_some())
+        });
+        if proof.sumcheck.len() != num_rounds
+            || num_rounds + self.fri.log_final_poly_len != num_vars
+            || !inputs_proven
+        {
+            return Err(BasefoldError::InvalidProofShape);
+        }
+
+        let log_height = num_vars + self.fri.log_blowup;
+        let mut sumcheck_challenges = vec![];
+
+        let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
+            TwoAdicFriGenericConfig(PhantomData);
+
+        verifier::verify_with_folding_challenges(
+            &g,
+            &self.fri,
+            &proof.fri_proof,
+            challenger,
+            |round, challenger| {
+                proof.sumcheck[round]
+                    .iter()
+                    .for_each(|&y| challenger.observe_algebra_element(y));
+                let r: Challenge = challenger.sample_algebra_element();
+                sumcheck_challenges.push(r);
+                r
+            },
+            |index, input_proof| {
+                let mut ro = Challenge::ZERO;
+                let mut gamma_pow_offset = Challenge::ONE;
+                for (batch_opening, (batch_commit, _)) in izip!(input_proof, &rounds) {
+                    let batch_dims = batch_opening
+                        .opened_values
+                        .iter()
+                        .map(|row| Dimensions {
+                            width: row.len(),
+                            height: 1 << log_height,
+                        })
+                        .collect_vec();
+                    self.mmcs.verify_batch(
+                        batch_commit,
+                        &batch_dims,
+                        index,
+                        &batch_opening.opened_values,
+                        batch_opening
+                            .opening_proof
+                            .as_ref()
+                            .expect("input proofs were checked to be present"),
+                    )?;
+
+                    for row in &batch_opening.opened_values {
+                        ro += gamma_pow_offset
+                            * dot_product::<Challenge, _, _>(gamma.powers(), row.iter().copied());
+                        gamma_pow_offset *= gamma.exp_u64(row.len() as u64);
+                    }
+                }
+                Ok(vec![(log_height, ro)])
+            },
+        )
+        .map_err(BasefoldError::FriError)?;
+
+        for (round_poly, &r) in izip!(&proof.sumcheck, &sumcheck_challenges) {
+            if round_poly[0] + round_poly[1] != claim {
+                return Err(BasefoldError::SumcheckMismatch);
+            }
+            claim = eval_round_poly(round_poly, r);
+        }
+
+        // The final polynomial holds the monomial coefficients of the combined polynomial with its
+        // first `num_rounds` variables bound to the sumcheck challenges.
+        let (point_prefix, point_suffix) = point.split_at(num_rounds);
+        let final_eval = proof
+            .fri_proof
+            .final_poly
+            .iter()
+            .enumerate()
+            .map(|(c, &coeff)| {
+                point_suffix
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| c >> i & 1 == 1)
+                    .fold(coeff, |acc, (_, &z)| acc * z)
+            })
+            .sum::<Challenge>();
+        if eq_eval(point_prefix, &sumcheck_challenges) * final_eval != claim {
+            return Err(BasefoldError::FinalEvaluationMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the opening point if every matrix is opened at exactly one point, and it is the same
+/// point for all matrices.
+fn common_point<'a, EF: Field>(points: &[&'a Vec<Vec<Vec<EF>>>]) -> Option<&'a Vec<EF>> {
+    let mut points_for_mats = points.iter().flat_map(|points| points.iter());
+    let first = points_for_mats.next()?;
+    if first.len() != 1 {
+        return None;
+    }
+    points_for_mats
+        .all(|p| p.len() == 1 && p[0] == first[0])
+        .then_some(&first[0])
+}
+
+/// Converts, in place, the columns of `mat` from evaluations over the boolean hypercube to
+/// coefficients in the multilinear monomial basis.
+#[instrument(skip_all, fields(dims = %mat.dimensions()))]
+fn hypercube_evals_to_coeffs<F: Field>(mat: &mut RowMajorMatrix<F>) {
+    let width = mat.width();
+    let num_vars = log2_strict_usize(mat.height());
+    for i in 0..num_vars {
+        let half = width << i;
+        mat.values.par_chunks_exact_mut(2 * half).for_each(|chunk| {
+            let (lo, hi) = chunk.split_at_mut(half);
+            izip!(hi, lo).for_each(|(h, &mut l)| *h -= l);
+        });
+    }
+}
+
+/// The evaluations of `eq(point, b)` for all `b` in the boolean hypercube.
+fn eq_evals<EF: Field>(point: &[EF]) -> Vec<EF> {
+    let mut evals = vec![EF::ONE];
+    for &z in point {
+        let hi = evals.iter().map(|&e| e * z).collect_vec();
+        izip!(&mut evals, &hi).for_each(|(e, &h)| *e -= h);
+        evals.extend(hi);
+    }
+    evals
+}
+
+/// Evaluates `eq(x, y) = prod_i (x_i y_i + (1 - x_i)(1 - y_i))`.
+fn eq_eval<EF: Field>(x: &[EF], y: &[EF]) -> EF {
+    izip!(x, y)
+        .map(|(&x, &y)| x * y + (EF::ONE - x) * (EF::ONE - y))
+        .product()
+}
+
+/// Evaluates at `r` the quadratic polynomial given by its values at 0, 1 and 2.
+fn eval_round_poly<EF: Field>(evals: &[EF; 3], r: EF) -> EF {
+    let [e0, e1, e2] = *evals;
+    let (r1, r2) = (r - EF::ONE, r - EF::TWO);
+    (e0 * r1 * r2 + e2 * r * r1).halve() - e1 * r * r2
+}
+
+/// Prover state of a sumcheck for `sum_b sum_j eq_j(b) f_j(b)`, binding the lowest variable first.
+struct EqSumcheck<EF> {
+    terms: Vec<(Vec<EF>, Vec<EF>)>,
+}
+
+impl<EF: Field> EqSumcheck<EF> {
+    /// The current round polynomial, evaluated at 0, 1 and 2.
+    fn round_poly(&self) -> [EF; 3] {
+        self.terms
+            .iter()
+            .map(|(eq, f)| {
+                eq.par_chunks_exact(2)
+                    .zip(f.par_chunks_exact(2))
+                    .par_fold_reduce(
+                        || [EF::ZERO; 3],
+                        |acc, (e, v)| {
+                            [
+                                acc[0] + e[0] * v[0],
+                                acc[1] + e[1] * v[1],
+                                acc[2] + (e[1].double() - e[0]) * (v[1].double() - v[0]),
+                            ]
+                        },
+                        |a, b| core::array::from_fn(|i| a[i] + b[i]),
+                    )
+            })
+            .fold([EF::ZERO; 3], |a, b| core::array::from_fn(|i| a[i] + b[i]))
+    }
+
+    /// Binds the lowest remaining variable to `r`.
+    fn bind(&mut self, r: EF) {
+        for (eq, f) in &mut self.terms {
+            for table in [eq, f] {
+                *table = table
+                    .par_chunks_exact(2)
+                    .map(|t| t[0] + r * (t[1] - t[0]))
+                    .collect();
+            }
+        }
+    }
+}
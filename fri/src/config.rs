@@ -3,14 +3,26 @@ use core::fmt::Debug;
 
 use p3_field::Field;
 use p3_matrix::Matrix;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
 
 #[derive(Debug)]
 pub struct FriConfig<M> {
     pub log_blowup: usize,
-    // TODO: This parameter and FRI early stopping are not yet implemented in `CirclePcs`.
     pub log_final_poly_len: usize,
     pub num_queries: usize,
     pub proof_of_work_bits: usize,
+    /// The number of bits of proof of work ground before the batching challenge and each folding
+    /// challenge of the commit phase, in addition to `proof_of_work_bits` before the queries. If
+    /// zero, there is no such grinding and proofs carry no witnesses for it.
+    pub commit_proof_of_work_bits: usize,
+    /// The log of the maximum number of evaluations folded into one by a commit phase round.
+    /// Must be at least 1. Rounds may use a smaller arity so that they end exactly at the height
+    /// of the next input, or at the final polynomial's height.
+    pub log_folding_arity: usize,
+    /// Whether the openings of each commit phase codeword, and of the inputs if the PCS supports
+    /// it, are proven with a single Merkle multiproof for all queries rather than one proof per
+    /// query. This shrinks proofs, since siblings shared between queries are only sent once.
+    pub use_multiproofs: bool,
     pub mmcs: M,
 }
 
@@ -23,6 +35,28 @@ impl<M> FriConfig<M> {
         1 << self.log_final_poly_len
     }
 
+    pub const fn folding_arity(&self) -> usize {
+        1 << self.log_folding_arity
+    }
+
+    /// Returns the log of the arity of a commit phase round starting from a domain of size
+    /// `2^log_height`, given the log height of the next input to roll in (if any) and the log
+    /// height at which the commit phase stops.
+    pub fn log_arity_for_round(
+        &self,
+        log_height: usize,
+        next_input_log_height: Option<usize>,
+        log_final_height: usize,
+    ) -> usize {
+        assert!(
+            self.log_folding_arity > 0,
+            "log_folding_arity must be positive"
+        );
+        let log_target_height =
+            next_input_log_height.map_or(log_final_height, |h| core::cmp::max(h, log_final_height));
+        core::cmp::min(self.log_folding_arity, log_height - log_target_height)
+    }
+
     /// Returns the soundness bits of this FRI instance based on the
     /// [ethSTARK](https://eprint.iacr.org/2021/582) conjecture.
     ///
@@ -44,8 +78,8 @@ pub trait FriGenericConfig<F: Field> {
     fn extra_query_index_bits(&self) -> usize;
 
     /// Fold a row, returning a single column.
-    /// Right now the input row will always be 2 columns wide,
-    /// but we may support higher folding arity in the future.
+    /// The input row is always 2 columns wide; higher folding arities are handled by
+    /// `fold_row_with_arity`, which folds repeatedly.
     fn fold_row(
         &self,
         index: usize,
@@ -56,16 +90,61 @@ pub trait FriGenericConfig<F: Field> {
 
     /// Same as applying fold_row to every row, possibly faster.
     fn fold_matrix<M: Matrix<F>>(&self, beta: F, m: M) -> Vec<F>;
+
+    /// Fold a row of `2^log_arity` evaluations into one, by folding pairs `log_arity` times with
+    /// `beta, beta^2, beta^4, ...`. `index` is the index of the row in the folded domain, of size
+    /// `2^log_folded_height`.
+    fn fold_row_with_arity(
+        &self,
+        index: usize,
+        log_folded_height: usize,
+        log_arity: usize,
+        mut beta: F,
+        mut evals: Vec<F>,
+    ) -> F {
+        for remaining_log_arity in (0..log_arity).rev() {
+            evals = evals
+                .chunks_exact(2)
+                .enumerate()
+                .map(|(i, pair)| {
+                    self.fold_row(
+                        (index << remaining_log_arity) | i,
+                        log_folded_height + remaining_log_arity,
+                        beta,
+                        pair.iter().copied(),
+                    )
+                })
+                .collect();
+            beta = beta.square();
+        }
+        evals[0]
+    }
+
+    /// Same as applying `fold_row_with_arity` to every chunk of `2^log_arity` evaluations.
+    fn fold_matrix_with_arity(&self, mut beta: F, log_arity: usize, evals: &[F]) -> Vec<F> {
+        let mut folded = self.fold_matrix(beta, RowMajorMatrixView::new(evals, 2));
+        for _ in 1..log_arity {
+            beta = beta.square();
+            folded = self.fold_matrix(beta, RowMajorMatrix::new(folded, 2));
+        }
+        folded
+    }
 }
 
 /// Creates a minimal `FriConfig` for testing purposes.
 /// This configuration is designed to reduce computational cost during tests.
-pub const fn create_test_fri_config<Mmcs>(mmcs: Mmcs) -> FriConfig<Mmcs> {
+pub const fn create_test_fri_config<Mmcs>(
+    mmcs: Mmcs,
+    log_final_poly_len: usize,
+) -> FriConfig<Mmcs> {
     FriConfig {
         log_blowup: 1,
-        log_final_poly_len: 0,
+        log_final_poly_len,
         num_queries: 2,
         proof_of_work_bits: 1,
+        commit_proof_of_work_bits: 0,
+        log_folding_arity: 1,
+        use_multiproofs: false,
         mmcs,
     }
 }
@@ -78,6 +157,9 @@ pub const fn create_benchmark_fri_config<Mmcs>(mmcs: Mmcs) -> FriConfig<Mmcs> {
         log_final_poly_len: 0,
         num_queries: 100,
         proof_of_work_bits: 16,
+        commit_proof_of_work_bits: 0,
+        log_folding_arity: 1,
+        use_multiproofs: false,
         mmcs,
     }
 }
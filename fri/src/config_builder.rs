@@ -0,0 +1,304 @@
+use p3_field::{ExtensionField, TwoAdicField};
+
+use crate::FriConfig;
+
+/// How the soundness of a FRI instance is estimated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundnessModel {
+    /// The [ethSTARK](https://eprint.iacr.org/2021/582) conjecture, under which each query adds
+    /// `log_blowup` bits of security. This is what `FriConfig::conjectured_soundness_bits` uses.
+    Conjectured,
+    /// Provable soundness up to the Johnson bound, following
+    /// [BCIKS20](https://eprint.iacr.org/2020/654), under which each query adds a little under
+    /// `log_blowup / 2` bits, and the extension field must be considerably larger.
+    Proven,
+}
+
+/// Which cost a `FriConfigBuilder` should favour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FriOptimization {
+    /// A blowup of 2 and binary folding, at the cost of more queries.
+    ProverTime,
+    /// A blowup of 8, folding by 8 and multiproofs, for fewer and smaller queries.
+    ProofSize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FriConfigError {
+    /// The sizes of the fields were never set.
+    MissingFieldSize,
+    /// The maximum trace height was never set.
+    MissingMaxHeight,
+    /// The extension field is too small for the folding and batching challenges to reach the
+    /// target security.
+    ExtensionFieldTooSmall {
+        required_bits: usize,
+        extension_field_bits: usize,
+    },
+    /// The largest LDE domain is larger than the base field's two-adic subgroup.
+    MaxHeightTooLarge {
+        log_max_lde_height: usize,
+        two_adicity: usize,
+    },
+}
+
+/// Chooses `FriConfig` parameters for a target number of bits of security.
+///
+/// The blowup, folding arity and use of multiproofs follow from the `FriOptimization`; the number
+/// of queries is then the smallest which, together with the proof of work, reaches the target in
+/// the chosen `SoundnessModel`.
+#[derive(Debug, Clone)]
+pub struct FriConfigBuilder {
+    security_bits: usize,
+    soundness: SoundnessModel,
+    /// `log2` of the size of the extension field, rounded down.
+    extension_field_bits: Option<usize>,
+    two_adicity: Option<usize>,
+    log_max_height: Option<usize>,
+    optimization: FriOptimization,
+    proof_of_work_bits: Option<usize>,
+    commit_proof_of_work_bits: usize,
+}
+
+/// `log2(7 / 6)` in thousandths of a bit, rounded up: the loss per query in the proven model from
+/// decoding slightly below the Johnson bound, for the multiplicity `m = 3` of BCIKS20.
+const JOHNSON_SLACK_MILLIBITS: usize = 223;
+
+/// `log2((m + 1/2)^7)` for `m = 3`, rounded up, from the commit phase error of BCIKS20.
+const JOHNSON_COMMIT_PHASE_BITS: usize = 13;
+
+impl FriConfigBuilder {
+    /// Starts a builder targeting `security_bits` bits of conjectured security, optimized for
+    /// prover time.
+    pub const fn new(security_bits: usize) -> Self {
+        Self {
+            security_bits,
+            soundness: SoundnessModel::Conjectured,
+            extension_field_bits: None,
+            two_adicity: None,
+            log_max_height: None,
+            optimization: FriOptimization::ProverTime,
+            proof_of_work_bits: None,
+            commit_proof_of_work_bits: 0,
+        }
+    }
+
+    pub const fn soundness(mut self, soundness: SoundnessModel) -> Self {
+        self.soundness = soundness;
+        self
+    }
+
+    /// Sets the sizes of the fields: the base field has at least `2^base_field_bits` elements, and
+    /// challenges are drawn from its extension of degree `extension_degree`.
+    pub const fn field_bits(mut self, base_field_bits: usize, extension_degree: usize) -> Self {
+        self.extension_field_bits = Some(base_field_bits * extension_degree);
+        self
+    }
+
+    /// Sets the sizes of the fields to those of `Val` and `Challenge`, and checks the largest LDE
+    /// domain against the two-adicity of `Val`.
+    pub fn fields<Val: TwoAdicField, Challenge: ExtensionField<Val>>(mut self) -> Self {
+        self.extension_field_bits = Some(Challenge::bits() - 1);
+        self.two_adicity = Some(Val::TWO_ADICITY);
+        self
+    }
+
+    /// Sets the log of the height of the largest trace to be committed, before the blowup.
+    pub const fn log_max_height(mut self, log_max_height: usize) -> Self {
+        self.log_max_height = Some(log_max_height);
+        self
+    }
+
+    pub const fn optimize_for(mut self, optimization: FriOptimization) -> Self {
+        self.optimization = optimization;
+        self
+    }
+
+    /// Overrides the number of proof of work bits, which otherwise depends on the
+    /// `FriOptimization`. Each bit of proof of work saves queries worth one bit of security.
+    pub const fn proof_of_work_bits(mut self, proof_of_work_bits: usize) -> Self {
+        self.proof_of_work_bits = Some(proof_of_work_bits);
+        self
+    }
+
+    /// Sets the number of proof of work bits ground before the batching challenge and each folding
+    /// challenge, which is zero by default. Each bit lowers the size of extension field required
+    /// by one bit.
+    pub const fn commit_proof_of_work_bits(mut self, commit_proof_of_work_bits: usize) -> Self {
+        self.commit_proof_of_work_bits = commit_proof_of_work_bits;
+        self
+    }
+
+    /// Chooses the parameters and returns the resulting config, with `mmcs` committing to the
+    /// commit phase codewords.
+    pub fn build<M>(&self, mmcs: M) -> Result<FriConfig<M>, FriConfigError> {
+        let extension_field_bits = self
+            .extension_field_bits
+            .ok_or(FriConfigError::MissingFieldSize)?;
+        let log_max_height = self
+            .log_max_height
+            .ok_or(FriConfigError::MissingMaxHeight)?;
+
+        let (log_blowup, log_folding_arity, use_multiproofs, default_pow_bits) =
+            match self.optimization {
+                FriOptimization::ProverTime => (1, 1, false, 16),
+                FriOptimization::ProofSize => (3, 3, true, 20),
+            };
+        let proof_of_work_bits = self.proof_of_work_bits.unwrap_or(default_pow_bits);
+
+        let log_max_lde_height = log_max_height + log_blowup;
+        if let Some(two_adicity) = self
+            .two_adicity
+            .filter(|&two_adicity| log_max_lde_height > two_adicity)
+        {
+            return Err(FriConfigError::MaxHeightTooLarge {
+                log_max_lde_height,
+                two_adicity,
+            });
+        }
+
+        // The folding and batching challenges must make a bad codeword look close to the code with
+        // probability at most 2^-security_bits. Under the conjecture this is about the size of the
+        // domain over the size of the field; the proven bound is quadratic in the size of the domain.
+        let (required_bits, query_millibits) = match self.soundness {
+            SoundnessModel::Conjectured => {
+                (self.security_bits + log_max_lde_height, 1000 * log_blowup)
+            }
+            SoundnessModel::Proven => (
+                self.security_bits
+                    + JOHNSON_COMMIT_PHASE_BITS
+                    + 2 * log_max_lde_height
+                    + (3 * log_blowup).div_ceil(2)
+                    - 1,
+                500 * log_blowup - JOHNSON_SLACK_MILLIBITS,
+            ),
+        };
+        // The PCSs grind before every such challenge, so each bit of this PoW counts against them.
+        let required_bits = required_bits.saturating_sub(self.commit_proof_of_work_bits);
+        if extension_field_bits < required_bits {
+            return Err(FriConfigError::ExtensionFieldTooSmall {
+                required_bits,
+                extension_field_bits,
+            });
+        }
+
+        let query_bits = self.security_bits.saturating_sub(proof_of_work_bits);
+        let num_queries = (1000 * query_bits).div_ceil(query_millibits).max(1);
+
+        Ok(FriConfig {
+            log_blowup,
+            log_final_poly_len: 0,
+            num_queries,
+            proof_of_work_bits,
+            commit_proof_of_work_bits: self.commit_proof_of_work_bits,
+            log_folding_arity,
+            use_multiproofs,
+            mmcs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_goldilocks::Goldilocks;
+
+    use super::*;
+
+    #[test]
+    fn conjectured_babybear() {
+        let config = FriConfigBuilder::new(100)
+            .fields::<BabyBear, BinomialExtensionField<BabyBear, 4>>()
+            .log_max_height(20)
+            .build(())
+            .unwrap();
+        assert_eq!(config.log_blowup, 1);
+        assert_eq!(config.proof_of_work_bits, 16);
+        assert_eq!(config.num_queries, 84);
+        assert!(config.conjectured_soundness_bits() >= 100);
+
+        let config = FriConfigBuilder::new(100)
+            .fields::<BabyBear, BinomialExtensionField<BabyBear, 4>>()
+            .log_max_height(20)
+            .optimize_for(FriOptimization::ProofSize)
+            .build(())
+            .unwrap();
+        assert_eq!(config.log_blowup, 3);
+        assert_eq!(config.num_queries, 27);
+        assert!(config.use_multiproofs);
+        assert!(config.conjectured_soundness_bits() >= 100);
+    }
+
+    #[test]
+    fn proven_needs_larger_extension() {
+        let builder = FriConfigBuilder::new(100)
+            .soundness(SoundnessModel::Proven)
+            .log_max_height(20);
+        assert_eq!(
+            builder
+                .clone()
+                .fields::<BabyBear, BinomialExtensionField<BabyBear, 4>>()
+                .build(())
+                .err(),
+            Some(FriConfigError::ExtensionFieldTooSmall {
+                required_bits: 156,
+                extension_field_bits: 123,
+            })
+        );
+
+        let config = builder
+            .clone()
+            .field_bits(30, 8)
+            .proof_of_work_bits(20)
+            .build(())
+            .unwrap();
+        // Each query adds 0.277 bits, so 80 bits take 289 queries.
+        assert_eq!(config.num_queries, 289);
+
+        // Grinding before the batching and each folding challenge makes up for the smaller
+        // extension field, but only once it covers the whole gap.
+        let builder = builder.fields::<BabyBear, BinomialExtensionField<BabyBear, 4>>();
+        assert_eq!(
+            builder
+                .clone()
+                .commit_proof_of_work_bits(32)
+                .build(())
+                .err(),
+            Some(FriConfigError::ExtensionFieldTooSmall {
+                required_bits: 124,
+                extension_field_bits: 123,
+            })
+        );
+        builder.commit_proof_of_work_bits(33).build(()).unwrap();
+    }
+
+    #[test]
+    fn rejects_incomplete_or_oversized() {
+        assert_eq!(
+            FriConfigBuilder::new(100)
+                .log_max_height(20)
+                .build(())
+                .err(),
+            Some(FriConfigError::MissingFieldSize)
+        );
+        assert_eq!(
+            FriConfigBuilder::new(100)
+                .fields::<Goldilocks, BinomialExtensionField<Goldilocks, 2>>()
+                .build(())
+                .err(),
+            Some(FriConfigError::MissingMaxHeight)
+        );
+        assert_eq!(
+            FriConfigBuilder::new(100)
+                .fields::<BabyBear, BinomialExtensionField<BabyBear, 4>>()
+                .log_max_height(27)
+                .build(())
+                .err(),
+            Some(FriConfigError::MaxHeightTooLarge {
+                log_max_lde_height: 28,
+                two_adicity: 27,
+            })
+        );
+    }
+}
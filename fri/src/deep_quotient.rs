@@ -0,0 +1,798 @@
+//! The DEEP quotient reduction used by `TwoAdicFriPcs`, which batches all opening claims into a
+//! single codeword per LDE height. It is public so that other low-degree tests over two-adic
+//! cosets can reuse it.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::{Itertools, izip};
+use p3_challenger::{FieldChallenger, GrindingChallenger};
+use p3_commit::{Mmcs, OpenedValues, PolynomialSpace, StoredMatrix, TwoAdicMultiplicativeCoset};
+use p3_field::{
+    ExtensionField, TwoAdicField, batch_multiplicative_inverse, cyclic_subgroup_coset_known_order,
+    dot_product,
+};
+use p3_interpolation::interpolate_coset;
+use p3_matrix::bitrev::BitReversalPerm;
+use p3_matrix::{Dimensions, Matrix};
+use p3_maybe_rayon::prelude::*;
+use p3_util::linear_map::LinearMap;
+use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits};
+use tracing::{info_span, instrument};
+
+use crate::BatchOpening;
+
+/// Evaluates the committed matrices at the requested points, observes the evaluations, and
+/// batches the quotients `(p(X) - p(z)) / (X - z)` with a random challenge into one codeword per
+/// LDE height.
+///
+/// Returns the opened values, along with the reduced codewords in bit-reversed order and sorted
+/// by descending height.
+///
+/// Panics if a point lies in the LDE domain of a matrix opened at it (see `in_lde_domain`), which
+/// only `open_and_reduce_with_degree_bounds` supports.
+#[allow(clippy::type_complexity)]
+pub fn open_and_reduce<Val, Challenge, InputMmcs, Challenger>(
+    mmcs: &InputMmcs,
+    log_blowup: usize,
+    // For each round, the prover data and, for each matrix, the points to open.
+    rounds: &[(
+        &InputMmcs::ProverData<StoredMatrix<Val, InputMmcs>>,
+        Vec<Vec<Challenge>>,
+    )],
+    challenger: &mut Challenger,
+) -> (OpenedValues<Challenge>, Vec<Vec<Challenge>>)
+where
+    Val: TwoAdicField,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    InputMmcs: Mmcs<Val>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger,
+{
+    for (data, points) in rounds {
+        for (mat, points_for_mat) in izip!(mmcs.get_matrices(data), points) {
+            let log_height = log2_strict_usize(mat.height());
+            assert!(
+                points_for_mat
+                    .iter()
+                    .all(|&point| !in_lde_domain::<Val, _>(point, log_height)),
+                "an opening point lies in the LDE domain of its matrix, which `open_and_reduce` does \
+                 not support"
+            );
+        }
+    }
+    let degree_bounds = rounds
+        .iter()
+        .map(|(data, _)| {
+            mmcs.get_matrices(data)
+                .iter()
+                .map(|mat| mat.height() >> log_blowup)
+                .collect_vec()
+        })
+        .collect_vec();
+    let (opened_values, _, reduced_codewords, _) =
+        open_and_reduce_with_degree_bounds(mmcs, log_blowup, rounds, &degree_bounds, 0, challenger);
+    (opened_values, reduced_codewords)
+}
+
+/// Whether `point` lies in the LDE domain of height `2^log_height` over which `TwoAdicFriPcs`
+/// commits, i.e. the coset of the two-adic subgroup of that size shifted by `Val::GENERATOR`.
+///
+/// The quotient `(p(X) - p(z)) / (X - z)` is undefined at `X = z` for such a point `z`, and takes
+/// the value `p'(z)` there instead.
+pub fn in_lde_domain<Val: TwoAdicField, Challenge: ExtensionField<Val>>(
+    point: Challenge,
+    log_height: usize,
+) -> bool {
+    (point * Val::GENERATOR.inverse()).exp_power_of_2(log_height) == Challenge::ONE
+}
+
+/// Like `open_and_reduce`, but also proves that the polynomial in each column of the `j`th matrix
+/// of round `i` has degree less than `degree_bounds[i][j]`, which may be anything from 1 up to
+/// the size `n` of the matrix's trace domain.
+///
+/// For a matrix with a degree bound `d < n`, each quotient `q(X) = (p(X) - p(z)) / (X - z)` is
+/// batched twice: as is, and multiplied by `X^{n - d + 1}`. As `q` has degree one less than `p`,
+/// the second term has degree less than `n`, as FRI requires, exactly when `p` has degree less
+/// than `d`; this is the degree correction `X^{n - d} p(X)` applied to the quotient.
+///
+/// Points may lie in the LDE domain of the matrices opened at them (see `in_lde_domain`). For
+/// each such point, the derivatives of the matrix's columns there are observed after its opened
+/// values, as the verifier needs them for the quotients at that point. These are returned after
+/// the opened values, in the same shape, with no derivatives for any other point.
+///
+/// If `alpha_proof_of_work_bits` is nonzero, a proof of work witness is ground before the
+/// batching challenge is sampled, and returned last.
+#[allow(clippy::type_complexity)]
+pub fn open_and_reduce_with_degree_bounds<Val, Challenge, InputMmcs, Challenger>(
+    mmcs: &InputMmcs,
+    log_blowup: usize,
+    rounds: &[(
+        &InputMmcs::ProverData<StoredMatrix<Val, InputMmcs>>,
+        Vec<Vec<Challenge>>,
+    )],
+    // For each round, for each matrix, the bound on the degree of its columns.
+    degree_bounds: &[Vec<usize>],
+    // The bits of proof of work to grind before sampling the batching challenge, if any.
+    alpha_proof_of_work_bits: usize,
+    challenger: &mut Challenger,
+) -> (
+    OpenedValues<Challenge>,
+    OpenedValues<Challenge>,
+    Vec<Vec<Challenge>>,
+    Option<Challenger::Witness>,
+)
+where
+    Val: TwoAdicField,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    InputMmcs: Mmcs<Val>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger,
+{
+    /*
+
+    A quick rundown of the optimizations in this function:
+    We are trying to compute sum_i alpha^i * (p(X) - y)/(X - z),
+    for each z an opening point, y = p(z). Each p(X) is given as evaluations in bit-reversed order
+    in the columns of the matrices. y is computed by barycentric interpolation.
+    X and p(X) are in the base field; alpha, y and z are in the extension.
+    The primary goal is to minimize extension multiplications.
+
+    - Instead of computing all alpha^i, we just compute alpha^i for i up to the largest width
+    of a matrix, then multiply by an "alpha offset" when accumulating.
+          a^0 x0 + a^1 x1 + a^2 x2 + a^3 x3 + ...
+        = a^0 ( a^0 x0 + a^1 x1 ) + a^2 ( a^0 x2 + a^1 x3 ) + ...
+        (see `alpha_pows`, `alpha_pow_offset`, `num_reduced`)
+
+    - For each unique point z, we precompute 1/(X-z) for the largest subgroup opened at this point.
+    Since we compute it in bit-reversed order, smaller subgroups can simply truncate the vector.
+        (see `inv_denoms`)
+
+    - Then, for each matrix (with columns p_i) and opening point z, we want:
+        for each row (corresponding to subgroup element X):
+            reduced[X] += alpha_offset * sum_i [ alpha^i * inv_denom[X] * (p_i[X] - y[i]) ]
+
+        We can factor out inv_denom, and expand what's left:
+            reduced[X] += alpha_offset * inv_denom[X] * sum_i [ alpha^i * p_i[X] - alpha^i * y[i] ]
+
+        And separate the sum:
+            reduced[X] += alpha_offset * inv_denom[X] * [ sum_i [ alpha^i * p_i[X] ] - sum_i [ alpha^i * y[i] ] ]
+
+        And now the last sum doesn't depend on X, so we can precompute that for the matrix, too.
+        So the hot loop (that depends on both X and i) is just:
+            sum_i [ alpha^i * p_i[X] ]
+
+        with alpha^i an extension, p_i[X] a base
+
+    - A matrix with a degree bound d below the size n of its trace domain takes up twice as many
+    powers of alpha, the second half of which multiply X^s times its quotients, for s = n - d + 1.
+    That is, its terms are multiplied by a single factor per row,
+        1 + alpha^width * X^s
+        (see `degree_correction`)
+
+    - A point z in the LDE domain has no inverse denominator at its own row, which is left as zero.
+    The row instead gets the value of the quotient there, p'(z), from the derivatives.
+        (see `derivatives_at_domain_point`)
+
+    */
+
+    let mats_and_points = rounds
+        .iter()
+        .map(|(data, points)| {
+            let mats = mmcs
+                .get_matrices(data)
+                .into_iter()
+                .map(|m| m.as_view())
+                .collect_vec();
+            debug_assert_eq!(
+                mats.len(),
+                points.len(),
+                "each matrix should have a corresponding set of evaluation points"
+            );
+            (mats, points)
+        })
+        .collect_vec();
+    assert_eq!(
+        degree_bounds.len(),
+        rounds.len(),
+        "each round should have a set of degree bounds"
+    );
+    for ((mats, _), bounds) in izip!(&mats_and_points, degree_bounds) {
+        assert_eq!(
+            bounds.len(),
+            mats.len(),
+            "each matrix should have a degree bound"
+        );
+        for (mat, &bound) in izip!(mats, bounds) {
+            assert!(
+                (1..=mat.height() >> log_blowup).contains(&bound),
+                "degree bound {bound} is out of range for a trace domain of size {}",
+                mat.height() >> log_blowup
+            );
+        }
+    }
+
+    // For each unique opening point z, we will find the largest degree bound
+    // for that point, and precompute 1/(z - X) for the largest subgroup (in bitrev order).
+    let inv_denoms = compute_inverse_denominators(&mats_and_points, Val::GENERATOR);
+
+    // Evaluate coset representations and write openings to the challenger
+    let (all_opened_values, all_derivatives): (Vec<_>, Vec<_>) = mats_and_points
+        .iter()
+        .map(|(mats, points)| {
+            izip!(mats.iter(), points.iter())
+                .map(|(mat, points_for_mat)| {
+                    points_for_mat
+                        .iter()
+                        .map(|&point| {
+                            let _guard =
+                                info_span!("evaluate matrix", dims = %mat.dimensions()).entered();
+
+                            // Use Barycentric interpolation to evaluate the matrix at the given point.
+                            let ys =
+                                info_span!("compute opened values with Lagrange interpolation")
+                                    .in_scope(|| {
+                                        let h = mat.height() >> log_blowup;
+                                        let (low_coset, _) = mat.split_rows(h);
+                                        let mut inv_denoms =
+                                            inv_denoms.get(&point).unwrap()[..h].to_vec();
+                                        reverse_slice_index_bits(&mut inv_denoms);
+                                        interpolate_coset(
+                                            &BitReversalPerm::new_view(low_coset),
+                                            Val::GENERATOR,
+                                            point,
+                                            Some(&inv_denoms),
+                                        )
+                                    });
+                            ys.iter()
+                                .for_each(|&y| challenger.observe_algebra_element(y));
+
+                            let derivatives = if in_lde_domain::<Val, _>(
+                                point,
+                                log2_strict_usize(mat.height()),
+                            ) {
+                                derivatives_at_domain_point(
+                                    mat,
+                                    point,
+                                    &inv_denoms.get(&point).unwrap()[..mat.height()],
+                                )
+                            } else {
+                                vec![]
+                            };
+                            derivatives
+                                .iter()
+                                .for_each(|&d| challenger.observe_algebra_element(d));
+                            (ys, derivatives)
+                        })
+                        .unzip::<_, _, Vec<_>, Vec<_>>()
+                })
+                .unzip::<_, _, Vec<_>, Vec<_>>()
+        })
+        .unzip();
+
+    // Batch combination challenge
+    let alpha_pow_witness =
+        (alpha_proof_of_work_bits > 0).then(|| challenger.grind(alpha_proof_of_work_bits));
+    let alpha: Challenge = challenger.sample_algebra_element();
+
+    let mut num_reduced = [0; 32];
+    let mut reduced_openings: [_; 32] = core::array::from_fn(|_| None);
+
+    for ((mats, points), openings_for_round, derivatives_for_round, bounds) in izip!(
+        &mats_and_points,
+        &all_opened_values,
+        &all_derivatives,
+        degree_bounds
+    ) {
+        for (mat, points_for_mat, openings_for_mat, derivatives_for_mat, &bound) in izip!(
+            mats.iter(),
+            points.iter(),
+            openings_for_round.iter(),
+            derivatives_for_round.iter(),
+            bounds
+        ) {
+            let _guard = info_span!("reduce matrix quotient", dims = %mat.dimensions()).entered();
+
+            let log_height = log2_strict_usize(mat.height());
+            let reduced_opening_for_log_height = reduced_openings[log_height]
+                .get_or_insert_with(|| vec![Challenge::ZERO; mat.height()]);
+            debug_assert_eq!(reduced_opening_for_log_height.len(), mat.height());
+
+            let mat_compressed = info_span!("compress mat")
+                .in_scope(|| mat.dot_ext_powers(alpha).collect::<Vec<_>>());
+
+            let correction = degree_correction_exponent(mat.height() >> log_blowup, bound)
+                .map(|exponent| degree_correction(alpha, mat.width(), log_height, exponent));
+
+            for (&point, openings, derivatives) in
+                izip!(points_for_mat, openings_for_mat, derivatives_for_mat)
+            {
+                let alpha_pow_offset = alpha.exp_u64(num_reduced[log_height] as u64);
+                let reduced_openings: Challenge =
+                    dot_product(alpha.powers(), openings.iter().copied());
+
+                info_span!("reduce rows").in_scope(|| {
+                    let rows = mat_compressed
+                        .par_iter()
+                        .zip(reduced_opening_for_log_height.par_iter_mut())
+                        // This might be longer, but zip will truncate to smaller subgroup
+                        // (which is ok because it's bitrev)
+                        .zip(inv_denoms.get(&point).unwrap().par_iter());
+                    match &correction {
+                        None => rows.for_each(|((&reduced_row, ro), &inv_denom)| {
+                            *ro += alpha_pow_offset * (reduced_openings - reduced_row) * inv_denom
+                        }),
+                        Some(correction) => rows.zip(correction.par_iter()).for_each(
+                            |(((&reduced_row, ro), &inv_denom), &factor)| {
+                                *ro += alpha_pow_offset
+                                    * (reduced_openings - reduced_row)
+                                    * inv_denom
+                                    * factor
+                            },
+                        ),
+                    }
+                });
+
+                if !derivatives.is_empty() {
+                    let row = inv_denoms.get(&point).unwrap()[..mat.height()]
+                        .iter()
+                        .position(|inv_denom| inv_denom.is_zero())
+                        .unwrap();
+                    let reduced_derivatives: Challenge =
+                        dot_product(alpha.powers(), derivatives.iter().copied());
+                    let factor = correction
+                        .as_ref()
+                        .map_or(Challenge::ONE, |correction| correction[row]);
+                    reduced_opening_for_log_height[row] +=
+                        alpha_pow_offset * reduced_derivatives * factor;
+                }
+
+                num_reduced[log_height] += mat.width();
+                if correction.is_some() {
+                    num_reduced[log_height] += mat.width();
+                }
+            }
+        }
+    }
+
+    let reduced_codewords = reduced_openings.into_iter().rev().flatten().collect_vec();
+    (
+        all_opened_values,
+        all_derivatives,
+        reduced_codewords,
+        alpha_pow_witness,
+    )
+}
+
+/// Returns the derivatives at `point` of the polynomials whose evaluations over an LDE coset are
+/// the columns of `mat`, in bit-reversed order. The point must lie in the coset, with `inv_denoms`
+/// holding `1 / (point - x)` for every other `x` of the coset and zero for the point itself.
+fn derivatives_at_domain_point<F, EF, M>(mat: &M, point: EF, inv_denoms: &[EF]) -> Vec<EF>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F>,
+    M: Matrix<F>,
+{
+    // Differentiating the barycentric formula, the derivative at a point x_j of the coset is
+    //     p'(x_j) = sum_{k != j} (x_k / x_j) (p(x_k) - p(x_j)) / (x_j - x_k),
+    // as the barycentric weights of a coset are proportional to x_k. Since
+    //     x_k / (x_j - x_k) = x_j / (x_j - x_k) - 1,
+    // the weight of p(x_k) - p(x_j) is 1 / (x_j - x_k) - 1 / x_j.
+    let row = inv_denoms
+        .iter()
+        .position(|inv_denom| inv_denom.is_zero())
+        .expect("the point lies in the coset");
+    let point_inv = point.inverse();
+    let weights = inv_denoms
+        .iter()
+        .enumerate()
+        .map(|(k, &inv_denom)| {
+            if k == row {
+                EF::ZERO
+            } else {
+                inv_denom - point_inv
+            }
+        })
+        .collect_vec();
+    let weight_sum: EF = weights.iter().copied().sum();
+    izip!(mat.columnwise_dot_product(&weights), mat.row(row))
+        .map(|(weighted_sum, p_at_point)| weighted_sum - weight_sum * p_at_point)
+        .collect()
+}
+
+/// Opens every committed batch at the row corresponding to `index` of the largest LDE domain, of
+/// size `2^log_global_max_height`.
+pub fn open_input_batches<Val, InputMmcs>(
+    mmcs: &InputMmcs,
+    rounds: &[&InputMmcs::ProverData<StoredMatrix<Val, InputMmcs>>],
+    log_global_max_height: usize,
+    index: usize,
+) -> Vec<BatchOpening<Val, InputMmcs>>
+where
+    Val: TwoAdicField,
+    InputMmcs: Mmcs<Val>,
+{
+    rounds
+        .iter()
+        .map(|data| {
+            let log_max_height = log2_strict_usize(mmcs.get_max_height(data));
+            let bits_reduced = log_global_max_height - log_max_height;
+            let reduced_index = index >> bits_reduced;
+            let (opened_values, opening_proof) = mmcs.open_batch(reduced_index, data);
+            BatchOpening {
+                opened_values,
+                opening_proof: Some(opening_proof),
+            }
+        })
+        .collect()
+}
+
+/// Like `open_input_batches` for each of `indices`, but proves the openings of each committed
+/// batch with a single multiproof.
+///
+/// Returns the openings of each query, which carry no proofs of their own, along with a multiproof
+/// for each batch.
+#[allow(clippy::type_complexity)]
+pub fn open_input_batches_multi<Val, InputMmcs>(
+    mmcs: &InputMmcs,
+    rounds: &[&InputMmcs::ProverData<StoredMatrix<Val, InputMmcs>>],
+    log_global_max_height: usize,
+    indices: &[usize],
+) -> (
+    Vec<Vec<BatchOpening<Val, InputMmcs>>>,
+    Vec<Vec<InputMmcs::Proof>>,
+)
+where
+    Val: TwoAdicField,
+    InputMmcs: Mmcs<Val>,
+{
+    let mut openings = (0..indices.len())
+        .map(|_| Vec::with_capacity(rounds.len()))
+        .collect_vec();
+    let multi_proofs = rounds
+        .iter()
+        .map(|data| {
+            let log_max_height = log2_strict_usize(mmcs.get_max_height(data));
+            let bits_reduced = log_global_max_height - log_max_height;
+            let reduced_indices = indices
+                .iter()
+                .map(|&index| index >> bits_reduced)
+                .collect_vec();
+            let (opened_values, multi_proof) = mmcs.open_multi(&reduced_indices, data);
+            for (query_openings, opened_values) in izip!(&mut openings, opened_values) {
+                query_openings.push(BatchOpening {
+                    opened_values,
+                    opening_proof: None,
+                });
+            }
+            multi_proof
+        })
+        .collect();
+    (openings, multi_proofs)
+}
+
+/// The verifier's counterpart of `open_and_reduce`: checks the openings of the committed batches
+/// at `index` of the largest LDE domain, of size `2^log_global_max_height`, and returns the values
+/// of the reduced codewords at the corresponding points, as `(log_height, value)` pairs sorted by
+/// descending height.
+///
+/// Openings without a proof of their own are not checked here; they must be checked with
+/// `verify_input_batches_multi` instead. The caller must make sure that no point lies in the LDE
+/// domain of a matrix opened at it, as `open_and_reduce` does.
+#[allow(clippy::type_complexity)]
+pub fn verify_and_reduce<Val, Challenge, InputMmcs>(
+    mmcs: &InputMmcs,
+    log_blowup: usize,
+    // For each round, the commitment and, for each matrix, its domain and the claimed values at
+    // each opening point.
+    rounds: &[(
+        InputMmcs::Commitment,
+        Vec<(
+            TwoAdicMultiplicativeCoset<Val>,
+            Vec<(Challenge, Vec<Challenge>)>,
+        )>,
+    )],
+    alpha: Challenge,
+    log_global_max_height: usize,
+    index: usize,
+    input_proof: &[BatchOpening<Val, InputMmcs>],
+) -> Result<Vec<(usize, Challenge)>, InputMmcs::Error>
+where
+    Val: TwoAdicField,
+    Challenge: ExtensionField<Val>,
+    InputMmcs: Mmcs<Val>,
+{
+    let degree_bounds = rounds
+        .iter()
+        .map(|(_, mats)| mats.iter().map(|(domain, _)| domain.size()).collect_vec())
+        .collect_vec();
+    verify_and_reduce_with_degree_bounds(
+        mmcs,
+        log_blowup,
+        rounds,
+        &degree_bounds,
+        &[],
+        alpha,
+        log_global_max_height,
+        index,
+        input_proof,
+    )
+}
+
+/// The verifier's counterpart of `open_and_reduce_with_degree_bounds`.
+///
+/// Each degree bound must be in the range from 1 up to the size of the matrix's domain, and
+/// `derivatives` must hold the derivatives sent for each point in the LDE domain of its matrix,
+/// in the shape of the opened values.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn verify_and_reduce_with_degree_bounds<Val, Challenge, InputMmcs>(
+    mmcs: &InputMmcs,
+    log_blowup: usize,
+    rounds: &[(
+        InputMmcs::Commitment,
+        Vec<(
+            TwoAdicMultiplicativeCoset<Val>,
+            Vec<(Challenge, Vec<Challenge>)>,
+        )>,
+    )],
+    degree_bounds: &[Vec<usize>],
+    derivatives: &[Vec<Vec<Vec<Challenge>>>],
+    alpha: Challenge,
+    log_global_max_height: usize,
+    index: usize,
+    input_proof: &[BatchOpening<Val, InputMmcs>],
+) -> Result<Vec<(usize, Challenge)>, InputMmcs::Error>
+where
+    Val: TwoAdicField,
+    Challenge: ExtensionField<Val>,
+    InputMmcs: Mmcs<Val>,
+{
+    // log_height -> (alpha_pow, reduced_opening)
+    let mut reduced_openings = BTreeMap::<usize, (Challenge, Challenge)>::new();
+
+    for (round, (batch_opening, (batch_commit, mats), bounds)) in
+        izip!(input_proof, rounds, degree_bounds).enumerate()
+    {
+        if let Some(opening_proof) = &batch_opening.opening_proof {
+            let batch_dims = batch_dims(log_blowup, mats);
+            let reduced_index = batch_index(&batch_dims, log_global_max_height, index);
+            mmcs.verify_batch(
+                batch_commit,
+                &batch_dims,
+                reduced_index,
+                &batch_opening.opened_values,
+                opening_proof,
+            )?;
+        }
+
+        for (mat, (mat_opening, (mat_domain, mat_points_and_values), &bound)) in
+            izip!(&batch_opening.opened_values, mats, bounds).enumerate()
+        {
+            let log_height = log2_strict_usize(mat_domain.size()) + log_blowup;
+
+            let bits_reduced = log_global_max_height - log_height;
+            let rev_reduced_index = reverse_bits_len(index >> bits_reduced, log_height);
+
+            // todo: this can be nicer with domain methods?
+
+            let x = Val::GENERATOR
+                * Val::two_adic_generator(log_height).exp_u64(rev_reduced_index as u64);
+
+            let (alpha_pow, ro) = reduced_openings
+                .entry(log_height)
+                .or_insert((Challenge::ONE, Challenge::ZERO));
+
+            // See `open_and_reduce_with_degree_bounds`.
+            let correction = degree_correction_exponent(mat_domain.size(), bound).map(|exponent| {
+                let alpha_pow_width = alpha.exp_u64(mat_opening.len() as u64);
+                (
+                    alpha_pow_width,
+                    Challenge::ONE + alpha_pow_width * x.exp_u64(exponent as u64),
+                )
+            });
+
+            for (point, (z, ps_at_z)) in mat_points_and_values.iter().enumerate() {
+                let mut reduced_point = Challenge::ZERO;
+                if *z == Challenge::from(x) {
+                    // The quotient at its own point is the derivative.
+                    for &derivative in &derivatives[round][mat][point] {
+                        reduced_point += *alpha_pow * derivative;
+                        *alpha_pow *= alpha;
+                    }
+                } else {
+                    for (&p_at_x, &p_at_z) in izip!(mat_opening, ps_at_z) {
+                        let quotient = (-p_at_z + p_at_x) / (-*z + x);
+                        reduced_point += *alpha_pow * quotient;
+                        *alpha_pow *= alpha;
+                    }
+                }
+                match correction {
+                    None => *ro += reduced_point,
+                    Some((alpha_pow_width, factor)) => {
+                        *ro += reduced_point * factor;
+                        *alpha_pow *= alpha_pow_width;
+                    }
+                }
+            }
+        }
+    }
+
+    // Return reduced openings descending by log_height.
+    Ok(reduced_openings
+        .into_iter()
+        .rev()
+        .map(|(log_height, (_alpha_pow, ro))| (log_height, ro))
+        .collect())
+}
+
+/// Checks the multiproofs produced by `open_input_batches_multi`, given the queried `indices` of
+/// the largest LDE domain and the input proof of each query.
+#[allow(clippy::type_complexity)]
+pub fn verify_input_batches_multi<Val, Challenge, InputMmcs>(
+    mmcs: &InputMmcs,
+    log_blowup: usize,
+    rounds: &[(
+        InputMmcs::Commitment,
+        Vec<(
+            TwoAdicMultiplicativeCoset<Val>,
+            Vec<(Challenge, Vec<Challenge>)>,
+        )>,
+    )],
+    log_global_max_height: usize,
+    indices: &[usize],
+    input_proofs: &[&[BatchOpening<Val, InputMmcs>]],
+    multi_proofs: &[Vec<InputMmcs::Proof>],
+) -> Result<(), InputMmcs::Error>
+where
+    Val: TwoAdicField,
+    InputMmcs: Mmcs<Val>,
+{
+    for (batch, ((batch_commit, mats), multi_proof)) in izip!(rounds, multi_proofs).enumerate() {
+        let batch_dims = batch_dims(log_blowup, mats);
+        let reduced_indices = indices
+            .iter()
+            .map(|&index| batch_index(&batch_dims, log_global_max_height, index))
+            .collect_vec();
+        let opened_values = input_proofs
+            .iter()
+            .map(|input_proof| input_proof[batch].opened_values.clone())
+            .collect_vec();
+        mmcs.verify_multi(
+            batch_commit,
+            &batch_dims,
+            &reduced_indices,
+            &opened_values,
+            multi_proof,
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the dimensions of the LDEs of the matrices of a committed batch.
+#[allow(clippy::type_complexity)]
+fn batch_dims<Val: TwoAdicField, Challenge>(
+    log_blowup: usize,
+    mats: &[(
+        TwoAdicMultiplicativeCoset<Val>,
+        Vec<(Challenge, Vec<Challenge>)>,
+    )],
+) -> Vec<Dimensions> {
+    claimed_batch_dims(
+        mats.iter().map(|(domain, points_and_values)| {
+            (domain.size() << log_blowup, &points_and_values[..])
+        }),
+    )
+}
+
+/// Returns the dimensions of the matrices of a committed batch, given the height of each matrix
+/// and the values claimed at each of its opening points.
+///
+/// The width of each matrix is taken from the values claimed at its points, which the verifier
+/// knows from the statement, rather than from the prover's openings. A matrix which isn't opened
+/// at any point has no such width and is given width 0, so that no opening of it verifies.
+/// Verifiers should reject such a batch up front.
+pub fn claimed_batch_dims<'a, Challenge: 'a>(
+    mats: impl IntoIterator<Item = (usize, &'a [(Challenge, Vec<Challenge>)])>,
+) -> Vec<Dimensions> {
+    mats.into_iter()
+        .map(|(height, points_and_values)| Dimensions {
+            width: points_and_values
+                .first()
+                .map_or(0, |(_, values)| values.len()),
+            height,
+        })
+        .collect()
+}
+
+/// Returns the index of a committed batch's largest matrix which corresponds to `index` of the
+/// largest LDE domain.
+pub fn batch_index(batch_dims: &[Dimensions], log_global_max_height: usize, index: usize) -> usize {
+    batch_dims.iter().map(|dims| dims.height).max().map_or(
+        // Empty batch?
+        0,
+        |batch_max_height| index >> (log_global_max_height - log2_strict_usize(batch_max_height)),
+    )
+}
+
+/// Returns the exponent `s` of the degree correction `X^s` applied to the quotients of a
+/// polynomial with the given degree bound, over a trace domain of size `domain_size`, or `None` if
+/// the degree bound is the one FRI already enforces.
+const fn degree_correction_exponent(domain_size: usize, degree_bound: usize) -> Option<usize> {
+    if degree_bound < domain_size {
+        Some(domain_size - degree_bound + 1)
+    } else {
+        None
+    }
+}
+
+/// Computes `1 + alpha^width * X^exponent` for each point `X` of the LDE domain of height
+/// `2^log_height`, in bit-reversed order.
+fn degree_correction<F: TwoAdicField, EF: ExtensionField<F>>(
+    alpha: EF,
+    width: usize,
+    log_height: usize,
+    exponent: usize,
+) -> Vec<EF> {
+    let alpha_pow_width = alpha.exp_u64(width as u64);
+    let mut x_pows = cyclic_subgroup_coset_known_order(
+        F::two_adic_generator(log_height).exp_u64(exponent as u64),
+        F::GENERATOR.exp_u64(exponent as u64),
+        1 << log_height,
+    )
+    .collect_vec();
+    reverse_slice_index_bits(&mut x_pows);
+    x_pows
+        .into_par_iter()
+        .map(|x_pow| EF::ONE + alpha_pow_width * x_pow)
+        .collect()
+}
+
+#[instrument(skip_all)]
+fn compute_inverse_denominators<F: TwoAdicField, EF: ExtensionField<F>, M: Matrix<F>>(
+    mats_and_points: &[(Vec<M>, &Vec<Vec<EF>>)],
+    coset_shift: F,
+) -> LinearMap<EF, Vec<EF>> {
+    let mut max_log_height_for_point: LinearMap<EF, usize> = LinearMap::new();
+    for (mats, points) in mats_and_points {
+        for (mat, points_for_mat) in izip!(mats, *points) {
+            let log_height = log2_strict_usize(mat.height());
+            for &z in points_for_mat {
+                if let Some(lh) = max_log_height_for_point.get_mut(&z) {
+                    *lh = core::cmp::max(*lh, log_height);
+                } else {
+                    max_log_height_for_point.insert(z, log_height);
+                }
+            }
+        }
+    }
+
+    // Compute the largest subgroup we will use, in bitrev order.
+    let max_log_height = *max_log_height_for_point.values().max().unwrap();
+    let mut subgroup = cyclic_subgroup_coset_known_order(
+        F::two_adic_generator(max_log_height),
+        coset_shift,
+        1 << max_log_height,
+    )
+    .collect_vec();
+    reverse_slice_index_bits(&mut subgroup);
+
+    max_log_height_for_point
+        .into_iter()
+        .map(|(z, log_height)| {
+            let mut diffs = subgroup[..(1 << log_height)]
+                .iter()
+                .map(|&x| z - x)
+                .collect_vec();
+            // A point of the LDE domain has no inverse at its own row, which we leave as zero.
+            let domain_row = in_lde_domain::<F, _>(z, log_height)
+                .then(|| diffs.iter().position(|diff| diff.is_zero()).unwrap());
+            if let Some(row) = domain_row {
+                diffs[row] = EF::ONE;
+            }
+            let mut inv_denoms = batch_multiplicative_inverse(&diffs);
+            if let Some(row) = domain_row {
+                inv_denoms[row] = EF::ZERO;
+            }
+            (z, inv_denoms)
+        })
+        .collect()
+}
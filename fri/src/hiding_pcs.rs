@@ -1,9 +1,12 @@
 use alloc::vec::Vec;
-use core::cell::RefCell;
 use core::fmt::Debug;
+use core::marker::PhantomData;
 
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
-use p3_commit::{Mmcs, OpenedValues, Pcs, TwoAdicMultiplicativeCoset};
+use p3_commit::{
+    CommitRandomness, Mmcs, OpenedValues, Pcs, RngRandomness, SeededRandomness, StoredMatrix,
+    TwoAdicMultiplicativeCoset,
+};
 use p3_dft::TwoAdicSubgroupDft;
 use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::Matrix;
@@ -13,39 +16,90 @@ use p3_matrix::horizontally_truncated::HorizontallyTruncated;
 use p3_matrix::row_index_mapped::RowIndexMappedView;
 use rand::Rng;
 use rand::distr::{Distribution, StandardUniform};
+use rand_chacha::ChaCha20Rng;
 use tracing::instrument;
 
 use crate::verifier::FriError;
-use crate::{BatchOpening, FriConfig, FriProof, TwoAdicFriPcs};
+use crate::{FriConfig, TwoAdicFriPcs, TwoAdicFriPcsProof};
 
 /// A hiding FRI PCS. Both MMCSs must also be hiding; this is not enforced at compile time so it's
 /// the user's responsibility to configure.
+///
+/// The random codewords are drawn from the RNG `R` when built with `new`, or from a `ChaCha20Rng`
+/// derived from a secret seed for each commitment when built with `new_deterministic`. `S` is the
+/// source of these RNGs.
 #[derive(Debug)]
-pub struct HidingFriPcs<Val, Dft, InputMmcs, FriMmcs, R> {
+pub struct HidingFriPcs<Val, Dft, InputMmcs, FriMmcs, R, S = RngRandomness<R>> {
     inner: TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>,
     num_random_codewords: usize,
-    rng: RefCell<R>,
+    randomness: S,
+    _phantom: PhantomData<R>,
 }
 
 impl<Val, Dft, InputMmcs, FriMmcs, R> HidingFriPcs<Val, Dft, InputMmcs, FriMmcs, R> {
-    pub fn new(
+    pub const fn new(
         dft: Dft,
         mmcs: InputMmcs,
         fri: FriConfig<FriMmcs>,
         num_random_codewords: usize,
         rng: R,
+    ) -> Self {
+        Self::new_with_randomness(
+            dft,
+            mmcs,
+            fri,
+            num_random_codewords,
+            RngRandomness::new(rng),
+        )
+    }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs>
+    HidingFriPcs<Val, Dft, InputMmcs, FriMmcs, ChaCha20Rng, SeededRandomness>
+{
+    /// Like `new`, but derives the random codewords of each commitment from `seed` and the index
+    /// of the commitment. See `SeededRandomness`.
+    ///
+    /// The seed must be secret, independent of any seeds used by the MMCSs, and not reused for
+    /// another proof: proofs made with the same seed share their random codewords, which breaks
+    /// hiding.
+    pub fn new_deterministic(
+        dft: Dft,
+        mmcs: InputMmcs,
+        fri: FriConfig<FriMmcs>,
+        num_random_codewords: usize,
+        seed: [u8; 32],
+    ) -> Self {
+        Self::new_with_randomness(
+            dft,
+            mmcs,
+            fri,
+            num_random_codewords,
+            SeededRandomness::new(seed),
+        )
+    }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, R, S> HidingFriPcs<Val, Dft, InputMmcs, FriMmcs, R, S> {
+    pub const fn new_with_randomness(
+        dft: Dft,
+        mmcs: InputMmcs,
+        fri: FriConfig<FriMmcs>,
+        num_random_codewords: usize,
+        randomness: S,
     ) -> Self {
         let inner = TwoAdicFriPcs::new(dft, mmcs, fri);
         Self {
             inner,
             num_random_codewords,
-            rng: rng.into(),
+            randomness,
+            _phantom: PhantomData,
         }
     }
 }
 
-impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger, R> Pcs<Challenge, Challenger>
-    for HidingFriPcs<Val, Dft, InputMmcs, FriMmcs, R>
+impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger, R, S> Pcs<Challenge, Challenger>
+    for HidingFriPcs<Val, Dft, InputMmcs, FriMmcs, R, S>
 where
     Val: TwoAdicField,
     StandardUniform: Distribution<Val>,
@@ -56,19 +110,20 @@ where
     Challenger:
         FieldChallenger<Val> + CanObserve<FriMmcs::Commitment> + GrindingChallenger<Witness = Val>,
     R: Rng + Send + Sync,
+    S: CommitRandomness<Rng = R>,
 {
     type Domain = TwoAdicMultiplicativeCoset<Val>;
     type Commitment = InputMmcs::Commitment;
-    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
+    type ProverData = InputMmcs::ProverData<StoredMatrix<Val, InputMmcs>>;
     type EvaluationsOnDomain<'a> = HorizontallyTruncated<
         Val,
         RowIndexMappedView<BitReversalPerm, DenseMatrix<Val, &'a [Val]>>,
     >;
     /// The first item contains the openings of the random polynomials added by this wrapper.
-    /// The second item is the usual FRI proof.
+    /// The second item is the usual `TwoAdicFriPcs` proof.
     type Proof = (
         OpenedValues<Challenge>,
-        FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>,
+        TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>,
     );
     type Error = FriError<FriMmcs::Error, InputMmcs::Error>;
 
@@ -81,21 +136,45 @@ where
         &self,
         evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
     ) -> (Self::Commitment, Self::ProverData) {
-        let randomized_evaluations = evaluations
-            .into_iter()
-            .map(|(domain, mat)| {
-                (
-                    domain,
-                    add_random_cols(mat, self.num_random_codewords, &mut *self.rng.borrow_mut()),
-                )
-            })
-            .collect();
+        let randomized_evaluations = self.randomness.with_next_rng(|rng| {
+            evaluations
+                .into_iter()
+                .map(|(domain, mat)| {
+                    (
+                        domain,
+                        add_random_cols(mat, self.num_random_codewords, &mut *rng),
+                    )
+                })
+                .collect()
+        });
         <TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::commit(
             &self.inner,
             randomized_evaluations,
         )
     }
 
+    fn commit_coeffs(
+        &self,
+        coeffs: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        // Random columns are as random in coefficient form as in evaluation form.
+        let randomized_coeffs = self.randomness.with_next_rng(|rng| {
+            coeffs
+                .into_iter()
+                .map(|(domain, mat)| {
+                    (
+                        domain,
+                        add_random_cols(mat, self.num_random_codewords, &mut *rng),
+                    )
+                })
+                .collect()
+        });
+        <TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::commit_coeffs(
+            &self.inner,
+            randomized_coeffs,
+        )
+    }
+
     fn get_evaluations_on_domain<'a>(
         &self,
         prover_data: &'a Self::ProverData,
@@ -188,15 +267,17 @@ where
     }
 }
 
+/// Appends `num_random_codewords` columns of random values to `mat`. Committing to random
+/// evaluations over a trace domain amounts to committing to random low-degree codewords.
 #[instrument(level = "debug", skip_all)]
-fn add_random_cols<Val, R>(
+pub fn add_random_cols<Val, R>(
     mat: RowMajorMatrix<Val>,
     num_random_codewords: usize,
     mut rng: R,
 ) -> RowMajorMatrix<Val>
 where
     Val: Field,
-    R: Rng + Send + Sync,
+    R: Rng,
     StandardUniform: Distribution<Val>,
 {
     let old_w = mat.width();
@@ -0,0 +1,249 @@
+//! A standalone FRI low-degree test, for proving that a committed codeword is close to a
+//! Reed-Solomon code without going through a PCS.
+//!
+//! A codeword of length `2^n` holds the evaluations of a function over a coset of the two-adic
+//! subgroup of that size, in bit-reversed order. It is accepted if it is close to the evaluations
+//! of a polynomial of degree less than `2^(n - log_blowup)`, i.e. to the code of rate
+//! `2^-log_blowup` set by the `FriConfig`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::Mmcs;
+use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_matrix::Dimensions;
+use p3_matrix::dense::RowMajorMatrix;
+use serde::{Deserialize, Serialize};
+
+use crate::verifier::{self, FriError};
+use crate::{FriConfig, FriProof, TwoAdicFriGenericConfig, prover};
+
+/// The opening of a committed codeword at a queried index.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct CodewordOpening<F: Field, M: Mmcs<F>> {
+    pub value: F,
+    pub opening_proof: M::Proof,
+}
+
+/// A proof that a committed codeword is close to a Reed-Solomon code.
+pub type LdtProof<F, M, Witness> = FriProof<F, M, Witness, CodewordOpening<F, M>>;
+
+type LdtGenericConfig<F, M> = TwoAdicFriGenericConfig<CodewordOpening<F, M>, <M as Mmcs<F>>::Error>;
+
+/// Commits to `codeword` with the MMCS of `config`, as a single column.
+#[allow(clippy::type_complexity)]
+pub fn commit_codeword<F: Field, M: Mmcs<F>>(
+    config: &FriConfig<M>,
+    codeword: Vec<F>,
+) -> (M::Commitment, M::ProverData<RowMajorMatrix<F>>) {
+    assert!(
+        codeword.len().is_power_of_two(),
+        "codeword length must be a power of two"
+    );
+    config.mmcs.commit_vec(codeword)
+}
+
+/// Proves that the codeword committed to by `commit_codeword` is close to the Reed-Solomon code
+/// of rate `2^-config.log_blowup`.
+///
+/// As with a PCS opening, the commitment is not observed here; the caller should observe it
+/// before calling this and `verify_low_degree`.
+pub fn prove_low_degree<Val, Challenge, M, Challenger>(
+    config: &FriConfig<M>,
+    prover_data: &M::ProverData<RowMajorMatrix<Challenge>>,
+    challenger: &mut Challenger,
+) -> LdtProof<Challenge, M, Challenger::Witness>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+{
+    let codeword = config.mmcs.get_matrices(prover_data)[0].values.clone();
+    let g: LdtGenericConfig<Challenge, M> = TwoAdicFriGenericConfig(PhantomData);
+    prover::prove(&g, config, vec![codeword], challenger, |index| {
+        let (opened_values, opening_proof) = config.mmcs.open_batch(index, prover_data);
+        CodewordOpening {
+            value: opened_values[0][0],
+            opening_proof,
+        }
+    })
+}
+
+/// Verifies a proof produced by `prove_low_degree` for the codeword of length `2^log_size`
+/// committed to by `commitment`.
+pub fn verify_low_degree<Val, Challenge, M, Challenger>(
+    config: &FriConfig<M>,
+    commitment: &M::Commitment,
+    log_size: usize,
+    proof: &LdtProof<Challenge, M, Challenger::Witness>,
+    challenger: &mut Challenger,
+) -> Result<(), FriError<M::Error, M::Error>>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+{
+    let g: LdtGenericConfig<Challenge, M> = TwoAdicFriGenericConfig(PhantomData);
+    let dims = [Dimensions {
+        width: 1,
+        height: 1 << log_size,
+    }];
+    // FRI checks that the opened value belongs to its largest round, which fixes the codeword's
+    // length to `2^log_size` as well.
+    verifier::verify(&g, config, proof, challenger, |index, opening| {
+        config.mmcs.verify_batch(
+            commitment,
+            &dims,
+            index,
+            &[vec![opening.value]],
+            &opening.opening_proof,
+        )?;
+        Ok(vec![(log_size, opening.value)])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_challenger::DuplexChallenger;
+    use p3_commit::ExtensionMmcs;
+    use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{Field, PrimeCharacteristicRing};
+    use p3_merkle_tree::MerkleTreeMmcs;
+    use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+    use p3_util::reverse_slice_index_bits;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+    type Perm = Poseidon2BabyBear<16>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type ValMmcs =
+        MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+
+    fn setup() -> (FriConfig<ChallengeMmcs>, Challenger) {
+        let perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(0));
+        let mmcs = ChallengeMmcs::new(ValMmcs::new(
+            MyHash::new(perm.clone()),
+            MyCompress::new(perm.clone()),
+        ));
+        let config = FriConfig {
+            log_blowup: 2,
+            log_final_poly_len: 0,
+            num_queries: 20,
+            proof_of_work_bits: 4,
+            commit_proof_of_work_bits: 0,
+            log_folding_arity: 1,
+            use_multiproofs: false,
+            mmcs,
+        };
+        (config, Challenger::new(perm))
+    }
+
+    /// Returns a word of length `2^log_size` holding the evaluations of a random polynomial of
+    /// degree less than `2^(log_size - log_blowup)`, over the coset used by `TwoAdicFriPcs`.
+    fn random_codeword(log_blowup: usize, log_size: usize) -> Vec<Challenge> {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let evals = (0..1 << (log_size - log_blowup))
+            .map(|_| rng.random())
+            .collect::<Vec<Challenge>>();
+        let mut codeword = Radix2Dit::default()
+            .coset_lde_batch(
+                RowMajorMatrix::new_col(evals),
+                log_blowup,
+                Challenge::GENERATOR,
+            )
+            .values;
+        reverse_slice_index_bits(&mut codeword);
+        codeword
+    }
+
+    #[test]
+    fn accepts_codeword() {
+        let (config, challenger) = setup();
+        let log_size = 10;
+        let (commitment, prover_data) =
+            commit_codeword(&config, random_codeword(config.log_blowup, log_size));
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commitment.clone());
+        let proof = prove_low_degree(&config, &prover_data, &mut p_challenger);
+
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(commitment.clone());
+        verify_low_degree(&config, &commitment, log_size, &proof, &mut v_challenger).unwrap();
+
+        // The proof is bound to the length of the codeword.
+        let mut v_challenger = challenger;
+        v_challenger.observe(commitment.clone());
+        assert!(
+            verify_low_degree(
+                &config,
+                &commitment,
+                log_size - 1,
+                &proof,
+                &mut v_challenger
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_other_commitment() {
+        let (config, challenger) = setup();
+        let log_size = 8;
+        let codeword = random_codeword(config.log_blowup, log_size);
+        let (commitment, prover_data) = commit_codeword(&config, codeword.clone());
+        let mut other = codeword;
+        other[3] += Challenge::ONE;
+        let (other_commitment, _) = commit_codeword(&config, other);
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commitment);
+        let proof = prove_low_degree(&config, &prover_data, &mut p_challenger);
+
+        let mut v_challenger = challenger;
+        v_challenger.observe(other_commitment.clone());
+        assert!(
+            verify_low_degree(
+                &config,
+                &other_commitment,
+                log_size,
+                &proof,
+                &mut v_challenger
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_high_degree_codeword() {
+        let (config, challenger) = setup();
+        let log_size = 10;
+        // Twice the degree the code allows, so the word is far from the code.
+        let codeword = random_codeword(config.log_blowup - 1, log_size);
+        let (commitment, prover_data) = commit_codeword(&config, codeword);
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commitment.clone());
+        let proof = prove_low_degree(&config, &prover_data, &mut p_challenger);
+
+        let mut v_challenger = challenger;
+        v_challenger.observe(commitment.clone());
+        assert!(
+            verify_low_degree(&config, &commitment, log_size, &proof, &mut v_challenger).is_err()
+        );
+    }
+}
@@ -4,16 +4,24 @@
 
 extern crate alloc;
 
+mod basefold;
 mod config;
+mod config_builder;
+mod deep_quotient;
 mod fold_even_odd;
 mod hiding_pcs;
+mod ldt;
 mod proof;
 pub mod prover;
 mod two_adic_pcs;
 pub mod verifier;
 
+pub use basefold::*;
 pub use config::*;
+pub use config_builder::*;
+pub use deep_quotient::*;
 pub use fold_even_odd::*;
 pub use hiding_pcs::*;
+pub use ldt::*;
 pub use proof::*;
 pub use two_adic_pcs::*;
@@ -12,8 +12,14 @@ use serde::{Deserialize, Serialize};
 pub struct FriProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
     pub commit_phase_commits: Vec<M::Commitment>,
     pub query_proofs: Vec<QueryProof<F, M, InputProof>>,
+    /// When `FriConfig::use_multiproofs` is set, a multiproof for each commit phase round which
+    /// covers the openings of all queries. Otherwise, this is empty.
+    pub commit_phase_multi_proofs: Vec<Vec<M::Proof>>,
     pub final_poly: Vec<F>,
     pub pow_witness: Witness,
+    /// When `FriConfig::commit_proof_of_work_bits` is nonzero, the proof of work witness ground
+    /// before the folding challenge of each commit phase round. Otherwise, this is empty.
+    pub commit_pow_witnesses: Vec<Witness>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -31,10 +37,11 @@ pub struct QueryProof<F: Field, M: Mmcs<F>, InputProof> {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
 pub struct CommitPhaseProofStep<F: Field, M: Mmcs<F>> {
-    /// The opening of the commit phase codeword at the sibling location.
-    // This may change to Vec<FC::Challenge> if the library is generalized to support other FRI
-    // folding arities besides 2, meaning that there can be multiple siblings.
-    pub sibling_value: F,
+    /// The openings of the commit phase codeword at the sibling locations, i.e. the other
+    /// evaluations folded together with the queried one, in order of their position in the row.
+    pub sibling_values: Vec<F>,
 
-    pub opening_proof: M::Proof,
+    /// The proof of this opening, which is absent if the openings of all queries are proven
+    /// together by one of `FriProof::commit_phase_multi_proofs`.
+    pub opening_proof: Option<M::Proof>,
 }
@@ -19,7 +19,38 @@ pub fn prove<G, Val, Challenge, M, Challenger>(
     config: &FriConfig<M>,
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
-    open_input: impl Fn(usize) -> G::InputProof,
+    open_input: impl FnMut(usize) -> G::InputProof,
+) -> FriProof<Challenge, M, Challenger::Witness, G::InputProof>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+    G: FriGenericConfig<Challenge>,
+{
+    prove_with_folding_challenges(
+        g,
+        config,
+        inputs,
+        challenger,
+        |_, challenger| challenger.sample_algebra_element(),
+        open_input,
+    )
+}
+
+/// Like `prove`, but the folding challenge of each commit phase round is obtained from
+/// `sample_beta`, which is called with the round index right after that round's commitment has
+/// been observed. This lets callers interleave their own messages with the commit phase.
+///
+/// Any commit phase proof of work is ground before `sample_beta` is called, so it does not cover
+/// messages which `sample_beta` observes.
+pub fn prove_with_folding_challenges<G, Val, Challenge, M, Challenger>(
+    g: &G,
+    config: &FriConfig<M>,
+    inputs: Vec<Vec<Challenge>>,
+    challenger: &mut Challenger,
+    sample_beta: impl FnMut(usize, &mut Challenger) -> Challenge,
+    mut open_input: impl FnMut(usize) -> G::InputProof,
 ) -> FriProof<Challenge, M, Challenger::Witness, G::InputProof>
 where
     Val: Field,
@@ -43,35 +74,69 @@ where
         assert!(log_min_height > config.log_final_poly_len + config.log_blowup);
     }
 
-    let commit_phase_result = commit_phase(g, config, inputs, challenger);
+    let commit_phase_result = commit_phase(g, config, inputs, challenger, sample_beta);
 
     let pow_witness = challenger.grind(config.proof_of_work_bits);
 
-    let query_proofs = info_span!("query phase").in_scope(|| {
-        iter::repeat_with(|| challenger.sample_bits(log_max_height + g.extra_query_index_bits()))
-            .take(config.num_queries)
-            .map(|index| QueryProof {
+    let (query_proofs, commit_phase_multi_proofs) = info_span!("query phase").in_scope(|| {
+        let indices = iter::repeat_with(|| {
+            challenger.sample_bits(log_max_height + g.extra_query_index_bits())
+        })
+        .take(config.num_queries)
+        .collect_vec();
+        let folded_indices = indices
+            .iter()
+            .map(|&index| index >> g.extra_query_index_bits())
+            .collect_vec();
+
+        let (commit_phase_openings, commit_phase_multi_proofs) = if config.use_multiproofs {
+            answer_queries_multi(
+                config,
+                &commit_phase_result.data,
+                &commit_phase_result.log_arities,
+                &folded_indices,
+            )
+        } else {
+            let openings = folded_indices
+                .iter()
+                .map(|&index| {
+                    answer_query(
+                        config,
+                        &commit_phase_result.data,
+                        &commit_phase_result.log_arities,
+                        index,
+                    )
+                })
+                .collect();
+            (openings, vec![])
+        };
+
+        let query_proofs = izip!(indices, commit_phase_openings)
+            .map(|(index, commit_phase_openings)| QueryProof {
                 input_proof: open_input(index),
-                commit_phase_openings: answer_query(
-                    config,
-                    &commit_phase_result.data,
-                    index >> g.extra_query_index_bits(),
-                ),
+                commit_phase_openings,
             })
-            .collect()
+            .collect();
+        (query_proofs, commit_phase_multi_proofs)
     });
 
     FriProof {
         commit_phase_commits: commit_phase_result.commits,
         query_proofs,
+        commit_phase_multi_proofs,
         final_poly: commit_phase_result.final_poly,
         pow_witness,
+        commit_pow_witnesses: commit_phase_result.pow_witnesses,
     }
 }
 
-struct CommitPhaseResult<F: Field, M: Mmcs<F>> {
+struct CommitPhaseResult<F: Field, M: Mmcs<F>, Witness> {
     commits: Vec<M::Commitment>,
     data: Vec<M::ProverData<RowMajorMatrix<F>>>,
+    /// The log of the folding arity used by each round.
+    log_arities: Vec<usize>,
+    /// The proof of work witness of each round, if there is commit phase grinding.
+    pow_witnesses: Vec<Witness>,
     final_poly: Vec<F>,
 }
 
@@ -81,31 +146,44 @@ fn commit_phase<G, Val, Challenge, M, Challenger>(
     config: &FriConfig<M>,
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
-) -> CommitPhaseResult<Challenge, M>
+    mut sample_beta: impl FnMut(usize, &mut Challenger) -> Challenge,
+) -> CommitPhaseResult<Challenge, M, Challenger::Witness>
 where
     Val: Field,
     Challenge: ExtensionField<Val> + TwoAdicField,
     M: Mmcs<Challenge>,
-    Challenger: FieldChallenger<Val> + CanObserve<M::Commitment>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
     G: FriGenericConfig<Challenge>,
 {
+    let log_final_height = config.log_blowup + config.log_final_poly_len;
     let mut inputs_iter = inputs.into_iter().peekable();
     let mut folded = inputs_iter.next().unwrap();
     let mut commits = vec![];
     let mut data = vec![];
+    let mut log_arities = vec![];
+    let mut pow_witnesses = vec![];
 
     while folded.len() > config.blowup() * config.final_poly_len() {
-        let leaves = RowMajorMatrix::new(folded, 2);
+        let log_arity = config.log_arity_for_round(
+            log2_strict_usize(folded.len()),
+            inputs_iter.peek().map(|v| log2_strict_usize(v.len())),
+            log_final_height,
+        );
+        let leaves = RowMajorMatrix::new(folded, 1 << log_arity);
         let (commit, prover_data) = config.mmcs.commit_matrix(leaves);
         challenger.observe(commit.clone());
 
-        let beta: Challenge = challenger.sample_algebra_element();
+        if config.commit_proof_of_work_bits > 0 {
+            pow_witnesses.push(challenger.grind(config.commit_proof_of_work_bits));
+        }
+        let beta = sample_beta(commits.len(), challenger);
         // We passed ownership of `current` to the MMCS, so get a reference to it
         let leaves = config.mmcs.get_matrices(&prover_data).pop().unwrap();
-        folded = g.fold_matrix(beta, leaves.as_view());
+        folded = g.fold_matrix_with_arity(beta, log_arity, &leaves.values);
 
         commits.push(commit);
         data.push(prover_data);
+        log_arities.push(log_arity);
 
         if let Some(v) = inputs_iter.next_if(|v| v.len() == folded.len()) {
             izip!(&mut folded, v).for_each(|(c, x)| *c += x);
@@ -122,17 +200,14 @@ where
     reverse_slice_index_bits(&mut folded);
     // TODO: For better performance, we could run the IDFT on only the first half
     //       (or less, depending on `log_blowup`) of `final_poly`.
-    let final_poly = debug_span!("idft final poly").in_scope(|| Radix2Dit::default().idft(folded));
+    let mut final_poly =
+        debug_span!("idft final poly").in_scope(|| Radix2Dit::default().idft(folded));
 
     // The evaluation domain is "blown-up" relative to the polynomial degree of `final_poly`,
-    // so all coefficients after the first final_poly_len should be zero.
-    debug_assert!(
-        final_poly
-            .iter()
-            .skip(1 << config.log_final_poly_len)
-            .all(|x| x.is_zero()),
-        "All coefficients beyond final_poly_len must be zero"
-    );
+    // so all coefficients after the first final_poly_len are zero if the inputs are low degree.
+    // Otherwise, the truncated polynomial doesn't match the folded codeword, and the proof is
+    // rejected when the verifier checks the queries against it.
+    final_poly.truncate(config.final_poly_len());
 
     // Observe all coefficients of the final polynomial.
     for &x in &final_poly {
@@ -142,6 +217,8 @@ where
     CommitPhaseResult {
         commits,
         data,
+        log_arities,
+        pow_witnesses,
         final_poly,
     }
 }
@@ -149,30 +226,83 @@ where
 fn answer_query<F, M>(
     config: &FriConfig<M>,
     commit_phase_commits: &[M::ProverData<RowMajorMatrix<F>>],
-    index: usize,
+    log_arities: &[usize],
+    mut index: usize,
 ) -> Vec<CommitPhaseProofStep<F, M>>
 where
     F: Field,
     M: Mmcs<F>,
 {
-    commit_phase_commits
-        .iter()
-        .enumerate()
-        .map(|(i, commit)| {
-            let index_i = index >> i;
-            let index_i_sibling = index_i ^ 1;
-            let index_pair = index_i >> 1;
-
-            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index_pair, commit);
+    izip!(commit_phase_commits, log_arities)
+        .map(|(commit, &log_arity)| {
+            let index_in_row = index & ((1 << log_arity) - 1);
+            let index_row = index >> log_arity;
+
+            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index_row, commit);
             assert_eq!(opened_rows.len(), 1);
-            let opened_row = opened_rows.pop().unwrap();
-            assert_eq!(opened_row.len(), 2, "Committed data should be in pairs");
-            let sibling_value = opened_row[index_i_sibling % 2];
+            let mut opened_row = opened_rows.pop().unwrap();
+            assert_eq!(
+                opened_row.len(),
+                1 << log_arity,
+                "Committed data should be in rows of the folding arity"
+            );
+            opened_row.remove(index_in_row);
+
+            index = index_row;
 
             CommitPhaseProofStep {
-                sibling_value,
-                opening_proof,
+                sibling_values: opened_row,
+                opening_proof: Some(opening_proof),
             }
         })
         .collect()
 }
+
+/// Like `answer_query` for each of `indices`, but proves the openings of each round with a single
+/// multiproof, which is returned alongside the openings of each query.
+#[allow(clippy::type_complexity)]
+fn answer_queries_multi<F, M>(
+    config: &FriConfig<M>,
+    commit_phase_commits: &[M::ProverData<RowMajorMatrix<F>>],
+    log_arities: &[usize],
+    indices: &[usize],
+) -> (Vec<Vec<CommitPhaseProofStep<F, M>>>, Vec<Vec<M::Proof>>)
+where
+    F: Field,
+    M: Mmcs<F>,
+{
+    let mut indices = indices.to_vec();
+    let mut openings = (0..indices.len())
+        .map(|_| Vec::with_capacity(log_arities.len()))
+        .collect_vec();
+    let multi_proofs = izip!(commit_phase_commits, log_arities)
+        .map(|(commit, &log_arity)| {
+            let index_rows = indices
+                .iter()
+                .map(|&index| index >> log_arity)
+                .collect_vec();
+            let (opened_rows, multi_proof) = config.mmcs.open_multi(&index_rows, commit);
+
+            for (index, index_row, mut opened_rows, query_openings) in
+                izip!(&mut indices, index_rows, opened_rows, &mut openings)
+            {
+                assert_eq!(opened_rows.len(), 1);
+                let mut opened_row = opened_rows.pop().unwrap();
+                assert_eq!(
+                    opened_row.len(),
+                    1 << log_arity,
+                    "Committed data should be in rows of the folding arity"
+                );
+                opened_row.remove(*index & ((1 << log_arity) - 1));
+                *index = index_row;
+
+                query_openings.push(CommitPhaseProofStep {
+                    sibling_values: opened_row,
+                    opening_proof: None,
+                });
+            }
+            multi_proof
+        })
+        .collect();
+    (openings, multi_proofs)
+}
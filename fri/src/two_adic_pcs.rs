@@ -1,4 +1,3 @@
-use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::Debug;
@@ -6,24 +5,24 @@ use core::marker::PhantomData;
 
 use itertools::{Itertools, izip};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
-use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
-use p3_dft::TwoAdicSubgroupDft;
-use p3_field::{
-    ExtensionField, Field, TwoAdicField, batch_multiplicative_inverse,
-    cyclic_subgroup_coset_known_order, dot_product,
+use p3_commit::{
+    Mmcs, OpenedValues, Pcs, PolynomialSpace, StoredMatrix, TwoAdicMultiplicativeCoset,
 };
-use p3_interpolation::interpolate_coset;
-use p3_matrix::bitrev::{BitReversalPerm, BitReversedMatrixView, BitReversibleMatrix};
+use p3_dft::TwoAdicSubgroupDft;
+use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_matrix::Matrix;
+use p3_matrix::bitrev::{BitReversedMatrixView, BitReversibleMatrix};
 use p3_matrix::dense::{DenseMatrix, RowMajorMatrix};
-use p3_matrix::{Dimensions, Matrix};
 use p3_maybe_rayon::prelude::*;
-use p3_util::linear_map::LinearMap;
 use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits};
 use serde::{Deserialize, Serialize};
-use tracing::{info_span, instrument};
 
 use crate::verifier::{self, FriError};
-use crate::{FriConfig, FriGenericConfig, FriProof, prover};
+use crate::{
+    FriConfig, FriGenericConfig, FriProof, in_lde_domain, open_and_reduce_with_degree_bounds,
+    open_input_batches, open_input_batches_multi, prover, verify_and_reduce_with_degree_bounds,
+    verify_input_batches_multi,
+};
 
 #[derive(Debug)]
 pub struct TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
@@ -48,7 +47,30 @@ impl<Val, Dft, InputMmcs, FriMmcs> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
 #[serde(bound = "")]
 pub struct BatchOpening<Val: Field, InputMmcs: Mmcs<Val>> {
     pub opened_values: Vec<Vec<Val>>,
-    pub opening_proof: <InputMmcs as Mmcs<Val>>::Proof,
+    /// The proof of this opening, which is absent if the openings of all queries are proven
+    /// together by a multiproof.
+    pub opening_proof: Option<<InputMmcs as Mmcs<Val>>::Proof>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct TwoAdicFriPcsProof<
+    Val: Field,
+    Challenge: Field,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+> {
+    pub fri_proof: FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>,
+    /// For each opening point which lies in the LDE domain of its matrix, the derivatives of the
+    /// matrix's columns at that point, in the shape of the opened values. These are empty for
+    /// every other point.
+    pub derivatives: OpenedValues<Challenge>,
+    /// When `FriConfig::use_multiproofs` is set, a multiproof for each committed batch which
+    /// covers the input openings of all queries. Otherwise, this is empty.
+    pub input_multi_proofs: Vec<Vec<InputMmcs::Proof>>,
+    /// When `FriConfig::commit_proof_of_work_bits` is nonzero, the proof of work witness ground
+    /// before the batching challenge. Otherwise, this is `None`.
+    pub alpha_pow_witness: Option<Val>,
 }
 
 pub struct TwoAdicFriGenericConfig<InputProof, InputError>(
@@ -142,9 +164,9 @@ where
 {
     type Domain = TwoAdicMultiplicativeCoset<Val>;
     type Commitment = InputMmcs::Commitment;
-    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
+    type ProverData = InputMmcs::ProverData<StoredMatrix<Val, InputMmcs>>;
     type EvaluationsOnDomain<'a> = BitReversedMatrixView<DenseMatrix<Val, &'a [Val]>>;
-    type Proof = FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>;
+    type Proof = TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>;
     type Error = FriError<FriMmcs::Error, InputMmcs::Error>;
 
     fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
@@ -165,10 +187,35 @@ where
                 assert_eq!(domain.size(), evals.height());
                 let shift = Val::GENERATOR / domain.shift;
                 // Commit to the bit-reversed LDE.
-                self.dft
+                let lde = self
+                    .dft
                     .coset_lde_batch(evals, self.fri.log_blowup, shift)
                     .bit_reverse_rows()
-                    .to_row_major_matrix()
+                    .to_row_major_matrix();
+                self.mmcs.store_matrix(lde)
+            })
+            .collect();
+
+        self.mmcs.commit(ldes)
+    }
+
+    fn commit_coeffs(
+        &self,
+        coeffs: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let ldes: Vec<_> = coeffs
+            .into_iter()
+            .map(|(domain, mut coeffs)| {
+                assert_eq!(domain.size(), coeffs.height());
+                // The coefficients are those of the polynomial itself, so the domain's shift plays
+                // no part; we evaluate straight over the LDE coset.
+                coeffs.pad_to_height(domain.size() << self.fri.log_blowup, Val::ZERO);
+                let lde = self
+                    .dft
+                    .coset_dft_batch(coeffs, Val::GENERATOR)
+                    .bit_reverse_rows()
+                    .to_row_major_matrix();
+                self.mmcs.store_matrix(lde)
             })
             .collect();
 
@@ -201,179 +248,17 @@ where
         )>,
         challenger: &mut Challenger,
     ) -> (OpenedValues<Challenge>, Self::Proof) {
-        /*
-
-        A quick rundown of the optimizations in this function:
-        We are trying to compute sum_i alpha^i * (p(X) - y)/(X - z),
-        for each z an opening point, y = p(z). Each p(X) is given as evaluations in bit-reversed order
-        in the columns of the matrices. y is computed by barycentric interpolation.
-        X and p(X) are in the base field; alpha, y and z are in the extension.
-        The primary goal is to minimize extension multiplications.
-
-        - Instead of computing all alpha^i, we just compute alpha^i for i up to the largest width
-        of a matrix, then multiply by an "alpha offset" when accumulating.
-              a^0 x0 + a^1 x1 + a^2 x2 + a^3 x3 + ...
-            = a^0 ( a^0 x0 + a^1 x1 ) + a^2 ( a^0 x2 + a^1 x3 ) + ...
-            (see `alpha_pows`, `alpha_pow_offset`, `num_reduced`)
-
-        - For each unique point z, we precompute 1/(X-z) for the largest subgroup opened at this point.
-        Since we compute it in bit-reversed order, smaller subgroups can simply truncate the vector.
-            (see `inv_denoms`)
-
-        - Then, for each matrix (with columns p_i) and opening point z, we want:
-            for each row (corresponding to subgroup element X):
-                reduced[X] += alpha_offset * sum_i [ alpha^i * inv_denom[X] * (p_i[X] - y[i]) ]
-
-            We can factor out inv_denom, and expand what's left:
-                reduced[X] += alpha_offset * inv_denom[X] * sum_i [ alpha^i * p_i[X] - alpha^i * y[i] ]
-
-            And separate the sum:
-                reduced[X] += alpha_offset * inv_denom[X] * [ sum_i [ alpha^i * p_i[X] ] - sum_i [ alpha^i * y[i] ] ]
-
-            And now the last sum doesn't depend on X, so we can precompute that for the matrix, too.
-            So the hot loop (that depends on both X and i) is just:
-                sum_i [ alpha^i * p_i[X] ]
-
-            with alpha^i an extension, p_i[X] a base
-
-        */
-
-        let mats_and_points = rounds
+        let degree_bounds = rounds
             .iter()
-            .map(|(data, points)| {
-                let mats = self
-                    .mmcs
+            .map(|(data, _)| {
+                self.mmcs
                     .get_matrices(data)
-                    .into_iter()
-                    .map(|m| m.as_view())
-                    .collect_vec();
-                debug_assert_eq!(
-                    mats.len(),
-                    points.len(),
-                    "each matrix should have a corresponding set of evaluation points"
-                );
-                (mats, points)
-            })
-            .collect_vec();
-        let mats = mats_and_points
-            .iter()
-            .flat_map(|(mats, _)| mats)
-            .collect_vec();
-
-        let global_max_height = mats.iter().map(|m| m.height()).max().unwrap();
-        let log_global_max_height = log2_strict_usize(global_max_height);
-
-        // For each unique opening point z, we will find the largest degree bound
-        // for that point, and precompute 1/(z - X) for the largest subgroup (in bitrev order).
-        let inv_denoms = compute_inverse_denominators(&mats_and_points, Val::GENERATOR);
-
-        // Evaluate coset representations and write openings to the challenger
-        let all_opened_values = mats_and_points
-            .iter()
-            .map(|(mats, points)| {
-                izip!(mats.iter(), points.iter())
-                    .map(|(mat, points_for_mat)| {
-                        points_for_mat
-                            .iter()
-                            .map(|&point| {
-                                let _guard =
-                                    info_span!("evaluate matrix", dims = %mat.dimensions())
-                                        .entered();
-
-                                // Use Barycentric interpolation to evaluate the matrix at the given point.
-                                let ys =
-                                    info_span!("compute opened values with Lagrange interpolation")
-                                        .in_scope(|| {
-                                            let h = mat.height() >> self.fri.log_blowup;
-                                            let (low_coset, _) = mat.split_rows(h);
-                                            let mut inv_denoms =
-                                                inv_denoms.get(&point).unwrap()[..h].to_vec();
-                                            reverse_slice_index_bits(&mut inv_denoms);
-                                            interpolate_coset(
-                                                &BitReversalPerm::new_view(low_coset),
-                                                Val::GENERATOR,
-                                                point,
-                                                Some(&inv_denoms),
-                                            )
-                                        });
-                                ys.iter()
-                                    .for_each(|&y| challenger.observe_algebra_element(y));
-                                ys
-                            })
-                            .collect_vec()
-                    })
-                    .collect_vec()
+                    .iter()
+                    .map(|mat| mat.height() >> self.fri.log_blowup)
+                    .collect()
             })
             .collect_vec();
-
-        // Batch combination challenge
-        let alpha: Challenge = challenger.sample_algebra_element();
-
-        let mut num_reduced = [0; 32];
-        let mut reduced_openings: [_; 32] = core::array::from_fn(|_| None);
-
-        for ((mats, points), openings_for_round) in
-            mats_and_points.iter().zip(all_opened_values.iter())
-        {
-            for (mat, points_for_mat, openings_for_mat) in
-                izip!(mats.iter(), points.iter(), openings_for_round.iter())
-            {
-                let _guard =
-                    info_span!("reduce matrix quotient", dims = %mat.dimensions()).entered();
-
-                let log_height = log2_strict_usize(mat.height());
-                let reduced_opening_for_log_height = reduced_openings[log_height]
-                    .get_or_insert_with(|| vec![Challenge::ZERO; mat.height()]);
-                debug_assert_eq!(reduced_opening_for_log_height.len(), mat.height());
-
-                let mat_compressed = info_span!("compress mat")
-                    .in_scope(|| mat.dot_ext_powers(alpha).collect::<Vec<_>>());
-
-                for (&point, openings) in points_for_mat.iter().zip(openings_for_mat) {
-                    let alpha_pow_offset = alpha.exp_u64(num_reduced[log_height] as u64);
-                    let reduced_openings: Challenge =
-                        dot_product(alpha.powers(), openings.iter().copied());
-
-                    info_span!("reduce rows").in_scope(|| {
-                        mat_compressed
-                            .par_iter()
-                            .zip(reduced_opening_for_log_height.par_iter_mut())
-                            // This might be longer, but zip will truncate to smaller subgroup
-                            // (which is ok because it's bitrev)
-                            .zip(inv_denoms.get(&point).unwrap().par_iter())
-                            .for_each(|((&reduced_row, ro), &inv_denom)| {
-                                *ro +=
-                                    alpha_pow_offset * (reduced_openings - reduced_row) * inv_denom
-                            });
-                    });
-
-                    num_reduced[log_height] += mat.width();
-                }
-            }
-        }
-
-        let fri_input = reduced_openings.into_iter().rev().flatten().collect_vec();
-
-        let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
-            TwoAdicFriGenericConfig(PhantomData);
-
-        let fri_proof = prover::prove(&g, &self.fri, fri_input, challenger, |index| {
-            rounds
-                .iter()
-                .map(|(data, _)| {
-                    let log_max_height = log2_strict_usize(self.mmcs.get_max_height(data));
-                    let bits_reduced = log_global_max_height - log_max_height;
-                    let reduced_index = index >> bits_reduced;
-                    let (opened_values, opening_proof) = self.mmcs.open_batch(reduced_index, data);
-                    BatchOpening {
-                        opened_values,
-                        opening_proof,
-                    }
-                })
-                .collect()
-        });
-
-        (all_opened_values, fri_proof)
+        self.open_with_degree_bounds(rounds, &degree_bounds, challenger)
     }
 
     fn verify(
@@ -397,153 +282,252 @@ where
         proof: &Self::Proof,
         challenger: &mut Challenger,
     ) -> Result<(), Self::Error> {
-        // Write evaluations to challenger
-        for (_, round) in &rounds {
-            for (_, mat) in round {
-                for (_, point) in mat {
+        let degree_bounds = rounds
+            .iter()
+            .map(|(_, mats)| mats.iter().map(|(domain, _)| domain.size()).collect())
+            .collect_vec();
+        self.verify_with_degree_bounds(rounds, &degree_bounds, proof, challenger)
+    }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
+where
+    Val: TwoAdicField,
+    InputMmcs: Mmcs<Val>,
+{
+    /// Like `Pcs::open`, but proves that the columns of the `j`th matrix of round `i` are
+    /// polynomials of degree less than `degree_bounds[i][j]`, rather than less than the size of
+    /// the matrix's domain. Bounds need not be powers of two.
+    ///
+    /// See `open_and_reduce_with_degree_bounds` for how the bounds are enforced.
+    #[allow(clippy::type_complexity)]
+    pub fn open_with_degree_bounds<Challenge, Challenger>(
+        &self,
+        rounds: Vec<(
+            &InputMmcs::ProverData<StoredMatrix<Val, InputMmcs>>,
+            Vec<Vec<Challenge>>,
+        )>,
+        degree_bounds: &[Vec<usize>],
+        challenger: &mut Challenger,
+    ) -> (
+        OpenedValues<Challenge>,
+        TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>,
+    )
+    where
+        FriMmcs: Mmcs<Challenge>,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>
+            + CanObserve<FriMmcs::Commitment>
+            + GrindingChallenger<Witness = Val>,
+    {
+        let (all_opened_values, derivatives, fri_input, alpha_pow_witness) =
+            open_and_reduce_with_degree_bounds(
+                &self.mmcs,
+                self.fri.log_blowup,
+                &rounds,
+                degree_bounds,
+                self.fri.commit_proof_of_work_bits,
+                challenger,
+            );
+        let log_global_max_height = log2_strict_usize(fri_input[0].len());
+        let prover_data = rounds.iter().map(|(data, _)| *data).collect_vec();
+
+        let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
+            TwoAdicFriGenericConfig(PhantomData);
+
+        if !self.fri.use_multiproofs {
+            let fri_proof = prover::prove(&g, &self.fri, fri_input, challenger, |index| {
+                open_input_batches(&self.mmcs, &prover_data, log_global_max_height, index)
+            });
+            let proof = TwoAdicFriPcsProof {
+                fri_proof,
+                derivatives,
+                input_multi_proofs: vec![],
+                alpha_pow_witness,
+            };
+            return (all_opened_values, proof);
+        }
+
+        // The inputs are opened at all query indices at once, after FRI has sampled them.
+        let mut indices = vec![];
+        let mut fri_proof = prover::prove(&g, &self.fri, fri_input, challenger, |index| {
+            indices.push(index);
+            vec![]
+        });
+        let (input_openings, input_multi_proofs) =
+            open_input_batches_multi(&self.mmcs, &prover_data, log_global_max_height, &indices);
+        for (query_proof, input_opening) in izip!(&mut fri_proof.query_proofs, input_openings) {
+            query_proof.input_proof = input_opening;
+        }
+
+        let proof = TwoAdicFriPcsProof {
+            fri_proof,
+            derivatives,
+            input_multi_proofs,
+            alpha_pow_witness,
+        };
+        (all_opened_values, proof)
+    }
+
+    /// Verifies a proof produced by `open_with_degree_bounds`, given the same degree bounds.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_with_degree_bounds<Challenge, Challenger>(
+        &self,
+        rounds: Vec<(
+            InputMmcs::Commitment,
+            Vec<(
+                TwoAdicMultiplicativeCoset<Val>,
+                Vec<(Challenge, Vec<Challenge>)>,
+            )>,
+        )>,
+        degree_bounds: &[Vec<usize>],
+        proof: &TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>,
+        challenger: &mut Challenger,
+    ) -> Result<(), FriError<FriMmcs::Error, InputMmcs::Error>>
+    where
+        FriMmcs: Mmcs<Challenge>,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>
+            + CanObserve<FriMmcs::Commitment>
+            + GrindingChallenger<Witness = Val>,
+    {
+        // Write evaluations, and derivatives at points of the LDE domains, to challenger
+        for ((_, round), derivatives_for_round) in izip!(&rounds, &proof.derivatives) {
+            for ((_, mat), derivatives_for_mat) in izip!(round, derivatives_for_round) {
+                for ((_, point), derivatives) in izip!(mat, derivatives_for_mat) {
                     point
                         .iter()
+                        .chain(derivatives)
                         .for_each(|&opening| challenger.observe_algebra_element(opening));
                 }
             }
         }
 
-        // Batch combination challenge
+        // Batch combination challenge, after checking the PoW before it, if any.
+        if proof.alpha_pow_witness.is_some() != (self.fri.commit_proof_of_work_bits > 0) {
+            return Err(FriError::InvalidProofShape);
+        }
+        let pow_ok = proof.alpha_pow_witness.is_none_or(|witness| {
+            challenger.check_witness(self.fri.commit_proof_of_work_bits, witness)
+        });
+        if !pow_ok {
+            return Err(FriError::InvalidPcsPowWitness);
+        }
         let alpha: Challenge = challenger.sample_algebra_element();
 
-        let log_global_max_height =
-            proof.commit_phase_commits.len() + self.fri.log_blowup + self.fri.log_final_poly_len;
+        // With higher folding arities the number of commit phase rounds no longer determines the
+        // height of the largest input, so we derive it from the opened domains instead.
+        let log_global_max_height = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .map(|(domain, _)| log2_strict_usize(domain.size()) + self.fri.log_blowup)
+            .max()
+            .unwrap_or(self.fri.log_blowup + self.fri.log_final_poly_len);
+
+        // Every input proof must open each batch, with a proof of its own unless the openings are
+        // covered by the input multiproofs. Each matrix must be opened at a point at least, as
+        // only the values claimed there fix its width. The values claimed at each of its points
+        // must agree on that width, and come with as many derivatives if the point lies in the
+        // matrix's LDE domain.
+        let use_multiproofs = self.fri.use_multiproofs;
+        let num_multi_proofs = if use_multiproofs { rounds.len() } else { 0 };
+        let well_shaped = rounds.iter().flat_map(|(_, mats)| mats).all(|(_, points)| {
+            !points.is_empty() && points.iter().map(|(_, values)| values.len()).all_equal()
+        }) && degree_bounds.len() == rounds.len()
+            && izip!(&rounds, degree_bounds).all(|((_, mats), bounds)| {
+                bounds.len() == mats.len()
+                    && izip!(mats, bounds)
+                        .all(|((domain, _), &bound)| (1..=domain.size()).contains(&bound))
+            })
+            && proof.derivatives.len() == rounds.len()
+            && izip!(&rounds, &proof.derivatives).all(|((_, mats), derivatives_for_round)| {
+                derivatives_for_round.len() == mats.len()
+                    && izip!(mats, derivatives_for_round).all(
+                        |((domain, points), derivatives_for_mat)| {
+                            let log_height = log2_strict_usize(domain.size()) + self.fri.log_blowup;
+                            derivatives_for_mat.len() == points.len()
+                                && izip!(points, derivatives_for_mat).all(
+                                    |((z, values), derivatives)| {
+                                        let num_derivatives =
+                                            if in_lde_domain::<Val, _>(*z, log_height) {
+                                                values.len()
+                                            } else {
+                                                0
+                                            };
+                                        derivatives.len() == num_derivatives
+                                    },
+                                )
+                        },
+                    )
+            })
+            && proof.input_multi_proofs.len() == num_multi_proofs
+            && proof.fri_proof.query_proofs.iter().all(|query_proof| {
+                query_proof.input_proof.len() == rounds.len()
+                    && query_proof.input_proof.iter().all(|batch_opening| {
+                        batch_opening.opening_proof.is_some() != use_multiproofs
+                    })
+            });
+        if !well_shaped {
+            return Err(FriError::InvalidProofShape);
+        }
 
         let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
             TwoAdicFriGenericConfig(PhantomData);
 
-        verifier::verify(&g, &self.fri, proof, challenger, |index, input_proof| {
-            // TODO: separate this out into functions
-
-            // log_height -> (alpha_pow, reduced_opening)
-            let mut reduced_openings = BTreeMap::<usize, (Challenge, Challenge)>::new();
-
-            for (batch_opening, (batch_commit, mats)) in izip!(input_proof, &rounds) {
-                let batch_heights = mats
-                    .iter()
-                    .map(|(domain, _)| domain.size() << self.fri.log_blowup)
-                    .collect_vec();
-                let batch_dims = batch_heights
-                    .iter()
-                    // TODO: MMCS doesn't really need width; we put 0 for now.
-                    .map(|&height| Dimensions { width: 0, height })
-                    .collect_vec();
-
-                if let Some(batch_max_height) = batch_heights.iter().max() {
-                    let log_batch_max_height = log2_strict_usize(*batch_max_height);
-                    let bits_reduced = log_global_max_height - log_batch_max_height;
-                    let reduced_index = index >> bits_reduced;
-
-                    self.mmcs.verify_batch(
-                        batch_commit,
-                        &batch_dims,
-                        reduced_index,
-                        &batch_opening.opened_values,
-                        &batch_opening.opening_proof,
-                    )?;
-                } else {
-                    // Empty batch?
-                    self.mmcs.verify_batch(
-                        batch_commit,
-                        &[],
-                        0,
-                        &batch_opening.opened_values,
-                        &batch_opening.opening_proof,
-                    )?;
-                }
-
-                for (mat_opening, (mat_domain, mat_points_and_values)) in
-                    izip!(&batch_opening.opened_values, mats)
+        let mut indices = vec![];
+        verifier::verify(
+            &g,
+            &self.fri,
+            &proof.fri_proof,
+            challenger,
+            |index, input_proof| {
+                indices.push(index);
+                let mut reduced_openings = verify_and_reduce_with_degree_bounds(
+                    &self.mmcs,
+                    self.fri.log_blowup,
+                    &rounds,
+                    degree_bounds,
+                    &proof.derivatives,
+                    alpha,
+                    log_global_max_height,
+                    index,
+                    input_proof,
+                )?;
+
+                // `reduced_openings` would have a log_height = log_blowup entry only if there was a
+                // trace matrix of height 1. In this case the reduced opening can be skipped as it will
+                // not be checked against any commit phase commit.
+                if reduced_openings
+                    .last()
+                    .is_some_and(|&(log_height, _)| log_height == self.fri.log_blowup)
                 {
-                    let log_height = log2_strict_usize(mat_domain.size()) + self.fri.log_blowup;
-
-                    let bits_reduced = log_global_max_height - log_height;
-                    let rev_reduced_index = reverse_bits_len(index >> bits_reduced, log_height);
-
-                    // todo: this can be nicer with domain methods?
-
-                    let x = Val::GENERATOR
-                        * Val::two_adic_generator(log_height).exp_u64(rev_reduced_index as u64);
-
-                    let (alpha_pow, ro) = reduced_openings
-                        .entry(log_height)
-                        .or_insert((Challenge::ONE, Challenge::ZERO));
-
-                    for (z, ps_at_z) in mat_points_and_values {
-                        for (&p_at_x, &p_at_z) in izip!(mat_opening, ps_at_z) {
-                            let quotient = (-p_at_z + p_at_x) / (-*z + x);
-                            *ro += *alpha_pow * quotient;
-                            *alpha_pow *= alpha;
-                        }
-                    }
+                    let (_, ro) = reduced_openings.pop().unwrap();
+                    assert!(ro.is_zero());
                 }
-            }
 
-            // `reduced_openings` would have a log_height = log_blowup entry only if there was a
-            // trace matrix of height 1. In this case the reduced opening can be skipped as it will
-            // not be checked against any commit phase commit.
-            if let Some((_alpha_pow, ro)) = reduced_openings.remove(&self.fri.log_blowup) {
-                assert!(ro.is_zero());
-            }
+                Ok(reduced_openings)
+            },
+        )?;
 
-            // Return reduced openings descending by log_height.
-            Ok(reduced_openings
-                .into_iter()
-                .rev()
-                .map(|(log_height, (_alpha_pow, ro))| (log_height, ro))
-                .collect())
-        })?;
+        if use_multiproofs {
+            let input_proofs = proof
+                .fri_proof
+                .query_proofs
+                .iter()
+                .map(|query_proof| query_proof.input_proof.as_slice())
+                .collect_vec();
+            verify_input_batches_multi(
+                &self.mmcs,
+                self.fri.log_blowup,
+                &rounds,
+                log_global_max_height,
+                &indices,
+                &input_proofs,
+                &proof.input_multi_proofs,
+            )
+            .map_err(|error| FriError::InputError { query: None, error })?;
+        }
 
         Ok(())
     }
 }
-
-#[instrument(skip_all)]
-fn compute_inverse_denominators<F: TwoAdicField, EF: ExtensionField<F>, M: Matrix<F>>(
-    mats_and_points: &[(Vec<M>, &Vec<Vec<EF>>)],
-    coset_shift: F,
-) -> LinearMap<EF, Vec<EF>> {
-    let mut max_log_height_for_point: LinearMap<EF, usize> = LinearMap::new();
-    for (mats, points) in mats_and_points {
-        for (mat, points_for_mat) in izip!(mats, *points) {
-            let log_height = log2_strict_usize(mat.height());
-            for &z in points_for_mat {
-                if let Some(lh) = max_log_height_for_point.get_mut(&z) {
-                    *lh = core::cmp::max(*lh, log_height);
-                } else {
-                    max_log_height_for_point.insert(z, log_height);
-                }
-            }
-        }
-    }
-
-    // Compute the largest subgroup we will use, in bitrev order.
-    let max_log_height = *max_log_height_for_point.values().max().unwrap();
-    let mut subgroup = cyclic_subgroup_coset_known_order(
-        F::two_adic_generator(max_log_height),
-        coset_shift,
-        1 << max_log_height,
-    )
-    .collect_vec();
-    reverse_slice_index_bits(&mut subgroup);
-
-    max_log_height_for_point
-        .into_iter()
-        .map(|(z, log_height)| {
-            (
-                z,
-                batch_multiplicative_inverse(
-                    &subgroup[..(1 << log_height)]
-                        .iter()
-                        .map(|&x| z - x)
-                        .collect_vec(),
-                ),
-            )
-        })
-        .collect()
-}
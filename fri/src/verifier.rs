@@ -1,22 +1,110 @@
 use alloc::vec;
 use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
 
 use itertools::{Itertools, izip};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
 use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::Dimensions;
-use p3_util::reverse_bits_len;
+use p3_util::{log2_strict_usize, reverse_bits_len};
 
 use crate::{CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof};
 
+/// Why a FRI proof was rejected. Queries are numbered in the order they were sampled, and commit
+/// phase rounds, i.e. FRI layers, from the largest.
 #[derive(Debug)]
 pub enum FriError<CommitMmcsErr, InputError> {
+    /// The proof as a whole doesn't have the shape the config calls for, e.g. the wrong number of
+    /// queries or final polynomial coefficients.
     InvalidProofShape,
-    CommitPhaseMmcsError(CommitMmcsErr),
-    InputError(InputError),
-    FinalPolyMismatch,
-    InvalidPowWitness,
+    /// A query proof doesn't fit the heights of its inputs, in the given round if the problem is
+    /// local to one.
+    InvalidQueryShape { query: usize, round: Option<usize> },
+    /// The opening of a commit phase codeword failed to verify, for the given query, or for all
+    /// queries at once if it was proven by a multiproof.
+    CommitPhaseMmcsError {
+        round: usize,
+        query: Option<usize>,
+        error: CommitMmcsErr,
+    },
+    /// The inputs failed to verify, for the given query, or for all queries at once if they were
+    /// proven by multiproofs.
+    InputError {
+        query: Option<usize>,
+        error: InputError,
+    },
+    /// The folded evaluation of a query, sampled at `index`, doesn't match the final polynomial.
+    FinalPolyMismatch { query: usize, index: usize },
+    /// The proof of work before the folding challenge of the given round, or before the queries
+    /// if `round` is `None`, is invalid.
+    InvalidPowWitness { round: Option<usize> },
+    /// The proof of work before a challenge which the PCS samples ahead of FRI, such as the
+    /// batching challenge, is invalid.
+    InvalidPcsPowWitness,
+}
+
+impl<CommitMmcsErr: Display, InputError: Display> Display for FriError<CommitMmcsErr, InputError> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidProofShape => write!(f, "invalid FRI proof shape"),
+            Self::InvalidQueryShape { query, round } => {
+                write!(f, "invalid shape of FRI query {query}")?;
+                if let Some(round) = round {
+                    write!(f, " in round {round}")?;
+                }
+                Ok(())
+            }
+            Self::CommitPhaseMmcsError {
+                round,
+                query: Some(query),
+                error,
+            } => write!(
+                f,
+                "invalid opening of FRI round {round} for query {query}: {error}"
+            ),
+            Self::CommitPhaseMmcsError {
+                round,
+                query: None,
+                error,
+            } => write!(f, "invalid multiproof of FRI round {round}: {error}"),
+            Self::InputError {
+                query: Some(query),
+                error,
+            } => write!(f, "invalid FRI inputs for query {query}: {error}"),
+            Self::InputError { query: None, error } => {
+                write!(f, "invalid FRI input multiproofs: {error}")
+            }
+            Self::FinalPolyMismatch { query, index } => write!(
+                f,
+                "FRI query {query} at index {index} does not match the final polynomial"
+            ),
+            Self::InvalidPowWitness { round: Some(round) } => {
+                write!(f, "invalid proof of work witness for FRI round {round}")
+            }
+            Self::InvalidPowWitness { round: None } => {
+                write!(f, "invalid proof of work witness for the FRI queries")
+            }
+            Self::InvalidPcsPowWitness => {
+                write!(f, "invalid proof of work witness for a PCS challenge")
+            }
+        }
+    }
+}
+
+impl<CommitMmcsErr, InputError> Error for FriError<CommitMmcsErr, InputError>
+where
+    CommitMmcsErr: Error + 'static,
+    InputError: Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::CommitPhaseMmcsError { error, .. } => Some(error),
+            Self::InputError { error, .. } => Some(error),
+            _ => None,
+        }
+    }
 }
 
 pub fn verify<G, Val, Challenge, M, Challenger>(
@@ -24,7 +112,34 @@ pub fn verify<G, Val, Challenge, M, Challenger>(
     config: &FriConfig<M>,
     proof: &FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
     challenger: &mut Challenger,
-    open_input: impl Fn(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
+    open_input: impl FnMut(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
+) -> Result<(), FriError<M::Error, G::InputError>>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+    G: FriGenericConfig<Challenge>,
+{
+    verify_with_folding_challenges(
+        g,
+        config,
+        proof,
+        challenger,
+        |_, challenger| challenger.sample_algebra_element(),
+        open_input,
+    )
+}
+
+/// Like `verify`, but the folding challenge of each commit phase round is obtained from
+/// `sample_beta`, mirroring `prover::prove_with_folding_challenges`.
+pub fn verify_with_folding_challenges<G, Val, Challenge, M, Challenger>(
+    g: &G,
+    config: &FriConfig<M>,
+    proof: &FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
+    challenger: &mut Challenger,
+    mut sample_beta: impl FnMut(usize, &mut Challenger) -> Challenge,
+    mut open_input: impl FnMut(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
 ) -> Result<(), FriError<M::Error, G::InputError>>
 where
     Val: Field,
@@ -33,14 +148,38 @@ where
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
     G: FriGenericConfig<Challenge>,
 {
+    let num_commit_pow_witnesses = if config.commit_proof_of_work_bits > 0 {
+        proof.commit_phase_commits.len()
+    } else {
+        0
+    };
+    if proof.commit_pow_witnesses.len() != num_commit_pow_witnesses {
+        return Err(FriError::InvalidProofShape);
+    }
+
     let betas: Vec<Challenge> = proof
         .commit_phase_commits
         .iter()
-        .map(|comm| {
+        .enumerate()
+        .map(|(round, comm)| {
             challenger.observe(comm.clone());
-            challenger.sample_algebra_element()
+            // Check the round's PoW, if any.
+            let pow_ok = proof
+                .commit_pow_witnesses
+                .get(round)
+                .is_none_or(|&witness| {
+                    challenger.check_witness(config.commit_proof_of_work_bits, witness)
+                });
+            if !pow_ok {
+                return Err(FriError::InvalidPowWitness { round: Some(round) });
+            }
+            Ok(sample_beta(round, challenger))
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
+
+    if proof.final_poly.len() != config.final_poly_len() {
+        return Err(FriError::InvalidProofShape);
+    }
 
     // Observe all coefficients of the final polynomial.
     proof
@@ -51,18 +190,59 @@ where
     if proof.query_proofs.len() != config.num_queries {
         return Err(FriError::InvalidProofShape);
     }
+    let num_multi_proofs = if config.use_multiproofs {
+        proof.commit_phase_commits.len()
+    } else {
+        0
+    };
+    if proof.commit_phase_multi_proofs.len() != num_multi_proofs {
+        return Err(FriError::InvalidProofShape);
+    }
 
     // Check PoW.
     if !challenger.check_witness(config.proof_of_work_bits, proof.pow_witness) {
-        return Err(FriError::InvalidPowWitness);
+        return Err(FriError::InvalidPowWitness { round: None });
     }
 
-    let log_max_height =
-        proof.commit_phase_commits.len() + config.log_blowup + config.log_final_poly_len;
+    // The arity of each round depends on the heights of the inputs, which are only known once
+    // they are opened, so the arities are read off the shape of the proof here and checked against
+    // the inputs in `verify_query`.
+    let log_final_height = config.log_blowup + config.log_final_poly_len;
+    let log_arities = match proof.query_proofs.first() {
+        Some(qp) => qp
+            .commit_phase_openings
+            .iter()
+            .enumerate()
+            .map(|(round, opening)| {
+                let arity = opening.sibling_values.len() + 1;
+                if arity > 1 && arity.is_power_of_two() {
+                    Ok(log2_strict_usize(arity))
+                } else {
+                    Err(FriError::InvalidQueryShape {
+                        query: 0,
+                        round: Some(round),
+                    })
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![],
+    };
+    let log_max_height = log_arities.iter().sum::<usize>() + log_final_height;
+
+    // With multiproofs, the row indices and opened rows of each round are collected from all
+    // queries, and checked together once every query has been folded.
+    let mut multi_openings = vec![(vec![], vec![]); num_multi_proofs];
+
+    for (query, qp) in proof.query_proofs.iter().enumerate() {
+        if qp.commit_phase_openings.len() != proof.commit_phase_commits.len() {
+            return Err(FriError::InvalidQueryShape { query, round: None });
+        }
 
-    for qp in &proof.query_proofs {
         let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
-        let ro = open_input(index, &qp.input_proof).map_err(FriError::InputError)?;
+        let ro = open_input(index, &qp.input_proof).map_err(|error| FriError::InputError {
+            query: Some(query),
+            error,
+        })?;
 
         debug_assert!(
             ro.iter().tuple_windows().all(|((l, _), (r, _))| l > r),
@@ -72,6 +252,7 @@ where
         let folded_eval = verify_query(
             g,
             config,
+            query,
             index >> g.extra_query_index_bits(),
             izip!(
                 &betas,
@@ -80,9 +261,10 @@ where
             ),
             ro,
             log_max_height,
+            &mut multi_openings,
         )?;
 
-        let final_poly_index = index >> (proof.commit_phase_commits.len());
+        let final_poly_index = index >> (log_max_height - log_final_height);
 
         let mut eval = Challenge::ZERO;
 
@@ -100,64 +282,143 @@ where
         }
 
         if eval != folded_eval {
-            return Err(FriError::FinalPolyMismatch);
+            return Err(FriError::FinalPolyMismatch { query, index });
         }
     }
 
+    let mut log_height = log_max_height;
+    for (round, (comm, &log_arity, (indices, opened_rows), multi_proof)) in izip!(
+        &proof.commit_phase_commits,
+        &log_arities,
+        &multi_openings,
+        &proof.commit_phase_multi_proofs
+    )
+    .enumerate()
+    {
+        let dims = &[Dimensions {
+            width: 1 << log_arity,
+            height: 1 << (log_height - log_arity),
+        }];
+        config
+            .mmcs
+            .verify_multi(comm, dims, indices, opened_rows, multi_proof)
+            .map_err(|error| FriError::CommitPhaseMmcsError {
+                round,
+                query: None,
+                error,
+            })?;
+        log_height -= log_arity;
+    }
+
     Ok(())
 }
 
+/// For each commit phase round, the row indices of the queries and the rows opened there.
+type MultiOpenings<F> = Vec<(Vec<usize>, Vec<Vec<Vec<F>>>)>;
+
 type CommitStep<'a, F, M> = (
     &'a F,
     &'a <M as Mmcs<F>>::Commitment,
     &'a CommitPhaseProofStep<F, M>,
 );
 
+#[allow(clippy::too_many_arguments)]
 fn verify_query<'a, G, F, M>(
     g: &G,
     config: &FriConfig<M>,
+    query: usize,
     mut index: usize,
     steps: impl Iterator<Item = CommitStep<'a, F, M>>,
     reduced_openings: Vec<(usize, F)>,
     log_max_height: usize,
+    multi_openings: &mut MultiOpenings<F>,
 ) -> Result<F, FriError<M::Error, G::InputError>>
 where
     F: Field,
     M: Mmcs<F> + 'a,
     G: FriGenericConfig<F>,
 {
+    if reduced_openings
+        .first()
+        .is_some_and(|&(lh, _)| lh != log_max_height)
+    {
+        return Err(FriError::InvalidQueryShape { query, round: None });
+    }
+
+    let log_final_height = config.log_blowup + config.log_final_poly_len;
     let mut folded_eval = F::ZERO;
     let mut ro_iter = reduced_openings.into_iter().peekable();
+    let mut log_height = log_max_height;
 
-    for (log_folded_height, (&beta, comm, opening)) in izip!((0..log_max_height).rev(), steps) {
-        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
+    for (round, (&beta, comm, opening)) in steps.enumerate() {
+        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_height) {
             folded_eval += ro;
         }
+        if log_height <= log_final_height || ro_iter.peek().is_some_and(|(lh, _)| *lh >= log_height)
+        {
+            return Err(FriError::InvalidQueryShape {
+                query,
+                round: Some(round),
+            });
+        }
+
+        let log_arity = config.log_arity_for_round(
+            log_height,
+            ro_iter.peek().map(|(lh, _)| *lh),
+            log_final_height,
+        );
+        if opening.sibling_values.len() + 1 != 1 << log_arity {
+            return Err(FriError::InvalidQueryShape {
+                query,
+                round: Some(round),
+            });
+        }
+        let log_folded_height = log_height - log_arity;
 
-        let index_sibling = index ^ 1;
-        let index_pair = index >> 1;
+        let index_in_row = index & ((1 << log_arity) - 1);
+        index >>= log_arity;
 
-        let mut evals = vec![folded_eval; 2];
-        evals[index_sibling % 2] = opening.sibling_value;
+        let mut evals = opening.sibling_values.clone();
+        evals.insert(index_in_row, folded_eval);
 
         let dims = &[Dimensions {
-            width: 2,
+            width: 1 << log_arity,
             height: 1 << log_folded_height,
         }];
-        config
-            .mmcs
-            .verify_batch(
-                comm,
-                dims,
-                index_pair,
-                &[evals.clone()],
-                &opening.opening_proof,
-            )
-            .map_err(FriError::CommitPhaseMmcsError)?;
-
-        index = index_pair;
-
-        folded_eval = g.fold_row(index, log_folded_height, beta, evals.into_iter());
+        match (&opening.opening_proof, multi_openings.get_mut(round)) {
+            (Some(opening_proof), None) => config
+                .mmcs
+                .verify_batch(comm, dims, index, &[evals.clone()], opening_proof)
+                .map_err(|error| FriError::CommitPhaseMmcsError {
+                    round,
+                    query: Some(query),
+                    error,
+                })?,
+            (None, Some((indices, opened_rows))) => {
+                indices.push(index);
+                opened_rows.push(vec![evals.clone()]);
+            }
+            _ => {
+                return Err(FriError::InvalidQueryShape {
+                    query,
+                    round: Some(round),
+                });
+            }
+        }
+
+        folded_eval = g.fold_row_with_arity(index, log_folded_height, log_arity, beta, evals);
+        log_height = log_folded_height;
+    }
+
+    if log_height != log_final_height {
+        return Err(FriError::InvalidQueryShape { query, round: None });
+    }
+    // An input may have exactly the final height, in which case it is added after the last round.
+    if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_height) {
+        folded_eval += ro;
+    }
+    if ro_iter.next().is_some() {
+        return Err(FriError::InvalidQueryShape { query, round: None });
     }
 
     debug_assert!(
@@ -165,10 +426,6 @@ where
         "index was {}",
         index,
     );
-    debug_assert!(
-        ro_iter.next().is_none(),
-        "verifier reduced_openings were not in descending order?"
-    );
 
     Ok(folded_eval)
 }
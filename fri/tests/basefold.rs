@@ -0,0 +1,200 @@
+use itertools::{Itertools, izip};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
+use p3_commit::{ExtensionMmcs, MultilinearPcs};
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::{BasefoldPcs, FriConfig};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Dft = Radix2DitParallel<Val>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type MyPcs = BasefoldPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+fn seeded_rng() -> impl Rng {
+    ChaCha20Rng::seed_from_u64(0)
+}
+
+fn get_pcs(log_blowup: usize, log_final_poly_len: usize) -> (MyPcs, Challenger) {
+    let perm = Perm::new_from_rng_128(&mut seeded_rng());
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup,
+        log_final_poly_len,
+        num_queries: 10,
+        proof_of_work_bits: 8,
+        commit_proof_of_work_bits: 0,
+        log_folding_arity: 1,
+        use_multiproofs: false,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+    (pcs, Challenger::new(perm))
+}
+
+/// Evaluates every column of `mat` at `point`, by repeatedly binding the lowest variable.
+fn naive_evaluate(mat: &RowMajorMatrix<Val>, point: &[Challenge]) -> Vec<Challenge> {
+    (0..mat.width())
+        .map(|col| {
+            let mut evals = mat
+                .values
+                .iter()
+                .skip(col)
+                .step_by(mat.width())
+                .map(|&x| Challenge::from(x))
+                .collect_vec();
+            for &z in point {
+                evals = evals
+                    .chunks_exact(2)
+                    .map(|e| e[0] + z * (e[1] - e[0]))
+                    .collect();
+            }
+            evals[0]
+        })
+        .collect()
+}
+
+/// Commits to `widths_by_round.len()` batches of random polynomials in `num_vars` variables and
+/// opens the `j`-th matrix of each round at the points `num_points_by_mat(j)` sampled after the
+/// commitments. Returns the verifier's result.
+fn do_test_basefold(
+    (pcs, challenger): &(MyPcs, Challenger),
+    num_vars: usize,
+    widths_by_round: &[&[usize]],
+    num_points_by_mat: impl Fn(usize) -> usize,
+    tamper: impl FnOnce(&mut Vec<Vec<Vec<Vec<Challenge>>>>),
+) -> Result<(), <MyPcs as MultilinearPcs<Challenge, Challenger>>::Error> {
+    let mut rng = seeded_rng();
+    let polys_by_round = widths_by_round
+        .iter()
+        .map(|widths| {
+            widths
+                .iter()
+                .map(|&width| RowMajorMatrix::<Val>::rand(&mut rng, 1 << num_vars, width))
+                .collect_vec()
+        })
+        .collect_vec();
+
+    let (commits_by_round, data_by_round): (Vec<_>, Vec<_>) = polys_by_round
+        .iter()
+        .map(|polys| MultilinearPcs::<Challenge, Challenger>::commit(pcs, polys.clone()))
+        .unzip();
+
+    let mut p_challenger = challenger.clone();
+    p_challenger.observe_slice(&commits_by_round);
+    let points_by_round = polys_by_round
+        .iter()
+        .map(|polys| {
+            (0..polys.len())
+                .map(|j| {
+                    (0..num_points_by_mat(j))
+                        .map(|_| {
+                            (0..num_vars)
+                                .map(|_| p_challenger.sample_algebra_element())
+                                .collect_vec()
+                        })
+                        .collect_vec()
+                })
+                .collect_vec()
+        })
+        .collect_vec();
+
+    let (mut opened_values, proof) = pcs.open(
+        izip!(&data_by_round, points_by_round.clone()).collect(),
+        &mut p_challenger,
+    );
+    for (polys, points, values) in izip!(&polys_by_round, &points_by_round, &opened_values) {
+        for (poly, points, values) in izip!(polys, points, values) {
+            for (point, ys) in izip!(points, values) {
+                assert_eq!(*ys, naive_evaluate(poly, point));
+            }
+        }
+    }
+    tamper(&mut opened_values);
+
+    let mut v_challenger = challenger.clone();
+    v_challenger.observe_slice(&commits_by_round);
+    for points in points_by_round.iter().flatten().flatten() {
+        for _ in points {
+            let _: Challenge = v_challenger.sample_algebra_element();
+        }
+    }
+    let claims = izip!(commits_by_round, points_by_round, opened_values)
+        .map(|(commit, points, values)| {
+            (
+                commit,
+                izip!(points, values)
+                    .map(|(points, values)| (num_vars, izip!(points, values).collect_vec()))
+                    .collect_vec(),
+            )
+        })
+        .collect_vec();
+    pcs.verify(claims, &proof, &mut v_challenger)
+}
+
+#[test]
+fn test_single_point() {
+    do_test_basefold(&get_pcs(1, 0), 6, &[&[3]], |_| 1, |_| {}).unwrap();
+}
+
+#[test]
+fn test_single_point_many_rounds() {
+    do_test_basefold(&get_pcs(2, 0), 5, &[&[3, 1], &[7]], |_| 1, |_| {}).unwrap();
+}
+
+#[test]
+fn test_early_stopping() {
+    do_test_basefold(&get_pcs(1, 2), 7, &[&[4, 2]], |_| 1, |_| {}).unwrap();
+}
+
+#[test]
+fn test_multiple_points() {
+    do_test_basefold(&get_pcs(1, 0), 6, &[&[3, 2], &[5]], |j| j + 1, |_| {}).unwrap();
+}
+
+#[test]
+fn test_unopened_matrix() {
+    do_test_basefold(&get_pcs(1, 1), 4, &[&[3, 2, 1]], |j| j % 2, |_| {}).unwrap();
+}
+
+#[test]
+fn test_wrong_value_single_point() {
+    let res = do_test_basefold(
+        &get_pcs(1, 0),
+        6,
+        &[&[3]],
+        |_| 1,
+        |values| values[0][0][0][1] += Challenge::ONE,
+    );
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_wrong_value_multiple_points() {
+    let res = do_test_basefold(
+        &get_pcs(1, 0),
+        6,
+        &[&[3, 2]],
+        |j| j + 1,
+        |values| values[0][1][1][0] += Challenge::ONE,
+    );
+    assert!(res.is_err());
+}
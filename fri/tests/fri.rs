@@ -7,6 +7,7 @@ use p3_commit::ExtensionMmcs;
 use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::verifier::FriError;
 use p3_fri::{FriConfig, TwoAdicFriGenericConfig, prover, verifier};
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
@@ -29,7 +30,12 @@ type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
 type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
 type MyFriConfig = FriConfig<ChallengeMmcs>;
 
-fn get_ldt_for_testing<R: Rng>(rng: &mut R, log_final_poly_len: usize) -> (Perm, MyFriConfig) {
+fn get_ldt_for_testing<R: Rng>(
+    rng: &mut R,
+    log_final_poly_len: usize,
+    log_folding_arity: usize,
+    commit_proof_of_work_bits: usize,
+) -> (Perm, MyFriConfig) {
     let perm = Perm::new_from_rng_128(rng);
     let hash = MyHash::new(perm.clone());
     let compress = MyCompress::new(perm.clone());
@@ -39,13 +45,26 @@ fn get_ldt_for_testing<R: Rng>(rng: &mut R, log_final_poly_len: usize) -> (Perm,
         log_final_poly_len,
         num_queries: 10,
         proof_of_work_bits: 8,
+        commit_proof_of_work_bits,
+        log_folding_arity,
+        use_multiproofs: false,
         mmcs,
     };
     (perm, fri_config)
 }
 
-fn do_test_fri_ldt<R: Rng>(rng: &mut R, log_final_poly_len: usize) {
-    let (perm, fc) = get_ldt_for_testing(rng, log_final_poly_len);
+fn do_test_fri_ldt<R: Rng>(
+    rng: &mut R,
+    log_final_poly_len: usize,
+    log_folding_arity: usize,
+    commit_proof_of_work_bits: usize,
+) {
+    let (perm, fc) = get_ldt_for_testing(
+        rng,
+        log_final_poly_len,
+        log_folding_arity,
+        commit_proof_of_work_bits,
+    );
     let dft = Radix2Dit::default();
 
     let shift = Val::GENERATOR;
@@ -109,7 +128,7 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R, log_final_poly_len: usize) {
         (proof, chal.sample_bits(8))
     };
 
-    let mut v_challenger = Challenger::new(perm);
+    let mut v_challenger = Challenger::new(perm.clone());
     let _alpha: Challenge = v_challenger.sample_algebra_element();
     verifier::verify(
         &TwoAdicFriGenericConfig::<Vec<(usize, Challenge)>, ()>(PhantomData),
@@ -125,6 +144,28 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R, log_final_poly_len: usize) {
         v_challenger.sample_bits(8),
         "prover and verifier transcript have same state after FRI"
     );
+
+    if commit_proof_of_work_bits > 0 {
+        assert_eq!(
+            proof.commit_pow_witnesses.len(),
+            proof.commit_phase_commits.len()
+        );
+        let mut tampered = proof;
+        tampered.commit_pow_witnesses[0] += Val::ONE;
+        let mut v_challenger = Challenger::new(perm);
+        let _alpha: Challenge = v_challenger.sample_algebra_element();
+        let result = verifier::verify(
+            &TwoAdicFriGenericConfig::<Vec<(usize, Challenge)>, ()>(PhantomData),
+            &fc,
+            &tampered,
+            &mut v_challenger,
+            |_index, proof| Ok(proof.clone()),
+        );
+        assert!(matches!(
+            result,
+            Err(FriError::InvalidPowWitness { round: Some(0) })
+        ));
+    }
 }
 
 #[test]
@@ -132,7 +173,17 @@ fn test_fri_ldt() {
     // FRI is kind of flaky depending on indexing luck
     for i in 0..4 {
         let mut rng = ChaCha20Rng::seed_from_u64(i as u64);
-        do_test_fri_ldt(&mut rng, i + 1);
+        do_test_fri_ldt(&mut rng, i + 1, 1, 0);
+    }
+}
+
+#[test]
+fn test_fri_ldt_higher_arity() {
+    for log_folding_arity in 2..=4 {
+        for log_final_poly_len in 0..3 {
+            let mut rng = ChaCha20Rng::seed_from_u64(log_folding_arity as u64);
+            do_test_fri_ldt(&mut rng, log_final_poly_len, log_folding_arity, 0);
+        }
     }
 }
 
@@ -143,6 +194,12 @@ fn test_fri_ldt_should_panic() {
     // FRI is kind of flaky depending on indexing luck
     for i in 0..4 {
         let mut rng = ChaCha20Rng::seed_from_u64(i);
-        do_test_fri_ldt(&mut rng, 5);
+        do_test_fri_ldt(&mut rng, 5, 1, 0);
     }
 }
+
+#[test]
+fn test_fri_ldt_commit_pow() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    do_test_fri_ldt(&mut rng, 1, 2, 8);
+}
@@ -1,11 +1,13 @@
 use itertools::{Itertools, izip};
 use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
 use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
-use p3_commit::{ExtensionMmcs, Pcs, PolynomialSpace};
-use p3_dft::Radix2DitParallel;
+use p3_commit::{ExtensionMmcs, Pcs, PolynomialSpace, SeededRandomness};
+use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
 use p3_field::extension::BinomialExtensionField;
-use p3_field::{ExtensionField, Field};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_fri::verifier::FriError;
 use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
@@ -151,6 +153,11 @@ macro_rules! make_tests_for_pcs {
 }
 
 mod babybear_fri_pcs {
+    use p3_dft::Radix2Dit;
+    use p3_field::TwoAdicField;
+    use p3_fri::open_and_reduce_with_degree_bounds;
+    use p3_util::reverse_slice_index_bits;
+
     use super::*;
 
     type Val = BabyBear;
@@ -168,12 +175,20 @@ mod babybear_fri_pcs {
     type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
     type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
 
-    fn get_pcs(log_blowup: usize) -> (MyPcs, Challenger) {
+    fn get_mmcs() -> (ValMmcs, Perm) {
         let perm = Perm::new_from_rng_128(&mut seeded_rng());
         let hash = MyHash::new(perm.clone());
         let compress = MyCompress::new(perm.clone());
+        (ValMmcs::new(hash, compress), perm)
+    }
 
-        let val_mmcs = ValMmcs::new(hash, compress);
+    fn get_pcs(
+        log_blowup: usize,
+        log_folding_arity: usize,
+        use_multiproofs: bool,
+        commit_proof_of_work_bits: usize,
+    ) -> (MyPcs, Challenger) {
+        let (val_mmcs, perm) = get_mmcs();
         let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
 
         let fri_config = FriConfig {
@@ -181,6 +196,9 @@ mod babybear_fri_pcs {
             log_final_poly_len: 0,
             num_queries: 10,
             proof_of_work_bits: 8,
+            commit_proof_of_work_bits,
+            log_folding_arity,
+            use_multiproofs,
             mmcs: challenge_mmcs,
         };
 
@@ -189,10 +207,464 @@ mod babybear_fri_pcs {
     }
 
     mod blowup_1 {
-        make_tests_for_pcs!(super::get_pcs(1));
+        make_tests_for_pcs!(super::get_pcs(1, 1, false, 0));
     }
     mod blowup_2 {
-        make_tests_for_pcs!(super::get_pcs(2));
+        make_tests_for_pcs!(super::get_pcs(2, 1, false, 0));
+    }
+    mod arity_4 {
+        make_tests_for_pcs!(super::get_pcs(1, 2, false, 0));
+    }
+    mod arity_8 {
+        make_tests_for_pcs!(super::get_pcs(2, 3, false, 0));
+    }
+    mod multiproofs {
+        make_tests_for_pcs!(super::get_pcs(1, 1, true, 0));
+    }
+    mod multiproofs_arity_4 {
+        make_tests_for_pcs!(super::get_pcs(2, 2, true, 0));
+    }
+    mod commit_pow {
+        make_tests_for_pcs!(super::get_pcs(1, 2, false, 4));
+    }
+
+    #[test]
+    fn multiproofs_shrink_proofs() {
+        let proof_size = |use_multiproofs| {
+            let (pcs, mut challenger) = get_pcs(1, 1, use_multiproofs, 0);
+            let mut rng = seeded_rng();
+            let evaluations = [10, 8]
+                .map(|log_degree| {
+                    let degree = 1 << log_degree;
+                    (
+                        <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                            &pcs, degree,
+                        ),
+                        RowMajorMatrix::<Val>::rand(&mut rng, degree, 8),
+                    )
+                })
+                .to_vec();
+            let (_, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations);
+            let zeta: Challenge = challenger.sample_algebra_element();
+            let (_, proof) = pcs.open(vec![(&data, vec![vec![zeta]; 2])], &mut challenger);
+            postcard::to_allocvec(&proof).unwrap().len()
+        };
+        assert!(proof_size(true) < proof_size(false));
+    }
+
+    #[test]
+    fn degree_bounds() {
+        let (pcs, challenger) = get_pcs(1, 1, false, 0);
+        let mut rng = seeded_rng();
+        // Over domains of sizes 32, 16 and 64, polynomials of degrees 20, 15 and 0.
+        let evaluations = [(5, 21), (4, 16), (6, 1)]
+            .map(|(log_n, num_coeffs)| {
+                let mut coeffs = RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 3);
+                coeffs.values[num_coeffs * 3..].fill(Val::ZERO);
+                (
+                    <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                        &pcs,
+                        1 << log_n,
+                    ),
+                    Dft::default().dft_batch(coeffs).to_row_major_matrix(),
+                )
+            })
+            .to_vec();
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations.clone());
+
+        let prove_and_verify = |prover_bounds: &[usize], verifier_bounds: &[usize]| {
+            let mut p_challenger = challenger.clone();
+            p_challenger.observe(commit.clone());
+            let zeta: Challenge = p_challenger.sample_algebra_element();
+            let (openings, proof) = pcs.open_with_degree_bounds(
+                vec![(&data, vec![vec![zeta]; 3])],
+                &[prover_bounds.to_vec()],
+                &mut p_challenger,
+            );
+
+            let mut v_challenger = challenger.clone();
+            v_challenger.observe(commit.clone());
+            let claims = izip!(&evaluations, &openings[0])
+                .map(|((domain, _), mat_openings)| (*domain, vec![(zeta, mat_openings[0].clone())]))
+                .collect_vec();
+            pcs.verify_with_degree_bounds(
+                vec![(commit.clone(), claims)],
+                &[verifier_bounds.to_vec()],
+                &proof,
+                &mut v_challenger,
+            )
+        };
+
+        prove_and_verify(&[21, 16, 1], &[21, 16, 1]).unwrap();
+        prove_and_verify(&[30, 16, 5], &[30, 16, 5]).unwrap();
+        // The verifier must hold the prover to its own bounds.
+        assert!(prove_and_verify(&[21, 16, 1], &[20, 16, 1]).is_err());
+        assert!(prove_and_verify(&[32, 16, 1], &[21, 16, 1]).is_err());
+        // Bounds must lie between 1 and the size of the domain.
+        assert!(prove_and_verify(&[21, 16, 1], &[21, 17, 1]).is_err());
+        // A bound below the true degree is rejected, even if the prover claims it too.
+        assert!(prove_and_verify(&[20, 16, 1], &[20, 16, 1]).is_err());
+        assert!(prove_and_verify(&[21, 8, 1], &[21, 8, 1]).is_err());
+    }
+
+    #[test]
+    fn alpha_pow_is_checked() {
+        let (pcs, challenger) = get_pcs(1, 1, false, 8);
+        let mut rng = seeded_rng();
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 5);
+        let evaluations = RowMajorMatrix::<Val>::rand(&mut rng, 1 << 5, 3);
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evaluations)]);
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit.clone());
+        let zeta: Challenge = p_challenger.sample_algebra_element();
+        let (openings, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+        let witness = proof.alpha_pow_witness.unwrap();
+
+        let verify = |alpha_pow_witness| {
+            let mut proof = proof.clone();
+            proof.alpha_pow_witness = alpha_pow_witness;
+            let mut v_challenger = challenger.clone();
+            v_challenger.observe(commit.clone());
+            let claims = vec![(domain, vec![(zeta, openings[0][0][0].clone())])];
+            pcs.verify(vec![(commit.clone(), claims)], &proof, &mut v_challenger)
+        };
+        verify(Some(witness)).unwrap();
+        assert!(matches!(
+            verify(Some(witness + Val::ONE)),
+            Err(FriError::InvalidPcsPowWitness)
+        ));
+        assert!(matches!(verify(None), Err(FriError::InvalidProofShape)));
+    }
+
+    #[test]
+    fn unopened_matrices_are_rejected() {
+        let (pcs, challenger) = get_pcs(1, 1, false, 0);
+        let mut rng = seeded_rng();
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 4);
+        let evaluations = vec![
+            (domain, RowMajorMatrix::<Val>::rand(&mut rng, 1 << 4, 3)),
+            (domain, RowMajorMatrix::<Val>::rand(&mut rng, 1 << 4, 2)),
+        ];
+        let (commit, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations);
+
+        // Nothing in the statement fixes the width of the second matrix.
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit.clone());
+        let zeta: Challenge = p_challenger.sample_algebra_element();
+        let (openings, proof) =
+            pcs.open(vec![(&data, vec![vec![zeta], vec![]])], &mut p_challenger);
+
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(commit.clone());
+        let claims = vec![
+            (domain, vec![(zeta, openings[0][0][0].clone())]),
+            (domain, vec![]),
+        ];
+        assert!(matches!(
+            pcs.verify(vec![(commit, claims)], &proof, &mut v_challenger),
+            Err(FriError::InvalidProofShape)
+        ));
+    }
+
+    #[test]
+    fn opens_at_domain_points() {
+        let (pcs, challenger) = get_pcs(1, 1, false, 0);
+        let mut rng = seeded_rng();
+        let coeffs = [5, 3].map(|log_n| RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 3));
+        let evaluations = coeffs.clone().map(|coeffs| {
+            (
+                <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                    &pcs,
+                    coeffs.height(),
+                ),
+                Dft::default().dft_batch(coeffs).to_row_major_matrix(),
+            )
+        });
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations.to_vec());
+
+        // A point of every LDE domain, a point of the largest LDE domain only, and a point outside
+        // of both.
+        let points: [Challenge; 3] = [
+            Val::GENERATOR.into(),
+            (Val::GENERATOR * Val::two_adic_generator(6)).into(),
+            rng.random(),
+        ];
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit.clone());
+        let (openings, proof) =
+            pcs.open(vec![(&data, vec![points.to_vec(); 2])], &mut p_challenger);
+        for (mat_coeffs, mat_openings) in izip!(&coeffs, &openings[0]) {
+            for (&point, values) in izip!(&points, mat_openings) {
+                let expected = (0..3)
+                    .map(|col| {
+                        mat_coeffs.values[col..]
+                            .iter()
+                            .step_by(3)
+                            .rev()
+                            .fold(Challenge::ZERO, |acc, &c| acc * point + c)
+                    })
+                    .collect_vec();
+                assert_eq!(values, &expected);
+            }
+        }
+        let num_derivatives =
+            |mat: usize| proof.derivatives[0][mat].iter().map(Vec::len).collect_vec();
+        assert_eq!(num_derivatives(0), [3, 3, 0]);
+        assert_eq!(num_derivatives(1), [3, 0, 0]);
+
+        let verify = |proof| {
+            let mut v_challenger = challenger.clone();
+            v_challenger.observe(commit.clone());
+            let claims = izip!(&evaluations, &openings[0])
+                .map(|((domain, _), mat_openings)| {
+                    (*domain, izip!(points, mat_openings.clone()).collect_vec())
+                })
+                .collect_vec();
+            pcs.verify(vec![(commit.clone(), claims)], proof, &mut v_challenger)
+        };
+        verify(&proof).unwrap();
+        // The derivatives at a point of an LDE domain are not optional.
+        let mut missing_derivatives = proof.clone();
+        missing_derivatives.derivatives[0][1][0].clear();
+        assert!(verify(&missing_derivatives).is_err());
+    }
+
+    #[test]
+    fn domain_point_quotients_are_low_degree() {
+        let (pcs, mut challenger) = get_pcs(1, 1, false, 0);
+        let mut rng = seeded_rng();
+        let evaluations = [5, 3]
+            .map(|log_n| {
+                let mut coeffs = RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 3);
+                // The first matrix has degree 20, and a degree bound to match.
+                if log_n == 5 {
+                    coeffs.values[21 * 3..].fill(Val::ZERO);
+                }
+                (
+                    <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                        &pcs,
+                        1 << log_n,
+                    ),
+                    Dft::default().dft_batch(coeffs).to_row_major_matrix(),
+                )
+            })
+            .to_vec();
+        let (_, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations);
+
+        let (val_mmcs, _) = get_mmcs();
+        let points: Vec<Challenge> = vec![
+            Val::GENERATOR.into(),
+            (Val::GENERATOR * Val::two_adic_generator(6).exp_u64(5)).into(),
+        ];
+        let (_, _, reduced_codewords, _) = open_and_reduce_with_degree_bounds(
+            &val_mmcs,
+            1,
+            &[(&data, vec![points; 2])],
+            &[vec![21, 8]],
+            0,
+            &mut challenger,
+        );
+        // Each reduced codeword interpolates to a polynomial of degree less than the trace size.
+        for mut codeword in reduced_codewords {
+            reverse_slice_index_bits(&mut codeword);
+            let trace_size = codeword.len() >> 1;
+            let coeffs = Radix2Dit::default().idft(codeword);
+            assert!(coeffs[trace_size..].iter().all(|c| c.is_zero()));
+        }
+    }
+
+    #[test]
+    fn commit_coeffs_matches_commit() {
+        let (pcs, _) = get_pcs(2, 1, false, 0);
+        let mut rng = seeded_rng();
+        let coeffs = [6, 3].map(|log_n| {
+            (
+                <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_n),
+                RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 5),
+            )
+        });
+        let evaluations = coeffs.clone().map(|(domain, coeffs)| {
+            (
+                domain,
+                Dft::default().dft_batch(coeffs).to_row_major_matrix(),
+            )
+        });
+        let (commit, _) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations.to_vec());
+        let (coeffs_commit, _) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit_coeffs(&pcs, coeffs.to_vec());
+        assert_eq!(commit, coeffs_commit);
+    }
+}
+
+mod babybear_mmap_fri_pcs {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use p3_merkle_tree::MmapMerkleTreeMmcs;
+
+    use super::*;
+
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+
+    type Perm = Poseidon2BabyBear<16>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+
+    type InnerMmcs =
+        MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+    type ValMmcs =
+        MmapMerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, InnerMmcs>;
+
+    type Dft = Radix2DitParallel<Val>;
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+    /// Returns a scratch directory specific to this process and `name`.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("p3-fri-mmap-{}-{name}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn get_pcs(scratch_dir: PathBuf) -> (MyPcs, Challenger) {
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm.clone());
+        let inner_mmcs = InnerMmcs::new(hash, compress);
+        let val_mmcs = ValMmcs::new(inner_mmcs.clone(), scratch_dir).unwrap();
+
+        let fri_config = FriConfig {
+            log_blowup: 1,
+            log_final_poly_len: 0,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            commit_proof_of_work_bits: 0,
+            log_folding_arity: 1,
+            use_multiproofs: false,
+            mmcs: ChallengeMmcs::new(inner_mmcs),
+        };
+
+        let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+        (pcs, Challenger::new(perm))
+    }
+
+    mod blowup_1 {
+        make_tests_for_pcs!(super::get_pcs(super::scratch_dir("shared")));
+    }
+
+    #[test]
+    fn ldes_live_in_scratch_dir() {
+        let dir = scratch_dir("ldes");
+        let (pcs, _) = get_pcs(dir.clone());
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 16);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 16, 3);
+
+        let (_, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+        // The LDE of 32 rows, plus one file per digest layer from 32 leaf digests to the root.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 7);
+        drop(data);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir(dir).unwrap();
+    }
+}
+
+mod babybear_hiding_fri_pcs {
+    use p3_dft::Radix2Bowers;
+    use p3_fri::HidingFriPcs;
+    use p3_merkle_tree::MerkleTreeHidingMmcs;
+
+    use super::*;
+
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+
+    type Perm = Poseidon2BabyBear<16>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+
+    type ValMmcs = MerkleTreeHidingMmcs<
+        <Val as Field>::Packing,
+        <Val as Field>::Packing,
+        MyHash,
+        MyCompress,
+        ChaCha20Rng,
+        8,
+        4,
+        SeededRandomness,
+    >;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+    type Dft = Radix2DitParallel<Val>;
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    type MyPcs = HidingFriPcs<Val, Dft, ValMmcs, ChallengeMmcs, ChaCha20Rng, SeededRandomness>;
+
+    fn assert_sync<T: Sync>() {}
+
+    fn get_pcs(seed: u8) -> (MyPcs, Challenger) {
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm.clone());
+
+        let val_mmcs = ValMmcs::new_deterministic(hash, compress, [seed; 32]);
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+        let fri_config = FriConfig {
+            log_blowup: 2,
+            log_final_poly_len: 0,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            commit_proof_of_work_bits: 0,
+            log_folding_arity: 1,
+            use_multiproofs: false,
+            mmcs: challenge_mmcs,
+        };
+
+        let pcs = MyPcs::new_deterministic(Dft::default(), val_mmcs, fri_config, 4, [!seed; 32]);
+        (pcs, Challenger::new(perm))
+    }
+
+    mod deterministic {
+        make_tests_for_pcs!(super::get_pcs(1));
+    }
+
+    #[test]
+    fn proofs_are_reproducible() {
+        let prove = |seed| {
+            let (pcs, mut challenger) = get_pcs(seed);
+            let mut rng = seeded_rng();
+            let evaluations = [6, 4]
+                .map(|log_degree| {
+                    let degree = 1 << log_degree;
+                    (
+                        <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                            &pcs, degree,
+                        ),
+                        RowMajorMatrix::<Val>::rand(&mut rng, degree, 3),
+                    )
+                })
+                .to_vec();
+            let (commit, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations);
+            challenger.observe(commit);
+            let zeta: Challenge = challenger.sample_algebra_element();
+            let (_, proof) = pcs.open(vec![(&data, vec![vec![zeta]; 2])], &mut challenger);
+            postcard::to_allocvec(&proof).unwrap()
+        };
+        assert_eq!(prove(1), prove(1));
+        assert_ne!(prove(1), prove(2));
+    }
+
+    #[test]
+    fn deterministic_pcs_is_sync() {
+        // `Radix2DitParallel` caches twiddles in a `RefCell`, so use a DFT which is `Sync`.
+        assert_sync::<
+            HidingFriPcs<Val, Radix2Bowers, ValMmcs, ChallengeMmcs, ChaCha20Rng, SeededRandomness>,
+        >();
     }
 }
 
@@ -200,7 +672,7 @@ mod m31_fri_pcs {
     use std::marker::PhantomData;
 
     use p3_challenger::{HashChallenger, SerializingChallenger32};
-    use p3_circle::CirclePcs;
+    use p3_circle::{CircleEvaluations, CirclePcs};
     use p3_keccak::Keccak256Hash;
     use p3_mersenne_31::Mersenne31;
     use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
@@ -223,7 +695,13 @@ mod m31_fri_pcs {
 
     type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
 
-    fn get_pcs(log_blowup: usize) -> (Pcs, Challenger) {
+    fn get_pcs(
+        log_blowup: usize,
+        log_folding_arity: usize,
+        log_final_poly_len: usize,
+        use_multiproofs: bool,
+        commit_proof_of_work_bits: usize,
+    ) -> (Pcs, Challenger) {
         let byte_hash = ByteHash {};
         let field_hash = FieldHash::new(byte_hash);
         let compress = MyCompress::new(byte_hash);
@@ -231,9 +709,12 @@ mod m31_fri_pcs {
         let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
         let fri_config = FriConfig {
             log_blowup,
-            log_final_poly_len: 0,
+            log_final_poly_len,
             num_queries: 10,
             proof_of_work_bits: 8,
+            commit_proof_of_work_bits,
+            log_folding_arity,
+            use_multiproofs,
             mmcs: challenge_mmcs,
         };
         let pcs = Pcs {
@@ -245,9 +726,113 @@ mod m31_fri_pcs {
     }
 
     mod blowup_1 {
-        make_tests_for_pcs!(super::get_pcs(1));
+        make_tests_for_pcs!(super::get_pcs(1, 1, 0, false, 0));
     }
     mod blowup_2 {
-        make_tests_for_pcs!(super::get_pcs(2));
+        make_tests_for_pcs!(super::get_pcs(2, 1, 0, false, 0));
+    }
+    mod arity_4 {
+        make_tests_for_pcs!(super::get_pcs(1, 2, 0, false, 0));
+    }
+    mod arity_8 {
+        make_tests_for_pcs!(super::get_pcs(2, 3, 0, false, 0));
+    }
+    mod final_poly_len_2 {
+        make_tests_for_pcs!(super::get_pcs(1, 1, 1, false, 0));
+    }
+    mod final_poly_len_2_arity_4 {
+        make_tests_for_pcs!(super::get_pcs(2, 2, 1, false, 0));
+    }
+    mod multiproofs {
+        make_tests_for_pcs!(super::get_pcs(1, 1, 0, true, 0));
+    }
+    mod multiproofs_final_poly_len_2_arity_4 {
+        make_tests_for_pcs!(super::get_pcs(2, 2, 1, true, 0));
+    }
+    mod commit_pow {
+        make_tests_for_pcs!(super::get_pcs(1, 2, 1, false, 4));
+    }
+
+    #[test]
+    fn commit_coeffs_matches_commit() {
+        let (pcs, _) = get_pcs(1, 1, 0, false, 0);
+        let mut rng = seeded_rng();
+        let coeffs = [6, 3].map(|log_n| {
+            (
+                <Pcs as p3_commit::Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                    &pcs,
+                    1 << log_n,
+                ),
+                RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 5),
+            )
+        });
+        let evaluations = coeffs.clone().map(|(domain, coeffs)| {
+            (
+                domain,
+                CircleEvaluations::evaluate(domain, coeffs)
+                    .to_natural_order()
+                    .to_row_major_matrix(),
+            )
+        });
+        let (commit, _) =
+            <Pcs as p3_commit::Pcs<Challenge, Challenger>>::commit(&pcs, evaluations.to_vec());
+        let (coeffs_commit, _) =
+            <Pcs as p3_commit::Pcs<Challenge, Challenger>>::commit_coeffs(&pcs, coeffs.to_vec());
+        assert_eq!(commit, coeffs_commit);
+    }
+}
+
+mod m31_hiding_fri_pcs {
+    use p3_challenger::{HashChallenger, SerializingChallenger32};
+    use p3_circle::HidingCirclePcs;
+    use p3_keccak::Keccak256Hash;
+    use p3_merkle_tree::MerkleTreeHidingMmcs;
+    use p3_mersenne_31::Mersenne31;
+    use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+
+    use super::*;
+
+    type Val = Mersenne31;
+    type Challenge = BinomialExtensionField<Mersenne31, 3>;
+
+    type ByteHash = Keccak256Hash;
+    type FieldHash = SerializingHasher32<ByteHash>;
+
+    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+
+    type ValMmcs =
+        MerkleTreeHidingMmcs<Val, u8, FieldHash, MyCompress, ChaCha20Rng, 32, 4, SeededRandomness>;
+
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+
+    type Pcs = HidingCirclePcs<Val, ValMmcs, ChallengeMmcs, ChaCha20Rng, SeededRandomness>;
+
+    fn get_pcs(log_blowup: usize, log_final_poly_len: usize) -> (Pcs, Challenger) {
+        let byte_hash = ByteHash {};
+        let field_hash = FieldHash::new(byte_hash);
+        let compress = MyCompress::new(byte_hash);
+        let val_mmcs = ValMmcs::new_deterministic(field_hash, compress, [1; 32]);
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+        let fri_config = FriConfig {
+            log_blowup,
+            log_final_poly_len,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            commit_proof_of_work_bits: 0,
+            log_folding_arity: 1,
+            use_multiproofs: false,
+            mmcs: challenge_mmcs,
+        };
+        let pcs = Pcs::new_deterministic(val_mmcs, fri_config, 4, [2; 32]);
+        (pcs, Challenger::from_hasher(vec![], byte_hash))
+    }
+
+    mod blowup_1 {
+        make_tests_for_pcs!(super::get_pcs(1, 0));
+    }
+    mod blowup_2_final_poly_len_2 {
+        make_tests_for_pcs!(super::get_pcs(2, 1));
     }
 }
@@ -11,9 +11,9 @@ use num_bigint::BigUint;
 use p3_field::exponentiation::exp_10540996611094048183;
 use p3_field::integers::QuotientMap;
 use p3_field::{
-    Field, InjectiveMonomial, Packable, PermutationMonomial, PrimeCharacteristicRing, PrimeField,
-    PrimeField64, TwoAdicField, halve_u64, quotient_map_large_iint, quotient_map_large_uint,
-    quotient_map_small_int,
+    Field, InjectiveMonomial, Packable, PermutationMonomial, Pod, PrimeCharacteristicRing,
+    PrimeField, PrimeField64, TwoAdicField, halve_u64, quotient_map_large_iint,
+    quotient_map_large_uint, quotient_map_small_int,
 };
 use p3_util::{assume, branch_hint};
 use rand::Rng;
@@ -106,6 +106,9 @@ impl Eq for Goldilocks {}
 
 impl Packable for Goldilocks {}
 
+// SAFETY: `Goldilocks` is a repr(transparent) wrapper around a plain integer.
+unsafe impl Pod for Goldilocks {}
+
 impl Hash for Goldilocks {
     fn hash<H: Hasher>(&self, state: &mut H) {
         state.write_u64(self.as_canonical_u64());
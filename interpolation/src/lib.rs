@@ -17,7 +17,7 @@ use p3_util::log2_strict_usize;
 /// Given evaluations of a batch of polynomials over the canonical power-of-two subgroup, evaluate
 /// the polynomials at `point`.
 ///
-/// This assumes the point is not in the subgroup, otherwise the behavior is undefined.
+/// If the point lies in the subgroup, this returns the evaluations stored for it.
 pub fn interpolate_subgroup<F, EF, Mat>(subgroup_evals: &Mat, point: EF) -> Vec<EF>
 where
     F: TwoAdicField,
@@ -30,8 +30,9 @@ where
 /// Given evaluations of a batch of polynomials over the given coset of the canonical power-of-two
 /// subgroup, evaluate the polynomials at `point`.
 ///
-/// This assumes the point is not in the coset, otherwise the behavior is undefined.
-/// If available, reuse denominator diffs that is `1 / (x_i-z)` to avoid batch inversion.
+/// If the point lies in the coset, this returns the evaluations stored for it, and `diff_invs` is
+/// not used. Otherwise, if available, reuse denominator diffs that is `1 / (x_i-z)` to avoid batch
+/// inversion.
 pub fn interpolate_coset<F, EF, Mat>(
     coset_evals: &Mat,
     shift: F,
@@ -48,6 +49,18 @@ where
     let height = coset_evals.height();
     let log_height = log2_strict_usize(height);
 
+    let vanishing_polynomial =
+        two_adic_coset_vanishing_polynomial::<EF>(log_height, shift.into(), point);
+    if vanishing_polynomial.is_zero() {
+        // The barycentric formula would divide by zero, but the evaluation is simply stored.
+        let row = F::two_adic_generator(log_height)
+            .shifted_powers(shift)
+            .take(height)
+            .position(|x| point == x.into())
+            .expect("a root of the vanishing polynomial lies in the coset");
+        return coset_evals.row(row).map(EF::from).collect();
+    }
+
     let g = F::two_adic_generator(log_height).powers().take(height);
     let col_scale: Vec<_> = if let Some(diff_invs) = diff_invs {
         g.zip(diff_invs)
@@ -68,9 +81,6 @@ where
     };
     let sum = coset_evals.columnwise_dot_product(&col_scale);
 
-    let vanishing_polynomial =
-        two_adic_coset_vanishing_polynomial::<EF>(log_height, shift.into(), point);
-
     // In principle, height could be bigger than the characteristic of F.
     let denominator = shift
         .exp_u64(height as u64 - 1)
@@ -84,7 +94,7 @@ mod tests {
     use alloc::vec::Vec;
 
     use p3_baby_bear::BabyBear;
-    use p3_field::{Field, PrimeCharacteristicRing, batch_multiplicative_inverse};
+    use p3_field::{Field, PrimeCharacteristicRing, TwoAdicField, batch_multiplicative_inverse};
     use p3_matrix::dense::RowMajorMatrix;
     use p3_util::log2_strict_usize;
 
@@ -118,7 +128,6 @@ mod tests {
         let result = interpolate_coset(&evals_mat, shift, point, None);
         assert_eq!(result, vec![F::from_u16(10203)]);
 
-        use p3_field::TwoAdicField;
         let n = evals.len();
         let k = log2_strict_usize(n);
 
@@ -132,4 +141,39 @@ mod tests {
         let result = interpolate_coset(&evals_mat, shift, point, Some(&denom));
         assert_eq!(result, vec![F::from_u16(10203)]);
     }
+
+    #[test]
+    fn test_interpolate_coset_at_coset_point() {
+        // x^2 + 2 x + 3
+        type F = BabyBear;
+        let shift = F::GENERATOR;
+        let evals = [
+            1026, 129027310, 457985035, 994890337, 902, 1988942953, 1555278970, 913671254,
+        ]
+        .map(F::from_u32);
+        let evals_mat = RowMajorMatrix::new(evals.to_vec(), 1);
+        let g = F::two_adic_generator(3);
+        for (i, &eval) in evals.iter().enumerate() {
+            let point = shift * g.exp_u64(i as u64);
+            assert_eq!(
+                interpolate_coset(&evals_mat, shift, point, None),
+                vec![eval]
+            );
+            // The denominators are not needed, and may well be missing one.
+            assert_eq!(
+                interpolate_coset(&evals_mat, shift, point, Some(&[])),
+                vec![eval]
+            );
+        }
+
+        let subgroup_evals = [
+            6, 886605102, 1443543107, 708307799, 2, 556938009, 569722818, 1874680944,
+        ]
+        .map(F::from_u32);
+        let subgroup_mat = RowMajorMatrix::new(subgroup_evals.to_vec(), 1);
+        assert_eq!(
+            interpolate_subgroup(&subgroup_mat, F::NEG_ONE),
+            vec![F::from_u16(2)]
+        );
+    }
 }
@@ -23,6 +23,8 @@ pub mod bitrev;
 pub mod dense;
 pub mod extension;
 pub mod horizontally_truncated;
+#[cfg(feature = "mmap")]
+pub mod mmap;
 pub mod mul;
 pub mod row_index_mapped;
 pub mod sparse;
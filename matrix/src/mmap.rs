@@ -0,0 +1,248 @@
+//! Dense matrices backed by memory-mapped files, for data too large to comfortably keep in RAM.
+
+extern crate std;
+
+use alloc::format;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::{mem, slice};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use memmap2::Mmap;
+use p3_field::Pod;
+
+use crate::Matrix;
+use crate::dense::{DenseMatrix, DenseStorage};
+
+/// A dense matrix whose values live in a memory-mapped file.
+pub type MmapMatrix<T> = DenseMatrix<T, MmapStorage<T>>;
+
+/// Used to give each temporary file created by this process a distinct name.
+static NEXT_TEMP_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Read-only storage for a slice of `T` values, backed by a memory-mapped file.
+///
+/// The operating system pages values in on access and may evict them under memory pressure, so
+/// resident memory stays bounded even when the data is much larger than RAM.
+pub struct MmapStorage<T> {
+    mmap: Mmap,
+    len: usize,
+    /// If set, the backing file is removed when this storage is dropped.
+    temp_path: Option<PathBuf>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Pod> MmapStorage<T> {
+    /// Write `values` to a new file at `path`, replacing any existing file, and map it.
+    pub fn create(path: impl AsRef<Path>, values: impl IntoIterator<Item = T>) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Self::write_and_map(file, values, None)
+    }
+
+    /// Write `values` to a new, uniquely named file in `dir`, and map it.
+    ///
+    /// The file is removed again when the returned storage is dropped.
+    pub fn create_temp(
+        dir: impl AsRef<Path>,
+        values: impl IntoIterator<Item = T>,
+    ) -> io::Result<Self> {
+        let id = NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed);
+        let path = dir.as_ref().join(format!("p3-{}-{id}.bin", process::id()));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Self::write_and_map(file, values, Some(path))
+    }
+
+    /// Map an existing file, such as one written by [`MmapStorage::create`].
+    ///
+    /// # Safety
+    /// The file must consist of the bytes of valid values of `T`, and must not be modified while
+    /// the returned storage is alive.
+    pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        // SAFETY: upheld by the caller.
+        unsafe { Self::map(&File::open(path)?, None) }
+    }
+
+    /// The path of the backing file, if it is removed when this storage is dropped.
+    pub fn temp_path(&self) -> Option<&Path> {
+        self.temp_path.as_deref()
+    }
+
+    fn write_and_map(
+        file: File,
+        values: impl IntoIterator<Item = T>,
+        temp_path: Option<PathBuf>,
+    ) -> io::Result<Self> {
+        let mut writer = BufWriter::new(file);
+        for value in values {
+            // SAFETY: `T: Pod`, so every byte of `value` is initialized.
+            let bytes = unsafe {
+                slice::from_raw_parts((&raw const value).cast::<u8>(), mem::size_of::<T>())
+            };
+            writer.write_all(bytes)?;
+        }
+        let file = writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?;
+        // SAFETY: we just wrote the file from valid values of `T`.
+        unsafe { Self::map(&file, temp_path) }
+    }
+
+    /// # Safety
+    /// The file must hold valid values of `T`, and must not be modified while mapped.
+    unsafe fn map(file: &File, temp_path: Option<PathBuf>) -> io::Result<Self> {
+        assert_ne!(mem::size_of::<T>(), 0, "zero-sized types are not supported");
+        // SAFETY: the caller guarantees the file is not modified while mapped.
+        let mmap = unsafe { Mmap::map(file)? };
+        if mmap.len() % mem::size_of::<T>() != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "file length is not a multiple of the element size",
+            ));
+        }
+        // Mappings are page-aligned, which is sufficient for any field or digest type.
+        assert!(mmap.as_ptr().cast::<T>().is_aligned() || mmap.is_empty());
+        Ok(Self {
+            len: mmap.len() / mem::size_of::<T>(),
+            mmap,
+            temp_path,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<T: Pod> Borrow<[T]> for MmapStorage<T> {
+    fn borrow(&self) -> &[T] {
+        if self.len == 0 {
+            return &[];
+        }
+        // SAFETY: the mapping is aligned and holds `len` valid values of `T`, and is never
+        // written to while mapped.
+        unsafe { slice::from_raw_parts(self.mmap.as_ptr().cast::<T>(), self.len) }
+    }
+}
+
+impl<T: Pod> DenseStorage<T> for MmapStorage<T> {
+    fn to_vec(self) -> Vec<T> {
+        <Self as Borrow<[T]>>::borrow(&self).to_vec()
+    }
+}
+
+impl<T> Debug for MmapStorage<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MmapStorage")
+            .field("len", &self.len)
+            .field("temp_path", &self.temp_path)
+            .finish()
+    }
+}
+
+impl<T> Drop for MmapStorage<T> {
+    fn drop(&mut self) {
+        if let Some(path) = &self.temp_path {
+            // Unix keeps the file alive until the mapping below is dropped. Cleanup is best
+            // effort, so a failure here only leaves a stray file in the scratch directory.
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+impl<T: Pod> MmapMatrix<T> {
+    /// Write the rows of `matrix` to a new file at `path`, replacing any existing file, and map it.
+    pub fn create<M: Matrix<T>>(path: impl AsRef<Path>, matrix: &M) -> io::Result<Self> {
+        let storage = MmapStorage::create(path, matrix_values(matrix))?;
+        Ok(Self::new(storage, matrix.width()))
+    }
+
+    /// Write the rows of `matrix` to a new, uniquely named file in `dir`, and map it.
+    ///
+    /// The file is removed again when the returned matrix is dropped.
+    pub fn create_temp<M: Matrix<T>>(dir: impl AsRef<Path>, matrix: &M) -> io::Result<Self> {
+        let storage = MmapStorage::create_temp(dir, matrix_values(matrix))?;
+        Ok(Self::new(storage, matrix.width()))
+    }
+}
+
+fn matrix_values<T: Pod, M: Matrix<T>>(matrix: &M) -> impl Iterator<Item = T> + '_ {
+    (0..matrix.height()).flat_map(|r| matrix.row_slice(r).to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::*;
+    use crate::dense::RowMajorMatrix;
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join("p3-matrix-mmap-tests");
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn round_trip() {
+        type EF = BinomialExtensionField<BabyBear, 4>;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let matrix = RowMajorMatrix::<EF>::rand(&mut rng, 37, 5);
+
+        let mmap = MmapMatrix::create_temp(scratch_dir(), &matrix).unwrap();
+        assert_eq!(mmap.dimensions(), matrix.dimensions());
+        let values: &[EF] = mmap.values.borrow();
+        assert_eq!(values, &matrix.values[..]);
+        assert_eq!(mmap.to_row_major_matrix(), matrix);
+    }
+
+    #[test]
+    fn create_and_open() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 10, 3);
+        let path = scratch_dir().join(format!("create-and-open-{}.bin", process::id()));
+
+        let created = MmapMatrix::create(&path, &matrix).unwrap();
+        assert_eq!(created.values.temp_path(), None);
+        drop(created);
+        // SAFETY: the file was just written from `BabyBear` values.
+        let opened = unsafe { MmapStorage::<BabyBear>::open(&path) }.unwrap();
+        assert_eq!(MmapMatrix::new(opened, 3).to_row_major_matrix(), matrix);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn temp_file_removed_on_drop() {
+        let storage = MmapStorage::create_temp(scratch_dir(), [1u64, 2, 3]).unwrap();
+        let path = storage.temp_path().unwrap().to_path_buf();
+        assert!(path.exists());
+        let values: &[u64] = storage.borrow();
+        assert_eq!(values, [1, 2, 3]);
+        drop(storage);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn empty() {
+        let matrix = RowMajorMatrix::<BabyBear>::new(vec![], 4);
+        let mmap = MmapMatrix::create_temp(scratch_dir(), &matrix).unwrap();
+        assert_eq!(mmap.height(), 0);
+        assert_eq!(mmap.width(), 4);
+    }
+}
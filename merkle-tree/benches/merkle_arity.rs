@@ -0,0 +1,130 @@
+use core::mem::size_of;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_commit::Mmcs;
+use p3_field::{Field, PackedField, PackedValue};
+use p3_keccak::Keccak256Hash;
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{
+    CompressionFunctionFromHasher, CryptographicHasher, PaddingFreeSponge,
+    PseudoCompressionFunction, SerializingHasher32, TruncatedPermutation,
+};
+use rand::distr::{Distribution, StandardUniform};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng, rng};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+const ROWS: usize = 1 << 16;
+const COLS: usize = 64;
+const NUM_QUERIES: usize = 100;
+
+fn bench_merkle_arity(criterion: &mut Criterion) {
+    bench_bb_poseidon2(criterion);
+    bench_bb_keccak(criterion);
+}
+
+fn bench_bb_poseidon2(criterion: &mut Criterion) {
+    type F = BabyBear;
+    type P = <F as Field>::Packing;
+
+    type Perm = Poseidon2BabyBear<16>;
+    let perm = Perm::new_from_rng_128(&mut rng());
+
+    type H = PaddingFreeSponge<Perm, 16, 8, 8>;
+    let h = H::new(perm.clone());
+
+    // The usual binary compression, which is a single permutation.
+    type C2 = TruncatedPermutation<Perm, 2, 8, 16>;
+    bench_arity::<P, P, H, C2, 8, 2>(criterion, "poseidon2 truncated", h.clone(), C2::new(perm));
+
+    // Wider compressions absorb all of their inputs into a sponge.
+    type SpongeC<const ARITY: usize> = CompressionFunctionFromHasher<H, ARITY, 8>;
+    bench_arity::<P, P, H, SpongeC<2>, 8, 2>(
+        criterion,
+        "poseidon2 sponge",
+        h.clone(),
+        SpongeC::new(h.clone()),
+    );
+    bench_arity::<P, P, H, SpongeC<4>, 8, 4>(
+        criterion,
+        "poseidon2 sponge",
+        h.clone(),
+        SpongeC::new(h.clone()),
+    );
+    bench_arity::<P, P, H, SpongeC<8>, 8, 8>(
+        criterion,
+        "poseidon2 sponge",
+        h.clone(),
+        SpongeC::new(h),
+    );
+}
+
+fn bench_bb_keccak(criterion: &mut Criterion) {
+    type F = BabyBear;
+
+    type H = SerializingHasher32<Keccak256Hash>;
+    let k = Keccak256Hash {};
+    let h = H::new(k);
+
+    type C<const ARITY: usize> = CompressionFunctionFromHasher<Keccak256Hash, ARITY, 32>;
+    bench_arity::<F, u8, H, C<2>, 32, 2>(criterion, "keccak", h, C::new(k));
+    bench_arity::<F, u8, H, C<4>, 32, 4>(criterion, "keccak", h, C::new(k));
+    bench_arity::<F, u8, H, C<8>, 32, 8>(criterion, "keccak", h, C::new(k));
+}
+
+/// Benchmarks committing to a matrix in a tree of the given arity, and prints the size of a
+/// single opening proof and of a multiproof for `NUM_QUERIES` random rows.
+fn bench_arity<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>(
+    criterion: &mut Criterion,
+    compression: &str,
+    h: H,
+    c: C,
+) where
+    P: PackedField,
+    PW: PackedValue,
+    PW::Value: Eq,
+    H: CryptographicHasher<P::Scalar, [PW::Value; DIGEST_ELEMS]>
+        + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
+        + Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>
+        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>
+        + Sync,
+    [PW::Value; DIGEST_ELEMS]: Serialize + DeserializeOwned,
+    StandardUniform: Distribution<P::Scalar>,
+{
+    let matrix = RowMajorMatrix::<P::Scalar>::rand(&mut rng(), ROWS, COLS);
+    let dims = matrix.dimensions();
+    let leaves = vec![matrix];
+
+    let mmcs = MerkleTreeMmcs::<P, PW, H, C, DIGEST_ELEMS, ARITY>::new(h, c);
+
+    let (_, prover_data) = mmcs.commit(leaves.clone());
+    let digest_size = size_of::<[PW::Value; DIGEST_ELEMS]>();
+    let (_, proof) = mmcs.open_batch(ROWS / 3, &prover_data);
+    let mut rng = SmallRng::seed_from_u64(1);
+    let indices = (0..NUM_QUERIES)
+        .map(|_| rng.random_range(0..ROWS))
+        .collect::<Vec<_>>();
+    let (_, multi_proofs) = mmcs.open_multi(&indices, &prover_data);
+    println!(
+        "{compression}, arity {ARITY}: opening proof {} bytes, multiproof for {NUM_QUERIES} queries {} bytes",
+        proof.len() * digest_size,
+        multi_proofs.iter().map(Vec::len).sum::<usize>() * digest_size,
+    );
+
+    let name = format!("MerkleTreeMmcs::commit ({compression})");
+    let mut group = criterion.benchmark_group(name);
+    group.sample_size(10);
+    group.bench_with_input(
+        BenchmarkId::new(format!("arity {ARITY}"), dims),
+        &leaves,
+        |b, input| b.iter(|| mmcs.commit(input.clone())),
+    );
+}
+
+criterion_group!(benches, bench_merkle_arity);
+criterion_main!(benches);
@@ -1,15 +1,16 @@
 use alloc::vec::Vec;
-use core::cell::RefCell;
+use core::marker::PhantomData;
 
 use itertools::Itertools;
-use p3_commit::Mmcs;
+use p3_commit::{CommitRandomness, Mmcs, RngRandomness, SeededRandomness, check_opening_count};
 use p3_field::PackedValue;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::stack::HorizontalPair;
 use p3_matrix::{Dimensions, Matrix};
-use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
+use p3_symmetric::{CryptographicHasher, MerkleCap, PseudoCompressionFunction};
 use rand::Rng;
 use rand::distr::{Distribution, StandardUniform};
+use rand_chacha::ChaCha20Rng;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
@@ -26,7 +27,9 @@ use crate::{MerkleTree, MerkleTreeError, MerkleTreeMmcs};
 ///
 /// `R` should be an appropriately seeded cryptographically secure pseudorandom number generator
 /// (CSPRNG). Something like `ThreadRng` may work, although it relies on the operating system to
-/// provide sufficient entropy.
+/// provide sufficient entropy. With `new_deterministic`, the salts are instead drawn from a
+/// `ChaCha20Rng` derived from a secret seed for each commitment, so that commitments can be
+/// reproduced exactly.
 ///
 /// Generics:
 /// - `P`: a leaf value
@@ -34,27 +37,59 @@ use crate::{MerkleTree, MerkleTreeError, MerkleTreeMmcs};
 /// - `H`: the leaf hasher
 /// - `C`: the digest compression function
 /// - `R`: a random number generator for blinding leaves
+/// - `S`: the source of the RNGs, which shares one RNG between all commitments by default
 #[derive(Clone, Debug)]
-pub struct MerkleTreeHidingMmcs<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize>
-{
+pub struct MerkleTreeHidingMmcs<
+    P,
+    PW,
+    H,
+    C,
+    R,
+    const DIGEST_ELEMS: usize,
+    const SALT_ELEMS: usize,
+    S = RngRandomness<R>,
+> {
     inner: MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS>,
-    rng: RefCell<R>,
+    randomness: S,
+    _phantom: PhantomData<R>,
 }
 
 impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize>
     MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS>
 {
-    pub fn new(hash: H, compress: C, rng: R) -> Self {
+    pub const fn new(hash: H, compress: C, rng: R) -> Self {
+        Self::new_with_randomness(hash, compress, RngRandomness::new(rng))
+    }
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize>
+    MerkleTreeHidingMmcs<P, PW, H, C, ChaCha20Rng, DIGEST_ELEMS, SALT_ELEMS, SeededRandomness>
+{
+    /// Like `new`, but derives the salts of each commitment from `seed` and the index of the
+    /// commitment. See `SeededRandomness`.
+    ///
+    /// The seed must be secret, and must not be reused for another proof: proofs made with the
+    /// same seed share their salts, which breaks hiding.
+    pub fn new_deterministic(hash: H, compress: C, seed: [u8; 32]) -> Self {
+        Self::new_with_randomness(hash, compress, SeededRandomness::new(seed))
+    }
+}
+
+impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize, S>
+    MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS, S>
+{
+    pub const fn new_with_randomness(hash: H, compress: C, randomness: S) -> Self {
         let inner = MerkleTreeMmcs::new(hash, compress);
         Self {
             inner,
-            rng: rng.into(),
+            randomness,
+            _phantom: PhantomData,
         }
     }
 }
 
-impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize> Mmcs<P::Value>
-    for MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS>
+impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize, S> Mmcs<P::Value>
+    for MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS, S>
 where
     P: PackedValue,
     P::Value: Serialize + DeserializeOwned,
@@ -66,29 +101,36 @@ where
         + PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>
         + Sync,
     R: Rng + Clone,
+    S: CommitRandomness<Rng = R> + Clone,
     PW::Value: Eq,
     [PW::Value; DIGEST_ELEMS]: Serialize + for<'de> Deserialize<'de>,
     StandardUniform: Distribution<P::Value>,
 {
     type ProverData<M> =
         MerkleTree<P::Value, PW::Value, HorizontalPair<M, RowMajorMatrix<P::Value>>, DIGEST_ELEMS>;
-    type Commitment = Hash<P::Value, PW::Value, DIGEST_ELEMS>;
+    type Commitment = MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>;
     /// The first item is salts; the second is the usual Merkle proof (sibling digests).
     type Proof = (Vec<Vec<P::Value>>, Vec<[PW::Value; DIGEST_ELEMS]>);
     type Error = MerkleTreeError;
+    type MatrixStorage = Vec<P::Value>;
+
+    fn store_matrix(&self, matrix: RowMajorMatrix<P::Value>) -> RowMajorMatrix<P::Value> {
+        matrix
+    }
 
     fn commit<M: Matrix<P::Value>>(
         &self,
         inputs: Vec<M>,
     ) -> (Self::Commitment, Self::ProverData<M>) {
-        let salted_inputs = inputs
-            .into_iter()
-            .map(|mat| {
-                let salts =
-                    RowMajorMatrix::rand(&mut *self.rng.borrow_mut(), mat.height(), SALT_ELEMS);
-                HorizontalPair::new(mat, salts)
-            })
-            .collect();
+        let salted_inputs = self.randomness.with_next_rng(|rng| {
+            inputs
+                .into_iter()
+                .map(|mat| {
+                    let salts = RowMajorMatrix::rand(rng, mat.height(), SALT_ELEMS);
+                    HorizontalPair::new(mat, salts)
+                })
+                .collect()
+        });
         self.inner.commit(salted_inputs)
     }
 
@@ -98,14 +140,24 @@ where
         prover_data: &Self::ProverData<M>,
     ) -> (Vec<Vec<P::Value>>, Self::Proof) {
         let (salted_openings, siblings) = self.inner.open_batch(index, prover_data);
-        let (openings, salts): (Vec<_>, Vec<_>) = salted_openings
+        let (openings, salts) = split_salts::<_, SALT_ELEMS>(salted_openings);
+        (openings, (salts, siblings))
+    }
+
+    fn open_multi<M: Matrix<P::Value>>(
+        &self,
+        indices: &[usize],
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<Vec<P::Value>>>, Vec<Self::Proof>) {
+        let (salted_openings, siblings) = self.inner.open_multi(indices, prover_data);
+        salted_openings
             .into_iter()
-            .map(|row| {
-                let (a, b) = row.split_at(row.len() - SALT_ELEMS);
-                (a.to_vec(), b.to_vec())
+            .zip(siblings)
+            .map(|(salted_opening, siblings)| {
+                let (opening, salts) = split_salts::<_, SALT_ELEMS>(salted_opening);
+                (opening, (salts, siblings))
             })
-            .unzip();
-        (openings, (salts, siblings))
+            .unzip()
     }
 
     fn get_matrices<'a, M: Matrix<P::Value>>(
@@ -125,30 +177,109 @@ where
     ) -> Result<(), Self::Error> {
         let (salts, siblings) = proof;
 
-        let opened_salted_values = opened_values
+        check_salts::<_, SALT_ELEMS>(salts)?;
+        let opened_salted_values = join_salts(opened_values, salts);
+
+        self.inner.verify_batch(
+            commit,
+            &salted_dimensions::<SALT_ELEMS>(dimensions),
+            index,
+            &opened_salted_values,
+            siblings,
+        )
+    }
+
+    fn verify_multi(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        indices: &[usize],
+        opened_values: &[Vec<Vec<P::Value>>],
+        proofs: &[Self::Proof],
+    ) -> Result<(), Self::Error> {
+        check_opening_count(indices, opened_values, proofs)?;
+        for (salts, _) in proofs {
+            check_salts::<_, SALT_ELEMS>(salts)?;
+        }
+        let (opened_salted_values, siblings): (Vec<_>, Vec<_>) = opened_values
             .iter()
-            .zip(salts.iter())
-            .map(|(opened, salt)| opened.iter().chain(salt.iter()).copied().collect_vec())
-            .collect_vec();
+            .zip(proofs)
+            .map(|(opened, (salts, siblings))| (join_salts(opened, salts), siblings.clone()))
+            .unzip();
 
-        self.inner
-            .verify_batch(commit, dimensions, index, &opened_salted_values, siblings)
+        self.inner.verify_multi(
+            commit,
+            &salted_dimensions::<SALT_ELEMS>(dimensions),
+            indices,
+            &opened_salted_values,
+            &siblings,
+        )
     }
 }
 
+/// Splits each salted row into the row itself and its salt.
+fn split_salts<T: Clone, const SALT_ELEMS: usize>(
+    salted_rows: Vec<Vec<T>>,
+) -> (Vec<Vec<T>>, Vec<Vec<T>>) {
+    salted_rows
+        .into_iter()
+        .map(|row| {
+            let (a, b) = row.split_at(row.len() - SALT_ELEMS);
+            (a.to_vec(), b.to_vec())
+        })
+        .unzip()
+}
+
+/// Checks that each salt has `SALT_ELEMS` elements. Otherwise values could be moved between a row
+/// and its salt without changing the committed leaf.
+fn check_salts<T, const SALT_ELEMS: usize>(salts: &[Vec<T>]) -> Result<(), MerkleTreeError> {
+    match salts
+        .iter()
+        .enumerate()
+        .find(|(_, salt)| salt.len() != SALT_ELEMS)
+    {
+        Some((matrix, salt)) => Err(MerkleTreeError::WrongWidth {
+            matrix,
+            expected: SALT_ELEMS,
+            actual: salt.len(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Returns the dimensions of the committed matrices, which have their salts appended.
+fn salted_dimensions<const SALT_ELEMS: usize>(dimensions: &[Dimensions]) -> Vec<Dimensions> {
+    dimensions
+        .iter()
+        .map(|dims| Dimensions {
+            width: dims.width + SALT_ELEMS,
+            height: dims.height,
+        })
+        .collect()
+}
+
+/// Appends its salt to each row.
+fn join_salts<T: Copy>(rows: &[Vec<T>], salts: &[Vec<T>]) -> Vec<Vec<T>> {
+    rows.iter()
+        .zip(salts.iter())
+        .map(|(opened, salt)| opened.iter().chain(salt.iter()).copied().collect_vec())
+        .collect_vec()
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
 
     use itertools::Itertools;
     use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
-    use p3_commit::Mmcs;
+    use p3_commit::{Mmcs, SeededRandomness};
     use p3_field::{Field, PrimeCharacteristicRing};
     use p3_matrix::Matrix;
     use p3_matrix::dense::RowMajorMatrix;
     use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
     use rand::prelude::ThreadRng;
     use rand::rng;
+    use rand_chacha::ChaCha20Rng;
 
     use super::MerkleTreeHidingMmcs;
     use crate::MerkleTreeError;
@@ -168,6 +299,23 @@ mod tests {
         8,
         SALT_ELEMS,
     >;
+    type MyDeterministicMmcs = MerkleTreeHidingMmcs<
+        <F as Field>::Packing,
+        <F as Field>::Packing,
+        MyHash,
+        MyCompress,
+        ChaCha20Rng,
+        8,
+        SALT_ELEMS,
+        SeededRandomness,
+    >;
+
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn deterministic_mmcs_is_sync() {
+        assert_sync::<MyDeterministicMmcs>();
+    }
 
     #[test]
     #[should_panic]
@@ -200,4 +348,70 @@ mod tests {
         let (opened_values, proof) = mmcs.open_batch(17, &prover_data);
         mmcs.verify_batch(&commit, &dims, 17, &opened_values, &proof)
     }
+
+    #[test]
+    fn malformed_salts_rejected() {
+        let perm = Perm::new_from_rng_128(&mut rng());
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress, rng());
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng(), 16, 4),
+            RowMajorMatrix::<F>::rand(&mut rng(), 16, 2),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+        let (opened_values, (salts, siblings)) = mmcs.open_batch(5, &prover_data);
+
+        // Moving a value from a row into its salt keeps the hashed leaf the same, but is caught
+        // by the width checks.
+        let mut short_row = opened_values.clone();
+        let moved = short_row[1].pop().unwrap();
+        let mut long_salts = salts.clone();
+        long_salts[1].insert(0, moved);
+        assert!(matches!(
+            mmcs.verify_batch(
+                &commit,
+                &dims,
+                5,
+                &short_row,
+                &(long_salts, siblings.clone())
+            ),
+            Err(MerkleTreeError::WrongWidth { matrix: 1, .. })
+        ));
+
+        mmcs.verify_batch(&commit, &dims, 5, &opened_values, &(salts, siblings))
+            .expect("expected verification to succeed");
+    }
+
+    #[test]
+    fn deterministic_salts() {
+        let perm = Perm::new_from_rng_128(&mut rng());
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng(), 16, 4),
+            RowMajorMatrix::<F>::rand(&mut rng(), 8, 2),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+
+        let mmcs = MyDeterministicMmcs::new_deterministic(hash.clone(), compress.clone(), [1; 32]);
+        let (commit, prover_data) = mmcs.commit(mats.clone());
+        let (opened_values, proof) = mmcs.open_batch(5, &prover_data);
+        mmcs.verify_batch(&commit, &dims, 5, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        // The same seed reproduces the same commitments, in order.
+        let (second_commit, _) = mmcs.commit(mats.clone());
+        let replay =
+            MyDeterministicMmcs::new_deterministic(hash.clone(), compress.clone(), [1; 32]);
+        assert_eq!(replay.commit(mats.clone()).0, commit);
+        assert_eq!(replay.commit(mats.clone()).0, second_commit);
+
+        // Each commitment, and each seed, gets fresh salts.
+        assert_ne!(second_commit, commit);
+        let other_seed = MyDeterministicMmcs::new_deterministic(hash, compress, [2; 32]);
+        assert_ne!(other_seed.commit(mats).0, commit);
+    }
 }
@@ -1,11 +1,19 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "mmap")]
+extern crate std;
 
 mod hiding_mmcs;
 mod merkle_tree;
+#[cfg(feature = "mmap")]
+mod mmap_mmcs;
 mod mmcs;
+mod streaming;
 
 pub use hiding_mmcs::*;
 pub use merkle_tree::*;
+#[cfg(feature = "mmap")]
+pub use mmap_mmcs::*;
 pub use mmcs::*;
+pub use streaming::*;
@@ -1,31 +1,37 @@
 use alloc::vec;
 use alloc::vec::Vec;
 use core::array;
+use core::borrow::Borrow;
 use core::cmp::Reverse;
 use core::marker::PhantomData;
+use core::{iter, mem};
 
 use itertools::Itertools;
 use p3_field::PackedValue;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
-use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
+use p3_symmetric::{CryptographicHasher, Hash, MerkleCap, PseudoCompressionFunction};
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
-/// A binary Merkle tree for packed data. It has leaves of type `F` and digests of type
+/// A Merkle tree for packed data, binary unless built with `new_with_arity`. It has leaves of type `F` and digests of type
 /// `[W; DIGEST_ELEMS]`.
 ///
+/// Each layer of digests is held in an `L`, which is a `Vec` unless the tree was built with
+/// `new_with_layer_storage`.
+///
 /// This generally shouldn't be used directly. If you're using a Merkle tree as an MMCS,
 /// see `MerkleTreeMmcs`.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct MerkleTree<F, W, M, const DIGEST_ELEMS: usize> {
+pub struct MerkleTree<F, W, M, const DIGEST_ELEMS: usize, L = Vec<[W; DIGEST_ELEMS]>> {
     pub(crate) leaves: Vec<M>,
-    // Enable serialization for this type whenever the underlying array type supports it (len 1-32).
-    #[serde(bound(serialize = "[W; DIGEST_ELEMS]: Serialize"))]
-    // Enable deserialization for this type whenever the underlying array type supports it (len 1-32).
-    #[serde(bound(deserialize = "[W; DIGEST_ELEMS]: Deserialize<'de>"))]
-    pub(crate) digest_layers: Vec<Vec<[W; DIGEST_ELEMS]>>,
-    _phantom: PhantomData<F>,
+    // Enable serialization for this type whenever the layer storage supports it, e.g. for `Vec`s
+    // of arrays of length 1-32.
+    #[serde(bound(serialize = "L: Serialize"))]
+    // Enable deserialization for this type whenever the layer storage supports it.
+    #[serde(bound(deserialize = "L: Deserialize<'de>"))]
+    pub(crate) digest_layers: Vec<L>,
+    _phantom: PhantomData<(F, W)>,
 }
 
 impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
@@ -33,8 +39,6 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
 {
     /// Matrix heights need not be powers of two. However, if the heights of two given matrices
     /// round up to the same power of two, they must be equal.
-    #[instrument(name = "build merkle tree", level = "debug", skip_all,
-                 fields(dimensions = alloc::format!("{:?}", leaves.iter().map(|l| l.dimensions()).collect::<Vec<_>>())))]
     pub fn new<P, PW, H, C>(h: &H, c: &C, leaves: Vec<M>) -> Self
     where
         P: PackedValue<Value = F>,
@@ -45,11 +49,70 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
         C: PseudoCompressionFunction<[W; DIGEST_ELEMS], 2>
             + PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>
             + Sync,
+    {
+        Self::new_with_arity::<P, PW, H, C, 2>(h, c, leaves)
+    }
+
+    /// Like `new`, but builds a tree in which each node has `ARITY` children, which must be a
+    /// power of two.
+    ///
+    /// Each layer has `ARITY` times fewer nodes than the one below it, so a matrix can only be
+    /// injected if its height, rounded up to a power of two, is that of the tallest matrix divided
+    /// by a power of `ARITY` (rounding up to 1).
+    pub fn new_with_arity<P, PW, H, C, const ARITY: usize>(h: &H, c: &C, leaves: Vec<M>) -> Self
+    where
+        P: PackedValue<Value = F>,
+        PW: PackedValue<Value = W>,
+        H: CryptographicHasher<F, [W; DIGEST_ELEMS]>
+            + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
+            + Sync,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], ARITY>
+            + PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>
+            + Sync,
+    {
+        Self::new_with_layer_storage::<P, PW, H, C, ARITY>(h, c, leaves, |layer| layer)
+    }
+}
+
+impl<F, W, M, const DIGEST_ELEMS: usize, L> MerkleTree<F, W, M, DIGEST_ELEMS, L>
+where
+    F: Clone + Send + Sync,
+    W: Clone,
+    M: Matrix<F>,
+    L: Borrow<[[W; DIGEST_ELEMS]]>,
+{
+    /// Like `new_with_arity`, but passes each layer of digests to `store_layer` as soon as the
+    /// layer above it has been computed, and keeps the result in place of the layer.
+    ///
+    /// At most two layers are held in memory at once during construction, so this can be used
+    /// to move layers out of memory, e.g. to disk.
+    #[instrument(name = "build merkle tree", level = "debug", skip_all,
+                 fields(dimensions = alloc::format!("{:?}", leaves.iter().map(|l| l.dimensions()).collect::<Vec<_>>())))]
+    pub fn new_with_layer_storage<P, PW, H, C, const ARITY: usize>(
+        h: &H,
+        c: &C,
+        leaves: Vec<M>,
+        store_layer: impl FnMut(Vec<[W; DIGEST_ELEMS]>) -> L,
+    ) -> Self
+    where
+        P: PackedValue<Value = F>,
+        PW: PackedValue<Value = W>,
+        H: CryptographicHasher<F, [W; DIGEST_ELEMS]>
+            + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
+            + Sync,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], ARITY>
+            + PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>
+            + Sync,
     {
         assert!(!leaves.is_empty(), "No matrices given?");
 
         assert_eq!(P::WIDTH, PW::WIDTH, "Packing widths must match");
 
+        assert!(
+            ARITY >= 2 && ARITY.is_power_of_two(),
+            "arity must be a power of two"
+        );
+
         let mut leaves_largest_first = leaves
             .iter()
             .sorted_by_key(|l| Reverse(l.height()))
@@ -67,35 +130,31 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
         );
 
         let max_height = leaves_largest_first.peek().unwrap().height();
+        assert!(
+            leaves.iter().all(|m| {
+                padded_layer_heights(max_height, ARITY).contains(&m.height().next_power_of_two())
+            }),
+            "matrix heights must be the tallest height divided by a power of the arity"
+        );
+
         let tallest_matrices = leaves_largest_first
             .peeking_take_while(|m| m.height() == max_height)
             .collect_vec();
 
-        let mut digest_layers = vec![first_digest_layer::<P, _, _, _, DIGEST_ELEMS>(
+        let first_layer =
+            first_digest_layer::<P, _, _, _, DIGEST_ELEMS, ARITY>(h, tallest_matrices);
+        let digest_layers = build_digest_layers::<P, PW, H, C, M, L, DIGEST_ELEMS, ARITY>(
             h,
-            tallest_matrices,
-        )];
-        loop {
-            let prev_layer = digest_layers.last().unwrap().as_slice();
-            if prev_layer.len() == 1 {
-                break;
-            }
-            let next_layer_len = (prev_layer.len() / 2).next_power_of_two();
-
-            // The matrices that get injected at this layer.
-            let matrices_to_inject = leaves_largest_first
-                .peeking_take_while(|m| m.height().next_power_of_two() == next_layer_len)
-                .collect_vec();
-
-            let next_digests = compress_and_inject::<P, _, _, _, _, DIGEST_ELEMS>(
-                prev_layer,
-                matrices_to_inject,
-                h,
-                c,
-            );
-            digest_layers.push(next_digests);
-        }
+            c,
+            first_layer,
+            max_height,
+            leaves_largest_first,
+            store_layer,
+        );
+        Self::from_digest_layers(leaves, digest_layers)
+    }
 
+    pub(crate) const fn from_digest_layers(leaves: Vec<M>, digest_layers: Vec<L>) -> Self {
         Self {
             leaves,
             digest_layers,
@@ -108,12 +167,125 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
     where
         W: Copy,
     {
-        self.digest_layers.last().unwrap()[0].into()
+        self.digest_layers.last().unwrap().borrow()[0].into()
+    }
+
+    /// Returns the digests of all nodes `cap_height` layers below the root.
+    #[must_use]
+    pub fn cap(&self, cap_height: usize) -> MerkleCap<F, W, DIGEST_ELEMS>
+    where
+        W: Copy,
+    {
+        assert!(
+            cap_height < self.digest_layers.len(),
+            "cap height exceeds the height of the tree"
+        );
+        self.digest_layers[self.digest_layers.len() - 1 - cap_height]
+            .borrow()
+            .to_vec()
+            .into()
+    }
+}
+
+/// Returns the heights of the layers of a tree with the given arity whose tallest matrix has
+/// `max_height` rows, from the leaves up to the root, with each rounded up to a power of two.
+pub(crate) fn padded_layer_heights(
+    max_height: usize,
+    arity: usize,
+) -> impl Iterator<Item = usize> + Clone {
+    let mut next = Some(max_height.next_power_of_two());
+    iter::from_fn(move || {
+        let height = next?;
+        next = (height > 1).then(|| height.div_ceil(arity));
+        Some(height)
+    })
+}
+
+/// Returns the lengths of the digest layers of a tree whose tallest matrix has `max_height` rows,
+/// from the leaves up, including padding.
+pub(crate) fn digest_layer_lens<const ARITY: usize>(
+    max_height: usize,
+) -> impl Iterator<Item = usize> {
+    iter::successors(Some(first_layer_len_padded::<ARITY>(max_height)), |&len| {
+        (len > 1).then(|| next_layer_len_padded::<ARITY>(len))
+    })
+}
+
+/// Returns the length of the layer above `prev_layer_len` nodes, after padding. Every layer except
+/// the root is padded with default digests to a multiple of `ARITY` nodes.
+const fn next_layer_len_padded<const ARITY: usize>(prev_layer_len: usize) -> usize {
+    if prev_layer_len == ARITY {
+        1
+    } else {
+        (prev_layer_len / ARITY).next_multiple_of(ARITY)
+    }
+}
+
+/// Builds the digest layers of a tree, from the leaves up to the root, given the digests of the
+/// rows of its tallest matrices, which have `max_height` rows. The remaining matrices are injected
+/// in order, so `shorter_matrices` must be sorted by decreasing height.
+pub(crate) fn build_digest_layers<
+    'a,
+    P,
+    PW,
+    H,
+    C,
+    M,
+    L,
+    const DIGEST_ELEMS: usize,
+    const ARITY: usize,
+>(
+    h: &H,
+    c: &C,
+    first_layer: Vec<[PW::Value; DIGEST_ELEMS]>,
+    max_height: usize,
+    shorter_matrices: impl Iterator<Item = &'a M>,
+    mut store_layer: impl FnMut(Vec<[PW::Value; DIGEST_ELEMS]>) -> L,
+) -> Vec<L>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>
+        + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
+        + Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>
+        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>
+        + Sync,
+    M: Matrix<P::Value> + 'a,
+{
+    let mut shorter_matrices = shorter_matrices.peekable();
+    let mut prev_layer = first_layer;
+    let mut digest_layers = vec![];
+    for next_layer_height in padded_layer_heights(max_height, ARITY).skip(1) {
+        // The matrices that get injected at this layer.
+        let matrices_to_inject = shorter_matrices
+            .peeking_take_while(|m| m.height().next_power_of_two() == next_layer_height)
+            .collect_vec();
+
+        let next_digests = compress_and_inject::<P, _, _, _, _, DIGEST_ELEMS, ARITY>(
+            &prev_layer,
+            matrices_to_inject,
+            h,
+            c,
+        );
+        digest_layers.push(store_layer(mem::replace(&mut prev_layer, next_digests)));
+    }
+    digest_layers.push(store_layer(prev_layer));
+    digest_layers
+}
+
+/// Returns the length of the first digest layer of a tree whose tallest matrix has `max_height`
+/// rows. It is padded with default digests to a multiple of `ARITY`, unless it is the root.
+pub(crate) const fn first_layer_len_padded<const ARITY: usize>(max_height: usize) -> usize {
+    if max_height == 1 {
+        1
+    } else {
+        max_height.next_multiple_of(ARITY)
     }
 }
 
 #[instrument(name = "first digest layer", level = "debug", skip_all)]
-fn first_digest_layer<P, PW, H, M, const DIGEST_ELEMS: usize>(
+fn first_digest_layer<P, PW, H, M, const DIGEST_ELEMS: usize, const ARITY: usize>(
     h: &H,
     tallest_matrices: Vec<&M>,
 ) -> Vec<[PW::Value; DIGEST_ELEMS]>
@@ -125,25 +297,36 @@ where
         + Sync,
     M: Matrix<P::Value>,
 {
-    let width = PW::WIDTH;
     let max_height = tallest_matrices[0].height();
-    // we always want to return an even number of digests, except when it's the root.
-    let max_height_padded = if max_height == 1 {
-        1
-    } else {
-        max_height + max_height % 2
-    };
-
     let default_digest = [PW::Value::default(); DIGEST_ELEMS];
-    let mut digests = vec![default_digest; max_height_padded];
+    let mut digests = vec![default_digest; first_layer_len_padded::<ARITY>(max_height)];
+    hash_rows::<P, PW, _, _, DIGEST_ELEMS>(h, &tallest_matrices, &mut digests[..max_height]);
+    digests
+}
+
+/// Hashes the rows of `matrices`, which must all have `digests.len()` rows, into `digests`.
+pub(crate) fn hash_rows<P, PW, H, M, const DIGEST_ELEMS: usize>(
+    h: &H,
+    matrices: &[&M],
+    digests: &mut [[PW::Value; DIGEST_ELEMS]],
+) where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>
+        + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
+        + Sync,
+    M: Matrix<P::Value>,
+{
+    let width = PW::WIDTH;
+    let height = digests.len();
 
-    digests[0..max_height]
+    digests
         .par_chunks_exact_mut(width)
         .enumerate()
         .for_each(|(i, digests_chunk)| {
             let first_row = i * width;
             let packed_digest: [PW; DIGEST_ELEMS] = h.hash_iter(
-                tallest_matrices
+                matrices
                     .iter()
                     .flat_map(|m| m.vertically_packed_row(first_row)),
             );
@@ -152,20 +335,17 @@ where
             }
         });
 
-    // If our packing width did not divide max_height, fall back to single-threaded scalar code
+    // If our packing width did not divide the height, fall back to single-threaded scalar code
     // for the last bit.
     #[allow(clippy::needless_range_loop)]
-    for i in (max_height / width * width)..max_height {
-        digests[i] = h.hash_iter(tallest_matrices.iter().flat_map(|m| m.row(i)));
+    for i in (height / width * width)..height {
+        digests[i] = h.hash_iter(matrices.iter().flat_map(|m| m.row(i)));
     }
-
-    // Everything has been initialized so we can safely cast.
-    digests
 }
 
-/// Compress `n` digests from the previous layer into `n/2` digests, while potentially mixing in
-/// some leaf data, if there are input matrices with (padded) height `n/2`.
-fn compress_and_inject<P, PW, H, C, M, const DIGEST_ELEMS: usize>(
+/// Compress `n` digests from the previous layer into `n/ARITY` digests, while potentially mixing
+/// in some leaf data, if there are input matrices with (padded) height `n/ARITY`.
+fn compress_and_inject<P, PW, H, C, M, const DIGEST_ELEMS: usize, const ARITY: usize>(
     prev_layer: &[[PW::Value; DIGEST_ELEMS]],
     matrices_to_inject: Vec<&M>,
     h: &H,
@@ -177,40 +357,43 @@ where
     H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>
         + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
         + Sync,
-    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>
-        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>
+        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>
         + Sync,
     M: Matrix<P::Value>,
 {
     if matrices_to_inject.is_empty() {
-        return compress::<PW, _, DIGEST_ELEMS>(prev_layer, c);
+        return compress::<PW, _, DIGEST_ELEMS, ARITY>(prev_layer, c);
     }
 
     let width = PW::WIDTH;
     let next_len = matrices_to_inject[0].height();
-    // We always want to return an even number of digests, except when it's the root.
-    let next_len_padded = if prev_layer.len() == 2 {
-        1
-    } else {
-        (prev_layer.len() / 2 + 1) & !1
-    };
+    // We always want to return a multiple of ARITY digests, except when it's the root.
+    let next_len_padded = next_layer_len_padded::<ARITY>(prev_layer.len());
 
     let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+    let default_packed_digest = [PW::from_fn(|_| PW::Value::default()); DIGEST_ELEMS];
     let mut next_digests = vec![default_digest; next_len_padded];
     next_digests[0..next_len]
         .par_chunks_exact_mut(width)
         .enumerate()
         .for_each(|(i, digests_chunk)| {
             let first_row = i * width;
-            let left = array::from_fn(|j| PW::from_fn(|k| prev_layer[2 * (first_row + k)][j]));
-            let right = array::from_fn(|j| PW::from_fn(|k| prev_layer[2 * (first_row + k) + 1][j]));
-            let mut packed_digest = c.compress([left, right]);
+            let children = array::from_fn(|child| {
+                array::from_fn(|j| PW::from_fn(|k| prev_layer[ARITY * (first_row + k) + child][j]))
+            });
+            let packed_digest = c.compress(children);
             let tallest_digest = h.hash_iter(
                 matrices_to_inject
                     .iter()
                     .flat_map(|m| m.vertically_packed_row(first_row)),
             );
-            packed_digest = c.compress([packed_digest, tallest_digest]);
+            let packed_digest = inject::<_, _, DIGEST_ELEMS, ARITY>(
+                c,
+                packed_digest,
+                tallest_digest,
+                default_packed_digest,
+            );
             for (dst, src) in digests_chunk.iter_mut().zip(unpack_array(packed_digest)) {
                 *dst = src;
             }
@@ -219,45 +402,60 @@ where
     // If our packing width did not divide next_len, fall back to single-threaded scalar code
     // for the last bit.
     for i in (next_len / width * width)..next_len {
-        let left = prev_layer[2 * i];
-        let right = prev_layer[2 * i + 1];
-        let digest = c.compress([left, right]);
+        let digest = c.compress(array::from_fn(|child| prev_layer[ARITY * i + child]));
         let rows_digest = h.hash_iter(matrices_to_inject.iter().flat_map(|m| m.row(i)));
-        next_digests[i] = c.compress([digest, rows_digest]);
+        next_digests[i] =
+            inject::<_, _, DIGEST_ELEMS, ARITY>(c, digest, rows_digest, default_digest);
     }
 
     // At this point, we've exceeded the height of the matrices to inject, so we continue the
     // process above except with default_digest in place of an input digest.
-    // We only need go as far as half the length of the previous layer.
-    for i in next_len..(prev_layer.len() / 2) {
-        let left = prev_layer[2 * i];
-        let right = prev_layer[2 * i + 1];
-        let digest = c.compress([left, right]);
-        next_digests[i] = c.compress([digest, default_digest]);
+    // We only need go as far as the length of the previous layer divided by ARITY.
+    for i in next_len..(prev_layer.len() / ARITY) {
+        let digest = c.compress(array::from_fn(|child| prev_layer[ARITY * i + child]));
+        next_digests[i] =
+            inject::<_, _, DIGEST_ELEMS, ARITY>(c, digest, default_digest, default_digest);
     }
 
     next_digests
 }
 
-/// Compress `n` digests from the previous layer into `n/2` digests.
-fn compress<P, C, const DIGEST_ELEMS: usize>(
+/// Mixes the digest of some injected rows into the digest of a node, by compressing the two
+/// along with `ARITY - 2` default digests. For binary trees, this is just `c.compress([node,
+/// rows])`.
+#[inline]
+pub(crate) fn inject<T, C, const DIGEST_ELEMS: usize, const ARITY: usize>(
+    c: &C,
+    node_digest: [T; DIGEST_ELEMS],
+    rows_digest: [T; DIGEST_ELEMS],
+    default_digest: [T; DIGEST_ELEMS],
+) -> [T; DIGEST_ELEMS]
+where
+    T: Copy,
+    C: PseudoCompressionFunction<[T; DIGEST_ELEMS], ARITY>,
+{
+    c.compress(array::from_fn(|i| match i {
+        0 => node_digest,
+        1 => rows_digest,
+        _ => default_digest,
+    }))
+}
+
+/// Compress `n` digests from the previous layer into `n/ARITY` digests.
+fn compress<P, C, const DIGEST_ELEMS: usize, const ARITY: usize>(
     prev_layer: &[[P::Value; DIGEST_ELEMS]],
     c: &C,
 ) -> Vec<[P::Value; DIGEST_ELEMS]>
 where
     P: PackedValue,
-    C: PseudoCompressionFunction<[P::Value; DIGEST_ELEMS], 2>
-        + PseudoCompressionFunction<[P; DIGEST_ELEMS], 2>
+    C: PseudoCompressionFunction<[P::Value; DIGEST_ELEMS], ARITY>
+        + PseudoCompressionFunction<[P; DIGEST_ELEMS], ARITY>
         + Sync,
 {
     let width = P::WIDTH;
-    // Always return an even number of digests, except when it's the root.
-    let next_len_padded = if prev_layer.len() == 2 {
-        1
-    } else {
-        (prev_layer.len() / 2 + 1) & !1
-    };
-    let next_len = prev_layer.len() / 2;
+    let next_len = prev_layer.len() / ARITY;
+    // Always return a multiple of ARITY digests, except when it's the root.
+    let next_len_padded = next_layer_len_padded::<ARITY>(prev_layer.len());
 
     let default_digest = [P::Value::default(); DIGEST_ELEMS];
     let mut next_digests = vec![default_digest; next_len_padded];
@@ -267,9 +465,10 @@ where
         .enumerate()
         .for_each(|(i, digests_chunk)| {
             let first_row = i * width;
-            let left = array::from_fn(|j| P::from_fn(|k| prev_layer[2 * (first_row + k)][j]));
-            let right = array::from_fn(|j| P::from_fn(|k| prev_layer[2 * (first_row + k) + 1][j]));
-            let packed_digest = c.compress([left, right]);
+            let children = array::from_fn(|child| {
+                array::from_fn(|j| P::from_fn(|k| prev_layer[ARITY * (first_row + k) + child][j]))
+            });
+            let packed_digest = c.compress(children);
             for (dst, src) in digests_chunk.iter_mut().zip(unpack_array(packed_digest)) {
                 *dst = src;
             }
@@ -278,9 +477,7 @@ where
     // If our packing width did not divide next_len, fall back to single-threaded scalar code
     // for the last bit.
     for i in (next_len / width * width)..next_len {
-        let left = prev_layer[2 * i];
-        let right = prev_layer[2 * i + 1];
-        next_digests[i] = c.compress([left, right]);
+        next_digests[i] = c.compress(array::from_fn(|child| prev_layer[ARITY * i + child]));
     }
 
     // Everything has been initialized so we can safely cast.
@@ -324,7 +521,7 @@ mod tests {
             [0x03; 32], // 0x01 ^ 0x02
             [0x07; 32], // 0x03 ^ 0x04
         ];
-        let result = compress::<u8, DummyCompressionFunction, 32>(&prev_layer, &compressor);
+        let result = compress::<u8, DummyCompressionFunction, 32, 2>(&prev_layer, &compressor);
         assert_eq!(result, expected);
     }
 
@@ -336,7 +533,7 @@ mod tests {
             [0x03; 32], // 0x05 ^ 0x06
             [0x00; 32],
         ];
-        let result = compress::<u8, DummyCompressionFunction, 32>(&prev_layer, &compressor);
+        let result = compress::<u8, DummyCompressionFunction, 32, 2>(&prev_layer, &compressor);
         assert_eq!(result, expected);
     }
 
@@ -355,7 +552,7 @@ mod tests {
                 result
             })
             .collect();
-        let result = compress::<u8, DummyCompressionFunction, 32>(&prev_layer, &compressor);
+        let result = compress::<u8, DummyCompressionFunction, 32, 2>(&prev_layer, &compressor);
         assert_eq!(result, expected);
     }
 }
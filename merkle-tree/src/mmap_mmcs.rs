@@ -0,0 +1,264 @@
+use alloc::vec::Vec;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use p3_commit::Mmcs;
+use p3_field::{PackedValue, Pod};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::mmap::{MmapMatrix, MmapStorage};
+use p3_matrix::{Dimensions, Matrix};
+use p3_symmetric::{CryptographicHasher, MerkleCap, PseudoCompressionFunction};
+use serde::{Deserialize, Serialize};
+
+use crate::{MerkleTree, MerkleTreeError, MerkleTreeMmcs};
+
+/// A vector commitment scheme backed by a `MerkleTree` whose leaves and digest layers live on
+/// disk.
+///
+/// This commits to the same tree as `MerkleTreeMmcs`, with the same proofs, but spills each
+/// digest layer to a memory-mapped file in `scratch_dir` as soon as the layer above it has been
+/// computed. Openings read the sibling digests back from those files. The files are removed when
+/// the prover data is dropped.
+///
+/// Matrices passed through `store_matrix`, as a PCS does with its LDEs, are written to the
+/// scratch directory too, so openings read the opened rows back from disk. Other matrices are
+/// kept as given.
+#[derive(Clone, Debug)]
+pub struct MmapMerkleTreeMmcs<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize = 2> {
+    inner: MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>,
+    scratch_dir: PathBuf,
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MmapMerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
+{
+    /// Wraps `inner`, storing matrices and digest layers in files in `scratch_dir`.
+    ///
+    /// Returns an error if a file can't be written to `scratch_dir`.
+    pub fn new(
+        inner: MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>,
+        scratch_dir: PathBuf,
+    ) -> io::Result<Self> {
+        // The file is removed again as soon as it is dropped.
+        MmapStorage::create_temp(&scratch_dir, [0u8])?;
+        Ok(Self { inner, scratch_dir })
+    }
+
+    pub fn scratch_dir(&self) -> &Path {
+        &self.scratch_dir
+    }
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize> Mmcs<P::Value>
+    for MmapMerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>
+        + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
+        + Clone
+        + Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>
+        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>
+        + Clone
+        + Sync,
+    P::Value: Pod,
+    PW::Value: Eq + Pod,
+    [PW::Value; DIGEST_ELEMS]: Serialize + for<'de> Deserialize<'de>,
+{
+    type ProverData<M> =
+        MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, MmapStorage<[PW::Value; DIGEST_ELEMS]>>;
+    type Commitment = MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>;
+    type Proof = Vec<[PW::Value; DIGEST_ELEMS]>;
+    type Error = MerkleTreeError;
+    type MatrixStorage = MmapStorage<P::Value>;
+
+    /// Writes `matrix` to a file in the scratch directory, which is removed when the returned
+    /// matrix is dropped.
+    ///
+    /// # Panics
+    /// Panics if the file can't be written, e.g. because the disk is full.
+    fn store_matrix(&self, matrix: RowMajorMatrix<P::Value>) -> MmapMatrix<P::Value> {
+        let width = matrix.width;
+        let values = MmapStorage::create_temp(&self.scratch_dir, matrix.values)
+            .expect("failed to write matrix to the scratch directory");
+        MmapMatrix::new(values, width)
+    }
+
+    /// # Panics
+    /// Panics if a digest layer can't be written to the scratch directory, e.g. because the disk
+    /// is full.
+    fn commit<M: Matrix<P::Value>>(
+        &self,
+        inputs: Vec<M>,
+    ) -> (Self::Commitment, Self::ProverData<M>) {
+        self.inner.commit_with_layer_storage(inputs, |layer| {
+            MmapStorage::create_temp(&self.scratch_dir, layer)
+                .expect("failed to write digest layer to the scratch directory")
+        })
+    }
+
+    fn open_batch<M: Matrix<P::Value>>(
+        &self,
+        index: usize,
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<P::Value>>, Self::Proof) {
+        self.inner.open_batch_in(index, prover_data)
+    }
+
+    fn open_multi<M: Matrix<P::Value>>(
+        &self,
+        indices: &[usize],
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<Vec<P::Value>>>, Vec<Self::Proof>) {
+        self.inner.open_multi_in(indices, prover_data)
+    }
+
+    fn get_matrices<'a, M: Matrix<P::Value>>(
+        &self,
+        prover_data: &'a Self::ProverData<M>,
+    ) -> Vec<&'a M> {
+        prover_data.leaves.iter().collect()
+    }
+
+    fn verify_batch(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        index: usize,
+        opened_values: &[Vec<P::Value>],
+        proof: &Self::Proof,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .verify_batch(commit, dimensions, index, opened_values, proof)
+    }
+
+    fn verify_multi(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        indices: &[usize],
+        opened_values: &[Vec<Vec<P::Value>>],
+        proofs: &[Self::Proof],
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .verify_multi(commit, dimensions, indices, opened_values, proofs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use alloc::{format, vec};
+    use std::fs;
+    use std::path::PathBuf;
+
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_commit::Mmcs;
+    use p3_field::Field;
+    use p3_matrix::Matrix;
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::MmapMerkleTreeMmcs;
+    use crate::MerkleTreeMmcs;
+
+    type F = BabyBear;
+
+    type Perm = Poseidon2BabyBear<16>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type MyMmcs =
+        MerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, MyHash, MyCompress, 8>;
+    type MyMmapMmcs =
+        MmapMerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, MyHash, MyCompress, 8>;
+
+    /// Returns an empty scratch directory specific to the calling test.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("p3-merkle-tree-mmap-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn mmcs_pair(scratch_dir: PathBuf) -> (MyMmcs, MyMmapMmcs) {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new_with_cap_height(hash, compress, 1);
+        (mmcs.clone(), MyMmapMmcs::new(mmcs, scratch_dir).unwrap())
+    }
+
+    #[test]
+    fn matches_in_memory_mmcs() {
+        let dir = scratch_dir("matches");
+        let (mmcs, mmap_mmcs) = mmcs_pair(dir.clone());
+        let mut rng = SmallRng::seed_from_u64(2);
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 64, 5),
+            RowMajorMatrix::<F>::rand(&mut rng, 16, 3),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect::<Vec<_>>();
+
+        let (commit, prover_data) = mmcs.commit(mats.clone());
+        let stored = mats
+            .iter()
+            .map(|m| mmap_mmcs.store_matrix(m.clone()))
+            .collect::<Vec<_>>();
+        let (mmap_commit, mmap_prover_data) = mmap_mmcs.commit(stored);
+        assert_eq!(mmap_commit, commit);
+
+        for index in [0, 17, 63] {
+            let opening = mmap_mmcs.open_batch(index, &mmap_prover_data);
+            assert_eq!(opening, mmcs.open_batch(index, &prover_data));
+            mmap_mmcs
+                .verify_batch(&commit, &dims, index, &opening.0, &opening.1)
+                .expect("expected verification to succeed");
+        }
+
+        let indices = [3, 4, 40];
+        let (openings, proof) = mmap_mmcs.open_multi(&indices, &mmap_prover_data);
+        assert_eq!(
+            (openings.clone(), proof.clone()),
+            mmcs.open_multi(&indices, &prover_data)
+        );
+        mmap_mmcs
+            .verify_multi(&commit, &dims, &indices, &openings, &proof)
+            .expect("expected verification to succeed");
+
+        let matrices = mmap_mmcs.get_matrices(&mmap_prover_data);
+        assert_eq!(matrices[1].as_view().to_row_major_matrix(), mats[1]);
+    }
+
+    #[test]
+    fn scratch_files_removed_on_drop() {
+        let dir = scratch_dir("cleanup");
+        let (_, mmap_mmcs) = mmcs_pair(dir.clone());
+        let mut rng = SmallRng::seed_from_u64(3);
+        let mat = RowMajorMatrix::<F>::rand(&mut rng, 32, 2);
+
+        let (_, prover_data) = mmap_mmcs.commit(vec![mat]);
+        // One file per digest layer, from 32 leaf digests up to the root.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 6);
+        drop(prover_data);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        let stored = mmap_mmcs.store_matrix(RowMajorMatrix::<F>::rand(&mut rng, 8, 2));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        drop(stored);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir(dir).unwrap();
+    }
+
+    #[test]
+    fn new_rejects_missing_scratch_dir() {
+        let dir = scratch_dir("missing");
+        fs::remove_dir(&dir).unwrap();
+        let (mmcs, _) = mmcs_pair(std::env::temp_dir());
+        assert!(MyMmapMmcs::new(mmcs, dir).is_err());
+    }
+}
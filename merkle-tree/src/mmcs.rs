@@ -1,17 +1,28 @@
+use alloc::collections::BTreeMap;
+use alloc::vec;
 use alloc::vec::Vec;
+use core::array;
+use core::borrow::Borrow;
 use core::cmp::Reverse;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
 use core::marker::PhantomData;
 
-use itertools::Itertools;
-use p3_commit::Mmcs;
+use itertools::{Itertools, izip};
+use p3_commit::{Mmcs, WrongOpeningCount, check_opening_count};
 use p3_field::PackedValue;
+use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::{Dimensions, Matrix};
-use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
+use p3_symmetric::{CryptographicHasher, MerkleCap, PseudoCompressionFunction};
 use p3_util::log2_ceil_usize;
 use serde::{Deserialize, Serialize};
 
 use crate::MerkleTree;
-use crate::MerkleTreeError::{EmptyBatch, RootMismatch, WrongBatchSize, WrongHeight};
+use crate::MerkleTreeError::{
+    EmptyBatch, IncompatibleHeights, IndexOutOfBounds, RootMismatch, WrongBatchSize,
+    WrongCapLength, WrongHeight, WrongWidth,
+};
+use crate::merkle_tree::{digest_layer_lens, inject, padded_layer_heights};
 
 /// A vector commitment scheme backed by a `MerkleTree`.
 ///
@@ -20,61 +31,313 @@ use crate::MerkleTreeError::{EmptyBatch, RootMismatch, WrongBatchSize, WrongHeig
 /// - `PW`: an element of a digest
 /// - `H`: the leaf hasher
 /// - `C`: the digest compression function
+/// - `ARITY`: the number of children of each node, which must be a power of two
 #[derive(Copy, Clone, Debug)]
-pub struct MerkleTreeMmcs<P, PW, H, C, const DIGEST_ELEMS: usize> {
-    hash: H,
-    compress: C,
+pub struct MerkleTreeMmcs<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize = 2> {
+    pub(crate) hash: H,
+    pub(crate) compress: C,
+    /// The height of the Merkle cap which serves as the commitment. Opening proofs stop at the
+    /// cap, so they are `cap_height` layers shorter than a path to the root.
+    cap_height: usize,
     _phantom: PhantomData<(P, PW)>,
 }
 
 #[derive(Debug)]
 pub enum MerkleTreeError {
-    WrongBatchSize,
-    WrongWidth,
+    /// The number of opened rows doesn't match the number of matrices, or, for a multi-opening,
+    /// the number of openings or proofs doesn't match the number of indices.
+    WrongBatchSize {
+        expected: usize,
+        actual: usize,
+    },
+    /// The opened row of the given matrix, or its salt, has the wrong number of elements.
+    WrongWidth {
+        matrix: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// The proof has the wrong number of siblings for a tree of the given height.
     WrongHeight {
         max_height: usize,
         num_siblings: usize,
     },
-    RootMismatch,
+    /// The commitment doesn't have as many digests as the cap of a tree of the given height.
+    WrongCapLength {
+        max_height: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// The opening at the given index doesn't hash to the commitment.
+    RootMismatch {
+        index: usize,
+    },
     EmptyBatch,
+    /// The opened index is not a row of the tallest matrix.
+    IndexOutOfBounds {
+        max_height: usize,
+        index: usize,
+    },
+    /// The matrix heights don't fit in a tree of the given arity, so no commitment could have
+    /// been made to them.
+    IncompatibleHeights,
 }
 
-impl<P, PW, H, C, const DIGEST_ELEMS: usize> MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS> {
+impl Display for MerkleTreeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongBatchSize { expected, actual } => {
+                write!(f, "expected {expected} opened rows, got {actual}")
+            }
+            Self::WrongWidth {
+                matrix,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "expected {expected} values in the row of matrix {matrix}, got {actual}"
+            ),
+            Self::WrongHeight {
+                max_height,
+                num_siblings,
+            } => write!(
+                f,
+                "{num_siblings} siblings don't fit a tree of height {max_height}"
+            ),
+            Self::WrongCapLength {
+                max_height,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "expected a cap of {expected} digests for a tree of height {max_height}, got {actual}"
+            ),
+            Self::RootMismatch { index } => {
+                write!(f, "opening at index {index} doesn't match the commitment")
+            }
+            Self::EmptyBatch => write!(f, "no matrices were opened"),
+            Self::IndexOutOfBounds { max_height, index } => {
+                write!(f, "index {index} is out of bounds for height {max_height}")
+            }
+            Self::IncompatibleHeights => write!(f, "matrix heights don't fit a single tree"),
+        }
+    }
+}
+
+impl Error for MerkleTreeError {}
+
+impl From<WrongOpeningCount> for MerkleTreeError {
+    fn from(error: WrongOpeningCount) -> Self {
+        WrongBatchSize {
+            expected: error.expected,
+            actual: error.actual,
+        }
+    }
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
+{
+    const LOG_ARITY: usize = ARITY.ilog2() as usize;
+
     pub const fn new(hash: H, compress: C) -> Self {
+        Self::new_with_cap_height(hash, compress, 0)
+    }
+
+    /// Like `new`, but commits to the `ARITY^cap_height` nodes at height `cap_height` below the
+    /// root rather than to the root alone.
+    pub const fn new_with_cap_height(hash: H, compress: C, cap_height: usize) -> Self {
+        assert!(
+            ARITY >= 2 && ARITY.is_power_of_two(),
+            "arity must be a power of two"
+        );
         Self {
             hash,
             compress,
+            cap_height,
             _phantom: PhantomData,
         }
     }
+
+    /// Returns the number of layers above the layer at which a matrix of the given height is
+    /// injected.
+    const fn num_layers_above(height: usize) -> usize {
+        log2_ceil_usize(height).div_ceil(Self::LOG_ARITY)
+    }
+
+    /// Returns the height of the cap used to commit to matrices of the given heights.
+    ///
+    /// This is lowered for batches containing matrices shorter than the cap, since those are
+    /// injected into the tree above the cap and would otherwise not be committed to.
+    pub(crate) fn cap_height_for(&self, heights: impl IntoIterator<Item = usize>) -> usize {
+        heights
+            .into_iter()
+            .map(Self::num_layers_above)
+            .min()
+            .map_or(0, |min_layers| self.cap_height.min(min_layers))
+    }
+
+    /// Returns the number of layers between the leaves and the cap of a committed tree, each of
+    /// which contributes `ARITY - 1` siblings to an opening proof.
+    fn num_path_layers<F: Clone + Send + Sync, W, M: Matrix<F>, L>(
+        &self,
+        tree: &MerkleTree<F, W, M, DIGEST_ELEMS, L>,
+    ) -> usize {
+        let heights = tree.leaves.iter().map(|m| m.height()).collect_vec();
+        let max_height = *heights.iter().max().unwrap();
+        Self::num_layers_above(max_height) - self.cap_height_for(heights)
+    }
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>
+        + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
+        + Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>
+        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>
+        + Sync,
+{
+    /// Like `Mmcs::commit`, but stores the digest layers of the tree using `store_layer`, as in
+    /// `MerkleTree::new_with_layer_storage`.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn commit_with_layer_storage<M, L>(
+        &self,
+        inputs: Vec<M>,
+        store_layer: impl FnMut(Vec<[PW::Value; DIGEST_ELEMS]>) -> L,
+    ) -> (
+        MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>,
+        MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, L>,
+    )
+    where
+        M: Matrix<P::Value>,
+        L: Borrow<[[PW::Value; DIGEST_ELEMS]]>,
+    {
+        let tree = MerkleTree::new_with_layer_storage::<P, PW, H, C, ARITY>(
+            &self.hash,
+            &self.compress,
+            inputs,
+            store_layer,
+        );
+        let cap = tree.cap(self.cap_height_for(tree.leaves.iter().map(|m| m.height())));
+        (cap, tree)
+    }
+
+    /// `Mmcs::open_batch` for a tree with any digest layer storage.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn open_batch_in<M, L>(
+        &self,
+        index: usize,
+        prover_data: &MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, L>,
+    ) -> (Vec<Vec<P::Value>>, Vec<[PW::Value; DIGEST_ELEMS]>)
+    where
+        M: Matrix<P::Value>,
+        L: Borrow<[[PW::Value; DIGEST_ELEMS]]>,
+    {
+        let openings = open_rows(prover_data, index);
+
+        let proof: Vec<_> = prover_data.digest_layers[..self.num_path_layers(prover_data)]
+            .iter()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                let layer = layer.borrow();
+                let node = index >> (i * Self::LOG_ARITY);
+                let first_sibling = node & !(ARITY - 1);
+                (first_sibling..first_sibling + ARITY)
+                    .filter(move |&sibling| sibling != node)
+                    .map(move |sibling| layer[sibling])
+            })
+            .collect();
+
+        (openings, proof)
+    }
+
+    /// `Mmcs::open_multi` for a tree with any digest layer storage.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn open_multi_in<M, L>(
+        &self,
+        indices: &[usize],
+        prover_data: &MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, L>,
+    ) -> (Vec<Vec<Vec<P::Value>>>, Vec<Vec<[PW::Value; DIGEST_ELEMS]>>)
+    where
+        M: Matrix<P::Value>,
+        L: Borrow<[[PW::Value; DIGEST_ELEMS]]>,
+    {
+        let openings = indices
+            .iter()
+            .map(|&index| open_rows(prover_data, index))
+            .collect();
+
+        // Walk up the tree, only including the siblings of known nodes which are not known
+        // themselves. They go in the proof of the first query whose path passes through their
+        // parent.
+        let mut known_nodes = Self::path_owners(indices, 0);
+        let mut proofs = vec![Vec::new(); indices.len()];
+        for (layer, digests) in prover_data.digest_layers[..self.num_path_layers(prover_data)]
+            .iter()
+            .enumerate()
+        {
+            let digests = digests.borrow();
+            let parents = Self::path_owners(indices, layer + 1);
+            for (&parent, &owner) in &parents {
+                proofs[owner].extend(
+                    (parent * ARITY..(parent + 1) * ARITY)
+                        .filter(|node| !known_nodes.contains_key(node))
+                        .map(|node| digests[node]),
+                );
+            }
+            known_nodes = parents;
+        }
+
+        (openings, proofs)
+    }
+
+    /// Maps each node at `layer` on the path of some index to the position of the first such
+    /// index.
+    fn path_owners(indices: &[usize], layer: usize) -> BTreeMap<usize, usize> {
+        let mut owners = BTreeMap::new();
+        for (query, &index) in indices.iter().enumerate() {
+            owners
+                .entry(index >> (layer * Self::LOG_ARITY))
+                .or_insert(query);
+        }
+        owners
+    }
 }
 
-impl<P, PW, H, C, const DIGEST_ELEMS: usize> Mmcs<P::Value>
-    for MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS>
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize> Mmcs<P::Value>
+    for MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
 where
     P: PackedValue,
     PW: PackedValue,
     H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>
         + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
         + Sync,
-    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>
-        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>
+        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>
         + Sync,
     PW::Value: Eq,
     [PW::Value; DIGEST_ELEMS]: Serialize + for<'de> Deserialize<'de>,
 {
     type ProverData<M> = MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS>;
-    type Commitment = Hash<P::Value, PW::Value, DIGEST_ELEMS>;
+    type Commitment = MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>;
+    /// The `ARITY - 1` siblings of the opened node at each layer, from the leaves up, and in
+    /// increasing order of index within each layer.
     type Proof = Vec<[PW::Value; DIGEST_ELEMS]>;
     type Error = MerkleTreeError;
+    type MatrixStorage = Vec<P::Value>;
+
+    fn store_matrix(&self, matrix: RowMajorMatrix<P::Value>) -> RowMajorMatrix<P::Value> {
+        matrix
+    }
 
     fn commit<M: Matrix<P::Value>>(
         &self,
         inputs: Vec<M>,
     ) -> (Self::Commitment, Self::ProverData<M>) {
-        let tree = MerkleTree::new::<P, PW, H, C>(&self.hash, &self.compress, inputs);
-        let root = tree.root();
-        (root, tree)
+        self.commit_with_layer_storage(inputs, |layer| layer)
     }
 
     fn open_batch<M: Matrix<P::Value>>(
@@ -82,25 +345,15 @@ where
         index: usize,
         prover_data: &MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS>,
     ) -> (Vec<Vec<P::Value>>, Vec<[PW::Value; DIGEST_ELEMS]>) {
-        let max_height = self.get_max_height(prover_data);
-        let log_max_height = log2_ceil_usize(max_height);
-
-        let openings = prover_data
-            .leaves
-            .iter()
-            .map(|matrix| {
-                let log2_height = log2_ceil_usize(matrix.height());
-                let bits_reduced = log_max_height - log2_height;
-                let reduced_index = index >> bits_reduced;
-                matrix.row(reduced_index).collect()
-            })
-            .collect_vec();
-
-        let proof: Vec<_> = (0..log_max_height)
-            .map(|i| prover_data.digest_layers[i][(index >> i) ^ 1])
-            .collect();
+        self.open_batch_in(index, prover_data)
+    }
 
-        (openings, proof)
+    fn open_multi<M: Matrix<P::Value>>(
+        &self,
+        indices: &[usize],
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<Vec<P::Value>>>, Vec<Self::Proof>) {
+        self.open_multi_in(indices, prover_data)
     }
 
     fn get_matrices<'a, M: Matrix<P::Value>>(
@@ -118,25 +371,17 @@ where
         opened_values: &[Vec<P::Value>],
         proof: &Self::Proof,
     ) -> Result<(), Self::Error> {
+        let leaf_index = index;
         // Check that the openings have the correct shape.
-        if dimensions.len() != opened_values.len() {
-            return Err(WrongBatchSize);
+        check_opened_shape(dimensions, opened_values)?;
+        let max_height = check_dimensions(dimensions, ARITY)?;
+        if index >= max_height {
+            return Err(IndexOutOfBounds { max_height, index });
         }
-
-        // TODO: Disabled for now since TwoAdicFriPcs and CirclePcs currently pass 0 for width.
-        // for (dims, opened_vals) in dimensions.iter().zip(opened_values) {
-        //     if opened_vals.len() != dims.width {
-        //         return Err(WrongWidth);
-        //     }
-        // }
-
-        // TODO: Disabled for now, CirclePcs sometimes passes a height that's off by 1 bit.
-        let Some(max_height) = dimensions.iter().map(|dim| dim.height).max() else {
-            // dimensions is empty
-            return Err(EmptyBatch);
-        };
-        let log_max_height = log2_ceil_usize(max_height);
-        if proof.len() != log_max_height {
+        let num_layers = Self::num_layers_above(max_height)
+            - self.cap_height_for(dimensions.iter().map(|dims| dims.height));
+        check_cap_len::<_, _, DIGEST_ELEMS, ARITY>(commit, max_height, num_layers)?;
+        if proof.len() != num_layers * (ARITY - 1) {
             return Err(WrongHeight {
                 max_height,
                 num_siblings: proof.len(),
@@ -149,13 +394,7 @@ where
             .sorted_by_key(|(_, dims)| Reverse(dims.height))
             .peekable();
 
-        let Some(mut curr_height_padded) = heights_tallest_first
-            .peek()
-            .map(|x| x.1.height.next_power_of_two())
-        else {
-            // dimensions is empty
-            return Err(EmptyBatch);
-        };
+        let mut curr_height_padded = max_height.next_power_of_two();
 
         let mut root = self.hash.hash_iter_slices(
             heights_tallest_first
@@ -164,17 +403,22 @@ where
                 })
                 .map(|(i, _)| opened_values[i].as_slice()),
         );
+        let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+
+        for layer_siblings in proof.chunks_exact(ARITY - 1) {
+            let position = index % ARITY;
+            let mut siblings = layer_siblings.iter();
+            let children = array::from_fn(|i| {
+                if i == position {
+                    root
+                } else {
+                    *siblings.next().unwrap()
+                }
+            });
 
-        for &sibling in proof {
-            let (left, right) = if index & 1 == 0 {
-                (root, sibling)
-            } else {
-                (sibling, root)
-            };
-
-            root = self.compress.compress([left, right]);
-            index >>= 1;
-            curr_height_padded >>= 1;
+            root = self.compress.compress(children);
+            index /= ARITY;
+            curr_height_padded = curr_height_padded.div_ceil(ARITY);
 
             let next_height = heights_tallest_first
                 .peek()
@@ -187,34 +431,255 @@ where
                         .map(|(i, _)| opened_values[i].as_slice()),
                 );
 
-                root = self.compress.compress([root, next_height_openings_digest]);
+                root = inject::<_, _, DIGEST_ELEMS, ARITY>(
+                    &self.compress,
+                    root,
+                    next_height_openings_digest,
+                    default_digest,
+                );
             }
         }
 
-        if commit == &root {
+        // After walking up to the cap, `index` is the position of the node within the cap.
+        if commit.digests().get(index) == Some(&root) {
             Ok(())
         } else {
-            Err(RootMismatch)
+            Err(RootMismatch { index: leaf_index })
+        }
+    }
+
+    fn verify_multi(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        indices: &[usize],
+        opened_values: &[Vec<Vec<P::Value>>],
+        proofs: &[Self::Proof],
+    ) -> Result<(), Self::Error> {
+        // Check that the openings have the correct shape.
+        check_opening_count(indices, opened_values, proofs)?;
+        for opening in opened_values {
+            check_opened_shape(dimensions, opening)?;
         }
+        let max_height = check_dimensions(dimensions, ARITY)?;
+        if let Some(&index) = indices.iter().find(|&&index| index >= max_height) {
+            return Err(IndexOutOfBounds { max_height, index });
+        }
+        let num_layers = Self::num_layers_above(max_height)
+            - self.cap_height_for(dimensions.iter().map(|dims| dims.height));
+        check_cap_len::<_, _, DIGEST_ELEMS, ARITY>(commit, max_height, num_layers)?;
+
+        let mut heights_tallest_first = dimensions
+            .iter()
+            .enumerate()
+            .sorted_by_key(|(_, dims)| Reverse(dims.height))
+            .peekable();
+        let mut curr_height_padded = max_height.next_power_of_two();
+
+        // Hashes the opened rows of the given matrices at each index, where several indices may
+        // share a node at `layer`. The openings of indices sharing a node must agree.
+        let hash_rows_by_node = |matrices: &[usize], layer: usize| {
+            let mut digests = BTreeMap::new();
+            for (&index, opening) in izip!(indices, opened_values) {
+                let digest = self
+                    .hash
+                    .hash_iter_slices(matrices.iter().map(|&i| opening[i].as_slice()));
+                let node = index >> (layer * Self::LOG_ARITY);
+                if *digests.entry(node).or_insert(digest) != digest {
+                    return Err(RootMismatch { index });
+                }
+            }
+            Ok(digests)
+        };
+
+        let tallest_matrices = heights_tallest_first
+            .peeking_take_while(|(_, dims)| dims.height.next_power_of_two() == curr_height_padded)
+            .map(|(i, _)| i)
+            .collect_vec();
+        let mut nodes = hash_rows_by_node(&tallest_matrices, 0)?;
+        // The siblings of each parent are taken from the proof of the first query whose path
+        // passes through it, as in `open_multi`.
+        let mut siblings = proofs.iter().map(|proof| proof.iter()).collect_vec();
+        let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+
+        for layer in 1..=num_layers {
+            let parents = Self::path_owners(indices, layer);
+            let mut next_nodes = BTreeMap::new();
+            for (&parent, &owner) in &parents {
+                let mut children = [default_digest; ARITY];
+                for (i, child) in children.iter_mut().enumerate() {
+                    *child = match nodes.get(&(parent * ARITY + i)) {
+                        Some(&digest) => digest,
+                        None => *siblings[owner].next().ok_or(WrongHeight {
+                            max_height,
+                            num_siblings: proofs[owner].len(),
+                        })?,
+                    };
+                }
+                next_nodes.insert(parent, self.compress.compress(children));
+            }
+            curr_height_padded = curr_height_padded.div_ceil(ARITY);
+
+            let next_height = heights_tallest_first
+                .peek()
+                .map(|(_, dims)| dims.height)
+                .filter(|h| h.next_power_of_two() == curr_height_padded);
+            if let Some(next_height) = next_height {
+                let next_matrices = heights_tallest_first
+                    .peeking_take_while(|(_, dims)| dims.height == next_height)
+                    .map(|(i, _)| i)
+                    .collect_vec();
+                let injected = hash_rows_by_node(&next_matrices, layer)?;
+                for (node, digest) in &mut next_nodes {
+                    *digest = inject::<_, _, DIGEST_ELEMS, ARITY>(
+                        &self.compress,
+                        *digest,
+                        injected[node],
+                        default_digest,
+                    );
+                }
+            }
+            nodes = next_nodes;
+        }
+
+        if let Some(owner) = siblings
+            .iter_mut()
+            .position(|siblings| siblings.next().is_some())
+        {
+            return Err(WrongHeight {
+                max_height,
+                num_siblings: proofs[owner].len(),
+            });
+        }
+        // The remaining nodes are all in the cap layer.
+        let cap_shift = num_layers * Self::LOG_ARITY;
+        match indices.iter().find(|&&index| {
+            let node = index >> cap_shift;
+            commit.digests().get(node) != nodes.get(&node)
+        }) {
+            Some(&index) => Err(RootMismatch { index }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks that the opened rows match the claimed `dimensions`.
+fn check_opened_shape<T>(
+    dimensions: &[Dimensions],
+    opened_values: &[Vec<T>],
+) -> Result<(), MerkleTreeError> {
+    if dimensions.len() != opened_values.len() {
+        return Err(WrongBatchSize {
+            expected: dimensions.len(),
+            actual: opened_values.len(),
+        });
+    }
+    match izip!(dimensions, opened_values)
+        .enumerate()
+        .find(|(_, (dims, opened_vals))| opened_vals.len() != dims.width)
+    {
+        Some((matrix, (dims, opened_vals))) => Err(WrongWidth {
+            matrix,
+            expected: dims.width,
+            actual: opened_vals.len(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Checks that `dimensions` describe a batch of matrices which can be committed to in a tree of
+/// the given arity, and returns the height of the tallest one.
+fn check_dimensions(dimensions: &[Dimensions], arity: usize) -> Result<usize, MerkleTreeError> {
+    let heights_tallest_first = dimensions
+        .iter()
+        .map(|dims| dims.height)
+        .sorted_by_key(|&height| Reverse(height))
+        .collect_vec();
+    let Some(&max_height) = heights_tallest_first.first() else {
+        return Err(EmptyBatch);
+    };
+    // Mirrors the height properties checked by `MerkleTree::new_with_arity`.
+    let compatible = heights_tallest_first
+        .iter()
+        .tuple_windows()
+        .all(|(curr, next)| curr == next || curr.next_power_of_two() != next.next_power_of_two())
+        && heights_tallest_first.iter().all(|height| {
+            padded_layer_heights(max_height, arity).contains(&height.next_power_of_two())
+        });
+    if compatible {
+        Ok(max_height)
+    } else {
+        Err(IncompatibleHeights)
     }
 }
 
+/// Checks that `commit` has as many digests as the layer `num_layers` layers above the leaves of a
+/// tree of the given arity whose tallest matrix has `max_height` rows.
+fn check_cap_len<F, W, const DIGEST_ELEMS: usize, const ARITY: usize>(
+    commit: &MerkleCap<F, W, DIGEST_ELEMS>,
+    max_height: usize,
+    num_layers: usize,
+) -> Result<(), MerkleTreeError> {
+    let expected = digest_layer_lens::<ARITY>(max_height)
+        .nth(num_layers)
+        .expect("the cap is at most the root");
+    let actual = commit.digests().len();
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(WrongCapLength {
+            max_height,
+            expected,
+            actual,
+        })
+    }
+}
+
+/// Returns the rows of each committed matrix corresponding to `index`, in the sense of
+/// `Mmcs::open_batch`.
+fn open_rows<F: Clone + Send + Sync, W, M: Matrix<F>, const DIGEST_ELEMS: usize, L>(
+    prover_data: &MerkleTree<F, W, M, DIGEST_ELEMS, L>,
+    index: usize,
+) -> Vec<Vec<F>> {
+    let log_max_height = prover_data
+        .leaves
+        .iter()
+        .map(|matrix| log2_ceil_usize(matrix.height()))
+        .max()
+        .unwrap_or_else(|| panic!("No committed matrices?"));
+
+    prover_data
+        .leaves
+        .iter()
+        .map(|matrix| {
+            let log2_height = log2_ceil_usize(matrix.height());
+            let bits_reduced = log_max_height - log2_height;
+            let reduced_index = index >> bits_reduced;
+            matrix.row(reduced_index).collect()
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
+    use alloc::vec::Vec;
 
-    use itertools::Itertools;
+    use itertools::{Itertools, izip};
     use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
     use p3_commit::Mmcs;
     use p3_field::{Field, PrimeCharacteristicRing};
     use p3_matrix::dense::RowMajorMatrix;
     use p3_matrix::{Dimensions, Matrix};
     use p3_symmetric::{
-        CryptographicHasher, PaddingFreeSponge, PseudoCompressionFunction, TruncatedPermutation,
+        CompressionFunctionFromHasher, CryptographicHasher, PaddingFreeSponge,
+        PseudoCompressionFunction, TruncatedPermutation,
     };
+    use p3_util::log2_ceil_usize;
     use rand::rng;
 
     use super::MerkleTreeMmcs;
+    use crate::MerkleTreeError;
 
     type F = BabyBear;
 
@@ -254,7 +719,7 @@ mod tests {
                 compress.compress([hash.hash_item(v[6]), hash.hash_item(v[7])]),
             ]),
         ]);
-        assert_eq!(commit, expected_result);
+        assert_eq!(commit.digests(), [expected_result]);
     }
 
     #[test]
@@ -268,7 +733,7 @@ mod tests {
         let (commit, _) = mmcs.commit(vec![mat.clone()]);
 
         let expected_result = hash.hash_iter(mat.vertically_packed_row(0));
-        assert_eq!(commit, expected_result);
+        assert_eq!(commit.digests(), [expected_result]);
     }
 
     #[test]
@@ -290,7 +755,7 @@ mod tests {
             hash.hash_slice(&[F::ZERO, F::ONE]),
             hash.hash_slice(&[F::TWO, F::ONE]),
         ]);
-        assert_eq!(commit, expected_result);
+        assert_eq!(commit.digests(), [expected_result]);
     }
 
     #[test]
@@ -317,7 +782,7 @@ mod tests {
             ]),
             compress.compress([hash.hash_slice(&[F::TWO, F::TWO]), default_digest]),
         ]);
-        assert_eq!(commit, expected_result);
+        assert_eq!(commit.digests(), [expected_result]);
     }
 
     #[test]
@@ -405,7 +870,7 @@ mod tests {
             ]),
         ]);
 
-        assert_eq!(commit, expected_result);
+        assert_eq!(commit.digests(), [expected_result]);
 
         let (opened_values, _proof) = mmcs.open_batch(2, &prover_data);
         assert_eq!(
@@ -556,4 +1021,441 @@ mod tests {
         mmcs.verify_batch(&commit, &dims, 17, &opened_values, &proof)
             .expect("expected verification to succeed");
     }
+
+    #[test]
+    fn multi_opening() {
+        let perm = Perm::new_from_rng_128(&mut rng());
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        // mats with 64, 32 and 8 rows
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng(), 64, 4),
+            RowMajorMatrix::<F>::rand(&mut rng(), 32, 3),
+            RowMajorMatrix::<F>::rand(&mut rng(), 8, 2),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        // Includes a duplicate and two neighbouring leaves.
+        let indices = [5, 40, 5, 4, 63, 17];
+        let (opened_values, proofs) = mmcs.open_multi(&indices, &prover_data);
+        for (&index, values) in indices.iter().zip(&opened_values) {
+            assert_eq!(values, &mmcs.open_batch(index, &prover_data).0);
+        }
+        mmcs.verify_multi(&commit, &dims, &indices, &opened_values, &proofs)
+            .expect("expected verification to succeed");
+
+        // Shared siblings are only sent once, and a repeated index needs no siblings at all.
+        let separate_len: usize = indices
+            .iter()
+            .map(|&index| mmcs.open_batch(index, &prover_data).1.len())
+            .sum();
+        assert!(proofs.iter().map(Vec::len).sum::<usize>() < separate_len);
+        assert!(proofs[2].is_empty());
+
+        let mut tampered_values = opened_values.clone();
+        tampered_values[1][1][0] += F::ONE;
+        mmcs.verify_multi(&commit, &dims, &indices, &tampered_values, &proofs)
+            .expect_err("expected verification to fail");
+
+        let mut tampered_proofs = proofs.clone();
+        tampered_proofs[1][0][0] += F::ONE;
+        mmcs.verify_multi(&commit, &dims, &indices, &opened_values, &tampered_proofs)
+            .expect_err("expected verification to fail");
+
+        let mut short_proofs = proofs.clone();
+        short_proofs[1].pop();
+        mmcs.verify_multi(&commit, &dims, &indices, &opened_values, &short_proofs)
+            .expect_err("expected verification to fail");
+
+        // Siblings can't be moved between the proofs of different queries.
+        let mut moved_proofs = proofs.clone();
+        let sibling = moved_proofs[1].pop().unwrap();
+        moved_proofs[3].push(sibling);
+        mmcs.verify_multi(&commit, &dims, &indices, &opened_values, &moved_proofs)
+            .expect_err("expected verification to fail");
+
+        assert!(matches!(
+            mmcs.verify_multi(&commit, &dims, &indices, &opened_values, &proofs[1..]),
+            Err(MerkleTreeError::WrongBatchSize {
+                expected: 6,
+                actual: 5
+            })
+        ));
+    }
+
+    /// An MMCS which only implements the required methods, so it uses the default multi-opening.
+    #[derive(Clone)]
+    struct BatchOnlyMmcs(MyMmcs);
+
+    impl Mmcs<F> for BatchOnlyMmcs {
+        type ProverData<M> = <MyMmcs as Mmcs<F>>::ProverData<M>;
+        type Commitment = <MyMmcs as Mmcs<F>>::Commitment;
+        type Proof = <MyMmcs as Mmcs<F>>::Proof;
+        type Error = MerkleTreeError;
+        type MatrixStorage = Vec<F>;
+
+        fn store_matrix(&self, matrix: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+            matrix
+        }
+
+        fn commit<M: Matrix<F>>(&self, inputs: Vec<M>) -> (Self::Commitment, Self::ProverData<M>) {
+            self.0.commit(inputs)
+        }
+
+        fn open_batch<M: Matrix<F>>(
+            &self,
+            index: usize,
+            prover_data: &Self::ProverData<M>,
+        ) -> (Vec<Vec<F>>, Self::Proof) {
+            self.0.open_batch(index, prover_data)
+        }
+
+        fn get_matrices<'a, M: Matrix<F>>(
+            &self,
+            prover_data: &'a Self::ProverData<M>,
+        ) -> Vec<&'a M> {
+            self.0.get_matrices(prover_data)
+        }
+
+        fn verify_batch(
+            &self,
+            commit: &Self::Commitment,
+            dimensions: &[Dimensions],
+            index: usize,
+            opened_values: &[Vec<F>],
+            proof: &Self::Proof,
+        ) -> Result<(), Self::Error> {
+            self.0
+                .verify_batch(commit, dimensions, index, opened_values, proof)
+        }
+    }
+
+    #[test]
+    fn default_multi_opening() {
+        let perm = Perm::new_from_rng_128(&mut rng());
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = BatchOnlyMmcs(MyMmcs::new(hash, compress));
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng(), 32, 4),
+            RowMajorMatrix::<F>::rand(&mut rng(), 8, 2),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        let indices = [3, 30, 3];
+        let (opened_values, proofs) = mmcs.open_multi(&indices, &prover_data);
+        for (&index, values, proof) in izip!(&indices, &opened_values, &proofs) {
+            let (batch_values, batch_proof) = mmcs.open_batch(index, &prover_data);
+            assert_eq!(values, &batch_values);
+            assert_eq!(proof, &batch_proof);
+        }
+        mmcs.verify_multi(&commit, &dims, &indices, &opened_values, &proofs)
+            .expect("expected verification to succeed");
+
+        let mut tampered_values = opened_values.clone();
+        tampered_values[2][1][0] += F::ONE;
+        mmcs.verify_multi(&commit, &dims, &indices, &tampered_values, &proofs)
+            .expect_err("expected verification to fail");
+
+        assert!(matches!(
+            mmcs.verify_multi(&commit, &dims, &indices, &opened_values[..2], &proofs),
+            Err(MerkleTreeError::WrongBatchSize {
+                expected: 3,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn merkle_cap() {
+        let perm = Perm::new_from_rng_128(&mut rng());
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash.clone(), compress.clone());
+        let capped_mmcs = MyMmcs::new_with_cap_height(hash, compress, 2);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng(), 64, 4),
+            RowMajorMatrix::<F>::rand(&mut rng(), 16, 3),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats.clone());
+        let (cap, capped_prover_data) = capped_mmcs.commit(mats);
+        assert_eq!(cap.digests().len(), 4);
+
+        // The cap compresses to the root of the uncapped tree.
+        let cap_digests = cap.digests();
+        let root = capped_mmcs.compress.compress([
+            capped_mmcs
+                .compress
+                .compress([cap_digests[0], cap_digests[1]]),
+            capped_mmcs
+                .compress
+                .compress([cap_digests[2], cap_digests[3]]),
+        ]);
+        assert_eq!(commit.digests(), [root]);
+
+        for index in [0, 21, 63] {
+            let (opened_values, proof) = capped_mmcs.open_batch(index, &capped_prover_data);
+            assert_eq!(
+                proof.len(),
+                mmcs.open_batch(index, &prover_data).1.len() - 2
+            );
+            capped_mmcs
+                .verify_batch(&cap, &dims, index, &opened_values, &proof)
+                .expect("expected verification to succeed");
+            mmcs.verify_batch(&commit, &dims, index, &opened_values, &proof)
+                .expect_err("expected verification to fail");
+        }
+
+        let indices = [3, 7, 40, 41];
+        let (opened_values, proof) = capped_mmcs.open_multi(&indices, &capped_prover_data);
+        capped_mmcs
+            .verify_multi(&cap, &dims, &indices, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        // A cap with extra digests is rejected, even though every opened path ends in it.
+        let mut long_cap = cap.digests().to_vec();
+        long_cap.push(long_cap[0]);
+        let long_cap = long_cap.into();
+        assert!(matches!(
+            capped_mmcs.verify_multi(&long_cap, &dims, &indices, &opened_values, &proof),
+            Err(MerkleTreeError::WrongCapLength {
+                max_height: 64,
+                expected: 4,
+                actual: 5
+            })
+        ));
+        let (opened_values, proof) = capped_mmcs.open_batch(3, &capped_prover_data);
+        assert!(matches!(
+            capped_mmcs.verify_batch(&long_cap, &dims, 3, &opened_values, &proof),
+            Err(MerkleTreeError::WrongCapLength { .. })
+        ));
+
+        // A batch containing a small matrix lowers the cap height, so that the matrix is still
+        // injected below the cap.
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng(), 64, 4),
+            RowMajorMatrix::<F>::rand(&mut rng(), 2, 3),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (cap, capped_prover_data) = capped_mmcs.commit(mats);
+        assert_eq!(cap.digests().len(), 2);
+        let (opened_values, proof) = capped_mmcs.open_batch(33, &capped_prover_data);
+        assert_eq!(proof.len(), 5);
+        capped_mmcs
+            .verify_batch(&cap, &dims, 33, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
+
+    #[test]
+    fn malformed_openings_rejected() {
+        let perm = Perm::new_from_rng_128(&mut rng());
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng(), 32, 4),
+            RowMajorMatrix::<F>::rand(&mut rng(), 8, 3),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+        let (opened_values, proof) = mmcs.open_batch(13, &prover_data);
+        mmcs.verify_batch(&commit, &dims, 13, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        // A row with a value missing.
+        let mut short_row = opened_values.clone();
+        short_row[1].pop();
+        assert!(matches!(
+            mmcs.verify_batch(&commit, &dims, 13, &short_row, &proof),
+            Err(MerkleTreeError::WrongWidth { matrix: 1, .. })
+        ));
+
+        // A row with an extra value.
+        let mut long_row = opened_values.clone();
+        long_row[0].push(F::ONE);
+        assert!(matches!(
+            mmcs.verify_batch(&commit, &dims, 13, &long_row, &proof),
+            Err(MerkleTreeError::WrongWidth { matrix: 0, .. })
+        ));
+
+        // A missing matrix.
+        assert!(matches!(
+            mmcs.verify_batch(&commit, &dims, 13, &opened_values[..1], &proof),
+            Err(MerkleTreeError::WrongBatchSize {
+                expected: 2,
+                actual: 1
+            })
+        ));
+
+        // An index past the end of the tallest matrix.
+        assert!(matches!(
+            mmcs.verify_batch(&commit, &dims, 32 + 13, &opened_values, &proof),
+            Err(MerkleTreeError::IndexOutOfBounds { .. })
+        ));
+
+        // Heights which no batch could have been committed with.
+        let bad_dims = [
+            dims[0],
+            Dimensions {
+                width: 3,
+                height: 30,
+            },
+        ];
+        assert!(matches!(
+            mmcs.verify_batch(&commit, &bad_dims, 13, &opened_values, &proof),
+            Err(MerkleTreeError::IncompatibleHeights)
+        ));
+
+        // The same checks apply to each opening of a multiproof.
+        let indices = [13, 30];
+        let (opened_values, proof) = mmcs.open_multi(&indices, &prover_data);
+        mmcs.verify_multi(&commit, &dims, &indices, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        let mut short_row = opened_values.clone();
+        short_row[1][0].pop();
+        assert!(matches!(
+            mmcs.verify_multi(&commit, &dims, &indices, &short_row, &proof),
+            Err(MerkleTreeError::WrongWidth { matrix: 0, .. })
+        ));
+
+        assert!(matches!(
+            mmcs.verify_multi(&commit, &dims, &[13, 32 + 30], &opened_values, &proof),
+            Err(MerkleTreeError::IndexOutOfBounds { .. })
+        ));
+    }
+
+    /// Compresses `ARITY` digests at once by hashing them with a sponge.
+    type WideCompress<const ARITY: usize> = CompressionFunctionFromHasher<MyHash, ARITY, 8>;
+    type WideMmcs<const ARITY: usize> = MerkleTreeMmcs<
+        <F as Field>::Packing,
+        <F as Field>::Packing,
+        MyHash,
+        WideCompress<ARITY>,
+        8,
+        ARITY,
+    >;
+
+    #[test]
+    fn commit_single_arity_4() {
+        let perm = Perm::new_from_rng_128(&mut rng());
+        let hash = MyHash::new(perm);
+        let compress = WideCompress::<4>::new(hash.clone());
+        let mmcs = WideMmcs::<4>::new(hash.clone(), compress.clone());
+
+        let v = (0..16).map(F::from_u32).collect_vec();
+        let (commit, _) = mmcs.commit_vec(v.clone());
+
+        let leaves = v.iter().map(|&x| hash.hash_item(x)).collect_vec();
+        let layer_1: [_; 4] = core::array::from_fn(|i| {
+            compress.compress(core::array::from_fn(|j| leaves[4 * i + j]))
+        });
+        assert_eq!(commit.digests(), [compress.compress(layer_1)]);
+    }
+
+    fn open_and_verify_with_arity<const ARITY: usize>(heights: &[usize], cap_height: usize) {
+        let perm = Perm::new_from_rng_128(&mut rng());
+        let hash = MyHash::new(perm);
+        let compress = WideCompress::<ARITY>::new(hash.clone());
+        let mmcs = WideMmcs::<ARITY>::new_with_cap_height(hash, compress, cap_height);
+
+        let mats = heights
+            .iter()
+            .enumerate()
+            .map(|(i, &height)| RowMajorMatrix::<F>::rand(&mut rng(), height, i + 1))
+            .collect_vec();
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        let max_height = heights[0];
+        let num_layers = log2_ceil_usize(max_height).div_ceil(ARITY.ilog2() as usize);
+        assert_eq!(commit.digests().len(), ARITY.pow(cap_height as u32));
+
+        for index in [0, max_height / 3, max_height - 1] {
+            let (opened_values, proof) = mmcs.open_batch(index, &prover_data);
+            assert_eq!(proof.len(), (num_layers - cap_height) * (ARITY - 1));
+            mmcs.verify_batch(&commit, &dims, index, &opened_values, &proof)
+                .expect("expected verification to succeed");
+
+            let mut tampered_proof = proof.clone();
+            tampered_proof[ARITY - 2][0] += F::ONE;
+            mmcs.verify_batch(&commit, &dims, index, &opened_values, &tampered_proof)
+                .expect_err("expected verification to fail");
+        }
+
+        let indices = [1, 2, max_height / 2, max_height - 1, 1];
+        let (opened_values, proof) = mmcs.open_multi(&indices, &prover_data);
+        mmcs.verify_multi(&commit, &dims, &indices, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        let mut tampered_values = opened_values.clone();
+        tampered_values[2][0][0] += F::ONE;
+        mmcs.verify_multi(&commit, &dims, &indices, &tampered_values, &proof)
+            .expect_err("expected verification to fail");
+    }
+
+    #[test]
+    fn higher_arity() {
+        // Each height is the tallest one divided by a power of the arity, rounding up to 1.
+        open_and_verify_with_arity::<4>(&[64, 16, 4, 1], 0);
+        open_and_verify_with_arity::<4>(&[256, 64], 2);
+        open_and_verify_with_arity::<4>(&[40, 10, 3], 1);
+        open_and_verify_with_arity::<4>(&[32, 8, 2], 0);
+        open_and_verify_with_arity::<8>(&[512, 64, 8], 1);
+        open_and_verify_with_arity::<8>(&[100, 13, 2], 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn higher_arity_incompatible_heights() {
+        let perm = Perm::new_from_rng_128(&mut rng());
+        let hash = MyHash::new(perm);
+        let compress = WideCompress::<4>::new(hash.clone());
+        let mmcs = WideMmcs::<4>::new(hash, compress);
+
+        // A matrix with 32 rows would have to be injected between two layers.
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng(), 64, 2),
+            RowMajorMatrix::<F>::rand(&mut rng(), 32, 2),
+        ];
+        let _ = mmcs.commit(mats);
+    }
+
+    #[test]
+    fn higher_arity_rejects_incompatible_dimensions() {
+        let perm = Perm::new_from_rng_128(&mut rng());
+        let hash = MyHash::new(perm);
+        let compress = WideCompress::<4>::new(hash.clone());
+        let mmcs = WideMmcs::<4>::new(hash, compress);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng(), 64, 2),
+            RowMajorMatrix::<F>::rand(&mut rng(), 16, 2),
+        ];
+        let (commit, prover_data) = mmcs.commit(mats);
+        let (opened_values, proof) = mmcs.open_batch(5, &prover_data);
+
+        let bad_dims = [
+            Dimensions {
+                width: 2,
+                height: 64,
+            },
+            Dimensions {
+                width: 2,
+                height: 32,
+            },
+        ];
+        assert!(matches!(
+            mmcs.verify_batch(&commit, &bad_dims, 5, &opened_values, &proof),
+            Err(MerkleTreeError::IncompatibleHeights)
+        ));
+    }
 }
@@ -0,0 +1,239 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter;
+
+use p3_field::PackedValue;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::{Dimensions, Matrix};
+use p3_symmetric::{CryptographicHasher, MerkleCap, PseudoCompressionFunction};
+
+use crate::merkle_tree::{build_digest_layers, first_layer_len_padded, hash_rows};
+use crate::{MerkleTree, MerkleTreeMmcs};
+
+/// An in-progress commitment to a single matrix whose rows are supplied in chunks, as created by
+/// `MerkleTreeMmcs::commit_streaming`.
+///
+/// Rows are hashed as they are absorbed, so only their digests are kept. Once every row has been
+/// absorbed, the resulting commitment is the same as that of `Mmcs::commit` on the full matrix.
+pub struct MerkleTreeStreamingCommit<
+    'a,
+    P,
+    PW: PackedValue,
+    H,
+    C,
+    const DIGEST_ELEMS: usize,
+    const ARITY: usize,
+> {
+    mmcs: &'a MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>,
+    dimensions: Dimensions,
+    first_layer: Vec<[PW::Value; DIGEST_ELEMS]>,
+    rows_absorbed: usize,
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>
+        + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
+        + Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>
+        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>
+        + Sync,
+{
+    /// Starts a commitment to a single matrix with the given dimensions, whose rows will be
+    /// supplied in order by `MerkleTreeStreamingCommit::absorb`.
+    pub fn commit_streaming(
+        &self,
+        dimensions: Dimensions,
+    ) -> MerkleTreeStreamingCommit<'_, P, PW, H, C, DIGEST_ELEMS, ARITY> {
+        assert!(dimensions.height > 0, "cannot commit to an empty matrix");
+        assert_eq!(P::WIDTH, PW::WIDTH, "Packing widths must match");
+        let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+        MerkleTreeStreamingCommit {
+            mmcs: self,
+            dimensions,
+            first_layer: vec![default_digest; first_layer_len_padded::<ARITY>(dimensions.height)],
+            rows_absorbed: 0,
+        }
+    }
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTreeStreamingCommit<'_, P, PW, H, C, DIGEST_ELEMS, ARITY>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>
+        + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
+        + Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>
+        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>
+        + Sync,
+{
+    /// Hashes the next `chunk.height()` rows of the matrix.
+    pub fn absorb<M: Matrix<P::Value>>(&mut self, chunk: &M) {
+        assert_eq!(chunk.width(), self.dimensions.width, "wrong chunk width");
+        let end = self.rows_absorbed + chunk.height();
+        assert!(
+            end <= self.dimensions.height,
+            "absorbed more rows than the matrix has"
+        );
+        hash_rows::<P, PW, _, _, DIGEST_ELEMS>(
+            &self.mmcs.hash,
+            &[chunk],
+            &mut self.first_layer[self.rows_absorbed..end],
+        );
+        self.rows_absorbed = end;
+    }
+
+    /// Returns the number of rows absorbed so far.
+    pub const fn rows_absorbed(&self) -> usize {
+        self.rows_absorbed
+    }
+
+    /// Returns the commitment to the matrix, all of whose rows must have been absorbed.
+    pub fn finish(self) -> MerkleCap<P::Value, PW::Value, DIGEST_ELEMS> {
+        let height = self.dimensions.height;
+        let cap_height = self.mmcs.cap_height_for([height]);
+        let tree = MerkleTree::<_, _, RowMajorMatrix<P::Value>, DIGEST_ELEMS>::from_digest_layers(
+            vec![],
+            self.build_digest_layers(),
+        );
+        tree.cap(cap_height)
+    }
+
+    /// Like `finish`, but also returns prover data for opening the commitment, which keeps
+    /// `matrix` as the committed matrix.
+    ///
+    /// `matrix` must hold the absorbed rows, which is only checked as far as its dimensions.
+    #[allow(clippy::type_complexity)]
+    pub fn finish_with_matrix<M: Matrix<P::Value>>(
+        self,
+        matrix: M,
+    ) -> (
+        MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>,
+        MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS>,
+    ) {
+        assert_eq!(
+            matrix.dimensions(),
+            self.dimensions,
+            "wrong matrix dimensions"
+        );
+        let cap_height = self.mmcs.cap_height_for([matrix.height()]);
+        let tree = MerkleTree::from_digest_layers(vec![matrix], self.build_digest_layers());
+        (tree.cap(cap_height), tree)
+    }
+
+    fn build_digest_layers(self) -> Vec<Vec<[PW::Value; DIGEST_ELEMS]>> {
+        assert_eq!(
+            self.rows_absorbed, self.dimensions.height,
+            "not all rows have been absorbed"
+        );
+        build_digest_layers::<P, PW, H, C, RowMajorMatrix<P::Value>, _, DIGEST_ELEMS, ARITY>(
+            &self.mmcs.hash,
+            &self.mmcs.compress,
+            self.first_layer,
+            self.dimensions.height,
+            iter::empty(),
+            |layer| layer,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_commit::Mmcs;
+    use p3_field::Field;
+    use p3_matrix::Matrix;
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_symmetric::{CompressionFunctionFromHasher, PaddingFreeSponge, TruncatedPermutation};
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use crate::MerkleTreeMmcs;
+
+    type F = BabyBear;
+
+    type Perm = Poseidon2BabyBear<16>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type MyMmcs =
+        MerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, MyHash, MyCompress, 8>;
+    type WideCompress = CompressionFunctionFromHasher<MyHash, 4, 8>;
+    type WideMmcs =
+        MerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, MyHash, WideCompress, 8, 4>;
+
+    fn perm() -> Perm {
+        Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1))
+    }
+
+    /// Splits `mat` into chunks of `chunk_height` rows, the last of which may be shorter.
+    fn chunks(mat: &RowMajorMatrix<F>, chunk_height: usize) -> Vec<RowMajorMatrix<F>> {
+        mat.values
+            .chunks(chunk_height * mat.width)
+            .map(|values| RowMajorMatrix::new(values.to_vec(), mat.width))
+            .collect()
+    }
+
+    #[test]
+    fn matches_commit() {
+        let mmcs = MyMmcs::new_with_cap_height(MyHash::new(perm()), MyCompress::new(perm()), 2);
+        let mut rng = SmallRng::seed_from_u64(2);
+        for (height, chunk_height) in [(1, 1), (37, 5), (64, 16), (100, 100)] {
+            let mat = RowMajorMatrix::<F>::rand(&mut rng, height, 7);
+            let (commit, _) = mmcs.commit_matrix(mat.clone());
+
+            let mut streaming = mmcs.commit_streaming(mat.dimensions());
+            for chunk in chunks(&mat, chunk_height) {
+                streaming.absorb(&chunk);
+            }
+            assert_eq!(streaming.rows_absorbed(), height);
+            assert_eq!(streaming.finish(), commit);
+        }
+    }
+
+    #[test]
+    fn open_streamed_commitment() {
+        let hash = MyHash::new(perm());
+        let mmcs = WideMmcs::new_with_cap_height(hash.clone(), WideCompress::new(hash), 1);
+        let mut rng = SmallRng::seed_from_u64(3);
+        let mat = RowMajorMatrix::<F>::rand(&mut rng, 50, 3);
+        let dims = [mat.dimensions()];
+
+        let mut streaming = mmcs.commit_streaming(mat.dimensions());
+        for chunk in chunks(&mat, 9) {
+            streaming.absorb(&chunk);
+        }
+        let (commit, prover_data) = streaming.finish_with_matrix(mat.clone());
+        assert_eq!(commit, mmcs.commit_matrix(mat).0);
+
+        let (opened_values, proof) = mmcs.open_batch(42, &prover_data);
+        mmcs.verify_batch(&commit, &dims, 42, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
+
+    #[test]
+    #[should_panic(expected = "not all rows have been absorbed")]
+    fn missing_rows() {
+        let mmcs = MyMmcs::new(MyHash::new(perm()), MyCompress::new(perm()));
+        let mat = RowMajorMatrix::<F>::rand(&mut SmallRng::seed_from_u64(4), 16, 2);
+        let mut streaming = mmcs.commit_streaming(mat.dimensions());
+        streaming.absorb(&chunks(&mat, 8)[0]);
+        streaming.finish();
+    }
+
+    #[test]
+    #[should_panic(expected = "absorbed more rows than the matrix has")]
+    fn too_many_rows() {
+        let mmcs = MyMmcs::new(MyHash::new(perm()), MyCompress::new(perm()));
+        let mat = RowMajorMatrix::<F>::rand(&mut SmallRng::seed_from_u64(5), 16, 2);
+        let mut streaming = mmcs.commit_streaming(mat.dimensions());
+        streaming.absorb(&mat);
+        streaming.absorb(&chunks(&mat, 8)[0]);
+    }
+}
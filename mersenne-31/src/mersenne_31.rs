@@ -11,9 +11,9 @@ use num_bigint::BigUint;
 use p3_field::exponentiation::exp_1717986917;
 use p3_field::integers::QuotientMap;
 use p3_field::{
-    Field, InjectiveMonomial, Packable, PermutationMonomial, PrimeCharacteristicRing, PrimeField,
-    PrimeField32, PrimeField64, halve_u32, quotient_map_large_iint, quotient_map_large_uint,
-    quotient_map_small_int,
+    Field, InjectiveMonomial, Packable, PermutationMonomial, Pod, PrimeCharacteristicRing,
+    PrimeField, PrimeField32, PrimeField64, halve_u32, quotient_map_large_iint,
+    quotient_map_large_uint, quotient_map_small_int,
 };
 use rand::Rng;
 use rand::distr::{Distribution, StandardUniform};
@@ -83,6 +83,9 @@ impl Eq for Mersenne31 {}
 
 impl Packable for Mersenne31 {}
 
+// SAFETY: `Mersenne31` is a repr(transparent) wrapper around a plain integer.
+unsafe impl Pod for Mersenne31 {}
+
 impl Hash for Mersenne31 {
     fn hash<H: Hasher>(&self, state: &mut H) {
         state.write_u32(self.to_unique_u32());
@@ -12,8 +12,8 @@ use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 use num_bigint::BigUint;
 use p3_field::integers::QuotientMap;
 use p3_field::{
-    Field, InjectiveMonomial, Packable, PermutationMonomial, PrimeCharacteristicRing, PrimeField,
-    PrimeField32, PrimeField64, TwoAdicField, quotient_map_small_int,
+    Field, InjectiveMonomial, Packable, PermutationMonomial, Pod, PrimeCharacteristicRing,
+    PrimeField, PrimeField32, PrimeField64, TwoAdicField, quotient_map_small_int,
 };
 use rand::Rng;
 use rand::distr::{Distribution, StandardUniform};
@@ -162,6 +162,9 @@ impl<'de, FP: FieldParameters> Deserialize<'de> for MontyField31<FP> {
 
 impl<FP: FieldParameters> Packable for MontyField31<FP> {}
 
+// SAFETY: `MontyField31` is a repr(transparent) wrapper around a plain integer.
+unsafe impl<FP: FieldParameters> Pod for MontyField31<FP> {}
+
 impl<FP: FieldParameters> PrimeCharacteristicRing for MontyField31<FP> {
     type PrimeSubfield = Self;
 
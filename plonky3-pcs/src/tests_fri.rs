@@ -84,6 +84,9 @@ mod tests {
             log_final_poly_len: 0,
             num_queries,
             proof_of_work_bits: 16,
+            commit_proof_of_work_bits: 0,
+            log_folding_arity: 1,
+            use_multiproofs: false,
             mmcs: challenge_mmcs,
         };
 
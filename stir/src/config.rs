@@ -0,0 +1,71 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::min;
+
+#[derive(Debug)]
+pub struct StirConfig<M> {
+    /// The log of the inverse rate of the initial codeword.
+    pub log_blowup: usize,
+    /// The log of the folding factor `k`. Each round divides the degree bound by `k`, while the
+    /// evaluation domain only shrinks by a factor of 2, so the rate improves from round to round.
+    /// Must be at least 1.
+    pub log_folding_factor: usize,
+    /// Folding stops once the degree bound is at most `2^log_final_poly_len`, at which point the
+    /// prover sends the remaining polynomial in the clear.
+    pub log_final_poly_len: usize,
+    /// The number of queries to the initial codeword. Later codewords have a lower rate, so fewer
+    /// queries to them achieve the same conjectured security.
+    pub num_queries: usize,
+    /// The number of out-of-domain samples in each round.
+    pub num_ood_samples: usize,
+    /// The number of proof of work bits required before each round's queries are sampled.
+    pub proof_of_work_bits: usize,
+    pub mmcs: M,
+}
+
+impl<M> StirConfig<M> {
+    pub const fn blowup(&self) -> usize {
+        1 << self.log_blowup
+    }
+
+    /// Returns the log of the folding factor used by each fold of a polynomial with degree bound
+    /// `2^log_degree`. The last fold produces the final polynomial, and the others produce the
+    /// codewords committed in each round.
+    ///
+    /// Every fold uses the configured factor, except that the last one stops exactly at the final
+    /// polynomial's degree bound. If the input polynomial is already small enough, there is a
+    /// single trivial fold.
+    pub fn log_folding_factors(&self, log_degree: usize) -> Vec<usize> {
+        assert!(
+            self.log_folding_factor > 0,
+            "log_folding_factor must be positive"
+        );
+        if log_degree <= self.log_final_poly_len {
+            return vec![0];
+        }
+        let mut log_folding_factors = vec![];
+        let mut log_degree = log_degree;
+        while log_degree > self.log_final_poly_len {
+            let log_folding_factor = min(
+                self.log_folding_factor,
+                log_degree - self.log_final_poly_len,
+            );
+            log_folding_factors.push(log_folding_factor);
+            log_degree -= log_folding_factor;
+        }
+        log_folding_factors
+    }
+
+    /// Returns the number of queries made to a codeword of inverse rate `2^log_inv_rate`, so that
+    /// each query set matches the conjectured security of the queries to the initial codeword.
+    pub const fn num_queries_for_log_inv_rate(&self, log_inv_rate: usize) -> usize {
+        (self.num_queries * self.log_blowup).div_ceil(log_inv_rate)
+    }
+
+    /// Returns the soundness bits of this STIR instance based on the
+    /// [ethSTARK](https://eprint.iacr.org/2021/582) conjecture, as applied to the queries of each
+    /// round.
+    pub const fn conjectured_soundness_bits(&self) -> usize {
+        self.log_blowup * self.num_queries + self.proof_of_work_bits
+    }
+}
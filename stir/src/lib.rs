@@ -0,0 +1,17 @@
+//! An implementation of the [STIR](https://eprint.iacr.org/2024/390) low-degree test, along with
+//! a polynomial commitment scheme built on it.
+
+#![no_std]
+
+extern crate alloc;
+
+mod config;
+mod pcs;
+mod proof;
+pub mod prover;
+mod utils;
+pub mod verifier;
+
+pub use config::*;
+pub use pcs::*;
+pub use proof::*;
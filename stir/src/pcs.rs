@@ -0,0 +1,269 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use itertools::{Itertools, izip};
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::{
+    Mmcs, OpenedValues, Pcs, PolynomialSpace, StoredMatrix, TwoAdicMultiplicativeCoset,
+};
+use p3_dft::TwoAdicSubgroupDft;
+use p3_field::{ExtensionField, TwoAdicField};
+use p3_fri::{BatchOpening, in_lde_domain, open_and_reduce, open_input_batches, verify_and_reduce};
+use p3_matrix::Matrix;
+use p3_matrix::bitrev::{BitReversedMatrixView, BitReversibleMatrix};
+use p3_matrix::dense::{DenseMatrix, RowMajorMatrix};
+use p3_util::log2_strict_usize;
+
+use crate::verifier::{self, StirError};
+use crate::{StirConfig, StirProof, prover};
+
+/// A polynomial commitment scheme over two-adic cosets which batches its opening claims in the
+/// same way as `TwoAdicFriPcs`, but proves the proximity of the resulting codewords with STIR.
+#[derive(Debug)]
+pub struct StirPcs<Val, Dft, InputMmcs, StirMmcs> {
+    dft: Dft,
+    mmcs: InputMmcs,
+    stir: StirConfig<StirMmcs>,
+    _phantom: PhantomData<Val>,
+}
+
+impl<Val, Dft, InputMmcs, StirMmcs> StirPcs<Val, Dft, InputMmcs, StirMmcs> {
+    pub const fn new(dft: Dft, mmcs: InputMmcs, stir: StirConfig<StirMmcs>) -> Self {
+        Self {
+            dft,
+            mmcs,
+            stir,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<Val, Dft, InputMmcs, StirMmcs, Challenge, Challenger> Pcs<Challenge, Challenger>
+    for StirPcs<Val, Dft, InputMmcs, StirMmcs>
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val>,
+    InputMmcs: Mmcs<Val>,
+    StirMmcs: Mmcs<Challenge>,
+    Challenge: TwoAdicField + ExtensionField<Val>,
+    Challenger:
+        FieldChallenger<Val> + CanObserve<StirMmcs::Commitment> + GrindingChallenger<Witness = Val>,
+{
+    type Domain = TwoAdicMultiplicativeCoset<Val>;
+    type Commitment = InputMmcs::Commitment;
+    type ProverData = InputMmcs::ProverData<StoredMatrix<Val, InputMmcs>>;
+    type EvaluationsOnDomain<'a> = BitReversedMatrixView<DenseMatrix<Val, &'a [Val]>>;
+    /// One STIR proof for each distinct LDE height, in descending order of height.
+    type Proof = Vec<StirProof<Challenge, StirMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>>;
+    type Error = StirError<StirMmcs::Error, InputMmcs::Error>;
+
+    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
+        let log_n = log2_strict_usize(degree);
+        TwoAdicMultiplicativeCoset {
+            log_n,
+            shift: Val::ONE,
+        }
+    }
+
+    fn commit(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let ldes: Vec<_> = evaluations
+            .into_iter()
+            .map(|(domain, evals)| {
+                assert_eq!(domain.size(), evals.height());
+                let shift = Val::GENERATOR / domain.shift;
+                // Commit to the bit-reversed LDE.
+                let lde = self
+                    .dft
+                    .coset_lde_batch(evals, self.stir.log_blowup, shift)
+                    .bit_reverse_rows()
+                    .to_row_major_matrix();
+                self.mmcs.store_matrix(lde)
+            })
+            .collect();
+
+        self.mmcs.commit(ldes)
+    }
+
+    fn commit_coeffs(
+        &self,
+        coeffs: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let ldes: Vec<_> = coeffs
+            .into_iter()
+            .map(|(domain, mut coeffs)| {
+                assert_eq!(domain.size(), coeffs.height());
+                coeffs.pad_to_height(domain.size() << self.stir.log_blowup, Val::ZERO);
+                let lde = self
+                    .dft
+                    .coset_dft_batch(coeffs, Val::GENERATOR)
+                    .bit_reverse_rows()
+                    .to_row_major_matrix();
+                self.mmcs.store_matrix(lde)
+            })
+            .collect();
+
+        self.mmcs.commit(ldes)
+    }
+
+    fn get_evaluations_on_domain<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+        domain: Self::Domain,
+    ) -> Self::EvaluationsOnDomain<'a> {
+        assert_eq!(domain.shift, Val::GENERATOR);
+        let lde = self.mmcs.get_matrices(prover_data)[idx];
+        assert!(lde.height() >= domain.size());
+        lde.split_rows(domain.size()).0.bit_reverse_rows()
+    }
+
+    fn open(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<
+                // points to open
+                Vec<Challenge>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        let (all_opened_values, reduced_codewords) =
+            open_and_reduce(&self.mmcs, self.stir.log_blowup, &rounds, challenger);
+        let log_global_max_height = log2_strict_usize(reduced_codewords[0].len());
+        let prover_data = rounds.iter().map(|(data, _)| *data).collect_vec();
+
+        let proofs = reduced_codewords
+            .into_iter()
+            .map(|codeword| {
+                let bits_reduced = log_global_max_height - log2_strict_usize(codeword.len());
+                prover::prove(&self.stir, codeword, challenger, |index| {
+                    open_input_batches(
+                        &self.mmcs,
+                        &prover_data,
+                        log_global_max_height,
+                        index << bits_reduced,
+                    )
+                })
+            })
+            .collect();
+
+        (all_opened_values, proofs)
+    }
+
+    fn verify(
+        &self,
+        // For each round:
+        rounds: Vec<(
+            Self::Commitment,
+            // for each matrix:
+            Vec<(
+                // its domain,
+                Self::Domain,
+                // for each point:
+                Vec<(
+                    // the point,
+                    Challenge,
+                    // values at the point
+                    Vec<Challenge>,
+                )>,
+            )>,
+        )>,
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error> {
+        let in_domain = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .any(|(domain, points)| {
+                let log_height = log2_strict_usize(domain.size()) + self.stir.log_blowup;
+                points
+                    .iter()
+                    .any(|&(z, _)| in_lde_domain::<Val, _>(z, log_height))
+            });
+        if in_domain {
+            return Err(StirError::InDomainOpeningPoint);
+        }
+
+        // Write evaluations to challenger
+        for (_, round) in &rounds {
+            for (_, mat) in round {
+                for (_, point) in mat {
+                    point
+                        .iter()
+                        .for_each(|&opening| challenger.observe_algebra_element(opening));
+                }
+            }
+        }
+
+        // Batch combination challenge
+        let alpha: Challenge = challenger.sample_algebra_element();
+
+        let log_heights = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .map(|(domain, _)| log2_strict_usize(domain.size()) + self.stir.log_blowup)
+            .sorted()
+            .rev()
+            .dedup()
+            .collect_vec();
+        // Each matrix must be opened at a point at least, as only the values claimed there fix its
+        // width.
+        let every_matrix_opened = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .all(|(_, points)| !points.is_empty());
+        if proof.len() != log_heights.len() || !every_matrix_opened {
+            return Err(StirError::InvalidProofShape);
+        }
+        let Some(&log_global_max_height) = log_heights.first() else {
+            return Ok(());
+        };
+
+        for (&log_height, stir_proof) in izip!(&log_heights, proof) {
+            // Every input proof must open each matrix, so that a reduced opening is computed for
+            // every height, and prove each opening.
+            let well_shaped = stir_proof.input_proofs.iter().all(|input_proof| {
+                input_proof.len() == rounds.len()
+                    && izip!(input_proof, &rounds).all(|(batch_opening, (_, mats))| {
+                        batch_opening.opened_values.len() == mats.len()
+                            && batch_opening.opening_proof.is_some()
+                    })
+            });
+            if !well_shaped {
+                return Err(StirError::InvalidProofShape);
+            }
+
+            let bits_reduced = log_global_max_height - log_height;
+            verifier::verify(
+                &self.stir,
+                log_height,
+                stir_proof,
+                challenger,
+                |index, input_proof: &Vec<BatchOpening<Val, InputMmcs>>| {
+                    let reduced_openings = verify_and_reduce(
+                        &self.mmcs,
+                        self.stir.log_blowup,
+                        &rounds,
+                        alpha,
+                        log_global_max_height,
+                        index << bits_reduced,
+                        input_proof,
+                    )?;
+                    let (_, reduced_opening) = reduced_openings
+                        .into_iter()
+                        .find(|&(lh, _)| lh == log_height)
+                        .expect("input proofs were checked to open every matrix");
+                    Ok(reduced_opening)
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use alloc::vec::Vec;
+
+use p3_commit::Mmcs;
+use p3_field::Field;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "Witness: Serialize, InputProof: Serialize",
+    deserialize = "Witness: Deserialize<'de>, InputProof: Deserialize<'de>"
+))]
+pub struct StirProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
+    /// A commitment to the initial codeword, grouped in rows of the first folding factor.
+    pub initial_commitment: M::Commitment,
+    pub round_proofs: Vec<StirRoundProof<F, M, Witness>>,
+    /// The coefficients of the final polynomial.
+    pub final_poly: Vec<F>,
+    pub final_pow_witness: Witness,
+    /// Openings of the last committed codeword (or of the initial codeword, if there were no
+    /// rounds) at the rows queried by the final check.
+    pub final_query_proofs: Vec<StirQueryProof<F, M>>,
+    /// Openings of the inputs at the positions of the initial codeword which were queried.
+    pub input_proofs: Vec<InputProof>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "Witness: Serialize",
+    deserialize = "Witness: Deserialize<'de>"
+))]
+pub struct StirRoundProof<F: Field, M: Mmcs<F>, Witness> {
+    /// A commitment to the evaluations of the folded polynomial over the round's domain.
+    pub commitment: M::Commitment,
+    /// The evaluations of the folded polynomial at the out-of-domain points.
+    pub ood_answers: Vec<F>,
+    pub pow_witness: Witness,
+    /// Openings of the previous codeword at the rows queried in this round.
+    pub query_proofs: Vec<StirQueryProof<F, M>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct StirQueryProof<F: Field, M: Mmcs<F>> {
+    /// The evaluations which are folded together, in the order they were committed.
+    pub row: Vec<F>,
+    pub opening_proof: M::Proof,
+}
@@ -0,0 +1,216 @@
+use alloc::vec::Vec;
+use core::iter;
+
+use itertools::Itertools;
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::Mmcs;
+use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
+use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_util::{log2_strict_usize, reverse_slice_index_bits};
+use tracing::{info_span, instrument};
+
+use crate::utils::{degree_correct, eval_poly, fold_coeffs, folded_point, quotient};
+use crate::{StirConfig, StirProof, StirQueryProof, StirRoundProof};
+
+/// Proves that `input`, the bit-reversed evaluations of a polynomial over the coset
+/// `Val::GENERATOR * K` of size `input.len()`, is close to a codeword of rate `1 / blowup`.
+///
+/// `open_input` is called with each queried index of `input`, and should return a proof of the
+/// value at that index.
+#[instrument(name = "STIR prover", skip_all)]
+pub fn prove<Val, Challenge, M, Challenger, InputProof>(
+    config: &StirConfig<M>,
+    input: Vec<Challenge>,
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize) -> InputProof,
+) -> StirProof<Challenge, M, Challenger::Witness, InputProof>
+where
+    Val: TwoAdicField,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+{
+    let log_height = log2_strict_usize(input.len());
+    assert!(
+        log_height >= config.log_blowup,
+        "input is shorter than the blowup"
+    );
+    let log_degree = log_height - config.log_blowup;
+    let log_folding_factors = config.log_folding_factors(log_degree);
+    let shift = Val::GENERATOR;
+    let dft = Radix2Dit::default();
+
+    // Recover the coefficients of the polynomial from its evaluations.
+    let mut evals = input.clone();
+    reverse_slice_index_bits(&mut evals);
+    let mut coeffs = dft.coset_idft(evals, Challenge::from(shift));
+    coeffs.truncate(1 << log_degree);
+
+    let mut layer = commit_layer(config, input, log_folding_factors[0]);
+    challenger.observe(layer.commitment.clone());
+    let initial_commitment = layer.commitment.clone();
+    let mut r_fold: Challenge = challenger.sample_algebra_element();
+
+    let mut log_degree = log_degree;
+    let mut round_proofs = Vec::with_capacity(log_folding_factors.len() - 1);
+    let mut input_proofs = Vec::new();
+
+    for (round, &log_folding_factor) in log_folding_factors.iter().enumerate().skip(1) {
+        let _guard = info_span!("STIR round", round).entered();
+
+        // Fold the previous polynomial, and commit to its evaluations over the next domain, which
+        // is half the size of the previous one.
+        let prev_log_height = log_height - (round - 1);
+        let prev_log_folding_factor = log_folding_factors[round - 1];
+        let prev_log_inv_rate = prev_log_height - log_degree;
+        let g_coeffs = fold_coeffs(&coeffs, prev_log_folding_factor, r_fold);
+        log_degree -= prev_log_folding_factor;
+
+        let mut g_evals = g_coeffs.clone();
+        g_evals.resize(1 << (prev_log_height - 1), Challenge::ZERO);
+        let mut g_evals = dft.coset_dft(g_evals, Challenge::from(shift));
+        reverse_slice_index_bits(&mut g_evals);
+        let next_layer = commit_layer(config, g_evals, log_folding_factor);
+        challenger.observe(next_layer.commitment.clone());
+
+        // Answer the out-of-domain queries.
+        let ood_points: Vec<Challenge> = (0..config.num_ood_samples)
+            .map(|_| challenger.sample_algebra_element())
+            .collect();
+        let ood_answers = ood_points
+            .iter()
+            .map(|&z| eval_poly(&g_coeffs, z))
+            .collect_vec();
+        ood_answers
+            .iter()
+            .for_each(|&answer| challenger.observe_algebra_element(answer));
+
+        let r_comb: Challenge = challenger.sample_algebra_element();
+        let pow_witness = challenger.grind(config.proof_of_work_bits);
+
+        // Query the previous codeword. Each query determines the value of `g` at the
+        // corresponding point of the folded domain.
+        let num_queries = config.num_queries_for_log_inv_rate(prev_log_inv_rate);
+        let indices = sample_indices(challenger, prev_log_height, num_queries);
+        if round == 1 {
+            input_proofs = indices.iter().map(|&index| open_input(index)).collect();
+        }
+        let query_proofs = answer_queries(config, &layer, prev_log_folding_factor, &indices);
+
+        let mut points = ood_points;
+        for &index in &indices {
+            let y = folded_point(
+                shift,
+                prev_log_height,
+                prev_log_folding_factor,
+                index >> prev_log_folding_factor,
+            );
+            if !points.contains(&y) {
+                points.push(y);
+            }
+        }
+        let answers = points
+            .iter()
+            .map(|&point| eval_poly(&g_coeffs, point))
+            .collect_vec();
+
+        let q = quotient(&g_coeffs, &points, &answers);
+        coeffs = degree_correct(&q, r_comb, points.len(), 1 << log_degree);
+
+        round_proofs.push(StirRoundProof {
+            commitment: next_layer.commitment.clone(),
+            ood_answers,
+            pow_witness,
+            query_proofs,
+        });
+        layer = next_layer;
+        r_fold = challenger.sample_algebra_element();
+    }
+
+    // Send the last fold in the clear, and check it against the last committed codeword.
+    let last_log_folding_factor = *log_folding_factors.last().unwrap();
+    let last_log_height = log_height - (log_folding_factors.len() - 1);
+    let final_poly = fold_coeffs(&coeffs, last_log_folding_factor, r_fold);
+    final_poly
+        .iter()
+        .for_each(|&coeff| challenger.observe_algebra_element(coeff));
+
+    let final_pow_witness = challenger.grind(config.proof_of_work_bits);
+    let num_queries = config.num_queries_for_log_inv_rate(last_log_height - log_degree);
+    let indices = sample_indices(challenger, last_log_height, num_queries);
+    if log_folding_factors.len() == 1 {
+        input_proofs = indices.iter().map(|&index| open_input(index)).collect();
+    }
+    let final_query_proofs = answer_queries(config, &layer, last_log_folding_factor, &indices);
+
+    StirProof {
+        initial_commitment,
+        round_proofs,
+        final_poly,
+        final_pow_witness,
+        final_query_proofs,
+        input_proofs,
+    }
+}
+
+struct Layer<F: Field, M: Mmcs<F>> {
+    commitment: M::Commitment,
+    data: M::ProverData<RowMajorMatrix<F>>,
+}
+
+/// Commits to bit-reversed evaluations, grouped so that the evaluations folded together make up a
+/// single row.
+fn commit_layer<F, M>(
+    config: &StirConfig<M>,
+    evals: Vec<F>,
+    log_folding_factor: usize,
+) -> Layer<F, M>
+where
+    F: TwoAdicField,
+    M: Mmcs<F>,
+{
+    let (commitment, data) = config
+        .mmcs
+        .commit_matrix(RowMajorMatrix::new(evals, 1 << log_folding_factor));
+    Layer { commitment, data }
+}
+
+fn sample_indices<Val, Challenger>(
+    challenger: &mut Challenger,
+    log_height: usize,
+    num_queries: usize,
+) -> Vec<usize>
+where
+    Val: TwoAdicField,
+    Challenger: FieldChallenger<Val>,
+{
+    iter::repeat_with(|| challenger.sample_bits(log_height))
+        .take(num_queries)
+        .collect()
+}
+
+fn answer_queries<F, M>(
+    config: &StirConfig<M>,
+    layer: &Layer<F, M>,
+    log_folding_factor: usize,
+    indices: &[usize],
+) -> Vec<StirQueryProof<F, M>>
+where
+    F: TwoAdicField,
+    M: Mmcs<F>,
+{
+    indices
+        .iter()
+        .map(|&index| {
+            let (mut opened_rows, opening_proof) = config
+                .mmcs
+                .open_batch(index >> log_folding_factor, &layer.data);
+            assert_eq!(opened_rows.len(), 1);
+            StirQueryProof {
+                row: opened_rows.pop().unwrap(),
+                opening_proof,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,266 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+use p3_field::{ExtensionField, Field, TwoAdicField, batch_multiplicative_inverse};
+use p3_util::reverse_bits_len;
+
+/// Given the coefficients of `f(X) = sum_j X^j f_j(X^k)`, where `k = 2^log_folding_factor`,
+/// returns the coefficients of `sum_j r^j f_j(X)`.
+pub(crate) fn fold_coeffs<F: Field>(coeffs: &[F], log_folding_factor: usize, r: F) -> Vec<F> {
+    coeffs
+        .chunks(1 << log_folding_factor)
+        .map(|chunk| eval_poly(chunk, r))
+        .collect()
+}
+
+/// Evaluates the polynomial with the given coefficients at `x`, using Horner's method.
+pub(crate) fn eval_poly<F: Field>(coeffs: &[F], x: F) -> F {
+    coeffs
+        .iter()
+        .rev()
+        .fold(F::ZERO, |acc, &coeff| acc * x + coeff)
+}
+
+/// Returns the barycentric weights `1 / prod_{l != j} (p_j - p_l)` of the given distinct points.
+pub(crate) fn barycentric_weights<F: Field>(points: &[F]) -> Vec<F> {
+    let denominators = points
+        .iter()
+        .enumerate()
+        .map(|(j, &p_j)| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|&(l, _)| l != j)
+                .map(|(_, &p_l)| p_j - p_l)
+                .product()
+        })
+        .collect_vec();
+    batch_multiplicative_inverse(&denominators)
+}
+
+/// Returns the coefficients of the polynomial of degree less than `points.len()` which takes the
+/// given values at the given distinct points.
+pub(crate) fn interpolate_coeffs<F: Field>(points: &[F], values: &[F]) -> Vec<F> {
+    debug_assert_eq!(points.len(), values.len());
+    // The vanishing polynomial of all points.
+    let mut vanishing = vec![F::ONE];
+    for &p in points {
+        vanishing = multiply_by_linear(&vanishing, p);
+    }
+
+    let mut coeffs = vec![F::ZERO; points.len()];
+    for ((&p, weight), &value) in points.iter().zip(barycentric_weights(points)).zip(values) {
+        // The vanishing polynomial of every point except `p`.
+        let basis = divide_by_linear(&vanishing, p);
+        let scale = weight * value;
+        for (c, b) in coeffs.iter_mut().zip(basis) {
+            *c += scale * b;
+        }
+    }
+    coeffs
+}
+
+/// Returns the coefficients of `f(X) * (X - p)`.
+fn multiply_by_linear<F: Field>(coeffs: &[F], p: F) -> Vec<F> {
+    let mut result = vec![F::ZERO; coeffs.len() + 1];
+    for (i, &c) in coeffs.iter().enumerate() {
+        result[i + 1] += c;
+        result[i] -= c * p;
+    }
+    result
+}
+
+/// Returns the coefficients of the quotient of `f(X)` by `X - p`, discarding the remainder.
+fn divide_by_linear<F: Field>(coeffs: &[F], p: F) -> Vec<F> {
+    let Some((_, higher)) = coeffs.split_first() else {
+        return vec![];
+    };
+    let mut quotient = vec![F::ZERO; higher.len()];
+    let mut acc = F::ZERO;
+    for (q, &c) in quotient.iter_mut().zip(higher).rev() {
+        acc = acc * p + c;
+        *q = acc;
+    }
+    quotient
+}
+
+/// Returns the coefficients of `(f(X) - ans(X)) / V(X)`, where `ans` is the polynomial of degree
+/// less than `points.len()` interpolating `answers` over the distinct `points`, and `V` is the
+/// vanishing polynomial of `points`.
+///
+/// This is exact whenever `f` takes the values `answers` at `points`.
+pub(crate) fn quotient<F: Field>(coeffs: &[F], points: &[F], answers: &[F]) -> Vec<F> {
+    let ans = interpolate_coeffs(points, answers);
+    let mut numerator = coeffs.to_vec();
+    if numerator.len() < ans.len() {
+        numerator.resize(ans.len(), F::ZERO);
+    }
+    for (c, a) in numerator.iter_mut().zip(ans) {
+        *c -= a;
+    }
+    points
+        .iter()
+        .fold(numerator, |acc, &p| divide_by_linear(&acc, p))
+}
+
+/// Returns the first `len` coefficients of `q(X) * sum_{l=0}^{e} (r X)^l`.
+///
+/// Multiplying by this factor raises the degree of `q` by `e` without changing how far it is from
+/// a low-degree polynomial, except with small probability over `r`.
+pub(crate) fn degree_correct<F: Field>(q: &[F], r: F, e: usize, len: usize) -> Vec<F> {
+    let r_pow_e_plus_1 = r.exp_u64(e as u64 + 1);
+    let q_at = |d: usize| q.get(d).copied().unwrap_or(F::ZERO);
+
+    // Writing the product as `res`, we have `res[d] = sum_{l=0}^{min(d, e)} r^l q[d - l]`, which
+    // satisfies `res[d] = q[d] + r res[d - 1] - r^{e + 1} q[d - e - 1]`.
+    let mut result = Vec::with_capacity(len);
+    let mut prev = F::ZERO;
+    for d in 0..len {
+        let mut current = q_at(d) + r * prev;
+        if d > e {
+            current -= r_pow_e_plus_1 * q_at(d - e - 1);
+        }
+        result.push(current);
+        prev = current;
+    }
+    result
+}
+
+/// Evaluates `sum_{l=0}^{e} (r x)^l`, the degree correction factor of `degree_correct`.
+pub(crate) fn degree_correction_factor<F: Field>(r: F, e: usize, x: F) -> F {
+    let rx = r * x;
+    if rx == F::ONE {
+        F::from_usize(e + 1)
+    } else {
+        (rx.exp_u64(e as u64 + 1) - F::ONE) / (rx - F::ONE)
+    }
+}
+
+/// Evaluates, at `z`, the polynomial of degree less than `points.len()` which takes the given
+/// values at the given distinct points.
+pub(crate) fn lagrange_eval<F: Field>(points: &[F], values: &[F], z: F) -> F {
+    if let Some(i) = points.iter().position(|&p| p == z) {
+        return values[i];
+    }
+    let weights = barycentric_weights(points);
+    let inv_diffs = batch_multiplicative_inverse(&points.iter().map(|&p| z - p).collect_vec());
+    let vanishing: F = points.iter().map(|&p| z - p).product();
+    vanishing
+        * weights
+            .into_iter()
+            .zip(inv_diffs)
+            .zip(values)
+            .map(|((w, inv_diff), &v)| w * inv_diff * v)
+            .sum::<F>()
+}
+
+/// Returns the points of the evaluation domain `shift * K` of size `2^log_height` which make up
+/// row `row` when the bit-reversed evaluations over it are grouped into rows of
+/// `2^log_folding_factor`.
+///
+/// These are exactly the `2^log_folding_factor`-th roots of `folded_point(...)`.
+pub(crate) fn fiber_points<Val: TwoAdicField, F: ExtensionField<Val>>(
+    shift: Val,
+    log_height: usize,
+    log_folding_factor: usize,
+    row: usize,
+) -> Vec<F> {
+    let generator = Val::two_adic_generator(log_height);
+    (0..1 << log_folding_factor)
+        .map(|l| {
+            let index = (row << log_folding_factor) | l;
+            F::from(shift * generator.exp_u64(reverse_bits_len(index, log_height) as u64))
+        })
+        .collect()
+}
+
+/// Returns the point of the folded domain `shift^k * K^k` which corresponds to row `row` of the
+/// evaluation domain `shift * K` of size `2^log_height`, where `k = 2^log_folding_factor`.
+pub(crate) fn folded_point<Val: TwoAdicField, F: ExtensionField<Val>>(
+    shift: Val,
+    log_height: usize,
+    log_folding_factor: usize,
+    row: usize,
+) -> F {
+    let log_folded_height = log_height - log_folding_factor;
+    let folded_shift = shift.exp_power_of_2(log_folding_factor);
+    F::from(
+        folded_shift
+            * Val::two_adic_generator(log_folded_height)
+                .exp_u64(reverse_bits_len(row, log_folded_height) as u64),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::PrimeCharacteristicRing;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    type F = BabyBear;
+
+    #[test]
+    fn test_interpolate_and_quotient() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let coeffs: Vec<F> = (0..32).map(|_| rng.random()).collect();
+        let points: Vec<F> = (0..5).map(|_| rng.random()).collect();
+        let answers = points.iter().map(|&p| eval_poly(&coeffs, p)).collect_vec();
+
+        let ans = interpolate_coeffs(&points, &answers);
+        for (&p, &a) in points.iter().zip(&answers) {
+            assert_eq!(eval_poly(&ans, p), a);
+        }
+
+        // `f = ans + V * q`, so evaluating both sides at a random point checks the quotient.
+        let q = quotient(&coeffs, &points, &answers);
+        assert_eq!(q.len(), coeffs.len() - points.len());
+        let z: F = rng.random();
+        let vanishing: F = points.iter().map(|&p| z - p).product();
+        assert_eq!(
+            eval_poly(&coeffs, z),
+            eval_poly(&ans, z) + vanishing * eval_poly(&q, z)
+        );
+        assert_eq!(lagrange_eval(&points, &answers, z), eval_poly(&ans, z));
+    }
+
+    #[test]
+    fn test_degree_correct() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let q: Vec<F> = (0..10).map(|_| rng.random()).collect();
+        let r: F = rng.random();
+        let e = 3;
+        let corrected = degree_correct(&q, r, e, 16);
+        assert!(corrected[q.len() + e..].iter().all(|c| c.is_zero()));
+
+        let x: F = rng.random();
+        assert_eq!(
+            eval_poly(&corrected, x),
+            eval_poly(&q, x) * degree_correction_factor(r, e, x)
+        );
+    }
+
+    #[test]
+    fn test_fold_matches_fiber_interpolation() {
+        let mut rng = SmallRng::seed_from_u64(2);
+        let log_folding_factor = 2;
+        let log_height = 6;
+        let coeffs: Vec<F> = (0..16).map(|_| rng.random()).collect();
+        let r: F = rng.random();
+        let folded = fold_coeffs(&coeffs, log_folding_factor, r);
+
+        for row in 0..1 << (log_height - log_folding_factor) {
+            let xs: Vec<F> = fiber_points(F::GENERATOR, log_height, log_folding_factor, row);
+            let ys = xs.iter().map(|&x| eval_poly(&coeffs, x)).collect_vec();
+            let y: F = folded_point(F::GENERATOR, log_height, log_folding_factor, row);
+            assert!(
+                xs.iter()
+                    .all(|&x| x.exp_power_of_2(log_folding_factor) == y)
+            );
+            assert_eq!(lagrange_eval(&xs, &ys, r), eval_poly(&folded, y));
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::slice;
+
+use itertools::{Itertools, izip};
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::Mmcs;
+use p3_field::{ExtensionField, TwoAdicField, batch_multiplicative_inverse};
+use p3_matrix::Dimensions;
+
+use crate::utils::{
+    barycentric_weights, degree_correction_factor, eval_poly, fiber_points, folded_point,
+    lagrange_eval,
+};
+use crate::{StirConfig, StirProof, StirQueryProof};
+
+#[derive(Debug)]
+pub enum StirError<MmcsErr, InputError> {
+    InvalidProofShape,
+    /// An opening point lies in the LDE domain of its matrix, where the quotient by `X - z` is
+    /// undefined. Only `TwoAdicFriPcs` can open at such points.
+    InDomainOpeningPoint,
+    MmcsError(MmcsErr),
+    InputError(InputError),
+    /// The opened input value does not match the corresponding entry of the initial codeword.
+    InputMismatch,
+    FinalPolyMismatch,
+    InvalidPowWitness,
+}
+
+/// The constraint that a round of STIR places on the codeword committed in that round: the
+/// virtual function `f` which is folded in the next round is defined from the committed `g` by
+/// `f(x) = (g(x) - ans(x)) / V(x) * sum_{l=0}^{|points|} (r_comb x)^l`.
+struct RoundConstraint<F> {
+    points: Vec<F>,
+    answers: Vec<F>,
+    weights: Vec<F>,
+    r_comb: F,
+}
+
+impl<F: TwoAdicField> RoundConstraint<F> {
+    /// Evaluates the virtual function at the points `xs`, given the committed values `gs` there.
+    fn evaluate(&self, xs: &[F], gs: &[F]) -> Vec<F> {
+        xs.iter()
+            .zip(gs)
+            .map(|(&x, &g)| {
+                let inv_diffs =
+                    batch_multiplicative_inverse(&self.points.iter().map(|&p| x - p).collect_vec());
+                // `1 / V(x)`, and `ans(x) / V(x)` in barycentric form.
+                let inv_vanishing: F = inv_diffs.iter().copied().product();
+                let ans_over_vanishing: F = izip!(&self.weights, &self.answers, &inv_diffs)
+                    .map(|(&w, &a, &inv_diff)| w * a * inv_diff)
+                    .sum();
+                (g * inv_vanishing - ans_over_vanishing)
+                    * degree_correction_factor(self.r_comb, self.points.len(), x)
+            })
+            .collect()
+    }
+}
+
+/// Verifies a STIR proof that the input, a codeword over the coset `Val::GENERATOR * K` of size
+/// `2^log_height`, is close to a codeword of rate `1 / blowup`.
+///
+/// `open_input` is called with each queried index of the input along with its proof, and should
+/// return the value of the input at that index.
+pub fn verify<Val, Challenge, M, Challenger, InputProof, InputError>(
+    config: &StirConfig<M>,
+    log_height: usize,
+    proof: &StirProof<Challenge, M, Challenger::Witness, InputProof>,
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize, &InputProof) -> Result<Challenge, InputError>,
+) -> Result<(), StirError<M::Error, InputError>>
+where
+    Val: TwoAdicField,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+{
+    if log_height < config.log_blowup {
+        return Err(StirError::InvalidProofShape);
+    }
+    let mut log_degree = log_height - config.log_blowup;
+    let log_folding_factors = config.log_folding_factors(log_degree);
+    if proof.round_proofs.len() != log_folding_factors.len() - 1 {
+        return Err(StirError::InvalidProofShape);
+    }
+
+    challenger.observe(proof.initial_commitment.clone());
+    let mut r_fold: Challenge = challenger.sample_algebra_element();
+
+    // The commitment to the codeword being queried, and the constraint relating it to the virtual
+    // function being folded, which is absent for the initial codeword.
+    let mut commitment = &proof.initial_commitment;
+    let mut constraint = None;
+    let mut input_indices = vec![];
+
+    for (round, round_proof) in proof.round_proofs.iter().enumerate() {
+        let prev_log_height = log_height - round;
+        let prev_log_folding_factor = log_folding_factors[round];
+        let prev_log_inv_rate = prev_log_height - log_degree;
+        log_degree -= prev_log_folding_factor;
+
+        challenger.observe(round_proof.commitment.clone());
+
+        let ood_points: Vec<Challenge> = (0..config.num_ood_samples)
+            .map(|_| challenger.sample_algebra_element())
+            .collect();
+        if round_proof.ood_answers.len() != config.num_ood_samples {
+            return Err(StirError::InvalidProofShape);
+        }
+        round_proof
+            .ood_answers
+            .iter()
+            .for_each(|&answer| challenger.observe_algebra_element(answer));
+
+        let r_comb: Challenge = challenger.sample_algebra_element();
+        if !challenger.check_witness(config.proof_of_work_bits, round_proof.pow_witness) {
+            return Err(StirError::InvalidPowWitness);
+        }
+
+        let queries = verify_queries(
+            config,
+            challenger,
+            commitment,
+            constraint.as_ref(),
+            prev_log_height,
+            prev_log_folding_factor,
+            r_fold,
+            config.num_queries_for_log_inv_rate(prev_log_inv_rate),
+            &round_proof.query_proofs,
+        )?;
+        if round == 0 {
+            input_indices = queries.iter().map(|query| query.index).collect();
+        }
+
+        // The out-of-domain answers and the folds at the queried points all constrain the
+        // committed codeword.
+        let mut points = ood_points;
+        let mut answers = round_proof.ood_answers.clone();
+        for query in queries {
+            if !points.contains(&query.point) {
+                points.push(query.point);
+                answers.push(query.fold);
+            }
+        }
+        constraint = Some(RoundConstraint {
+            weights: barycentric_weights(&points),
+            points,
+            answers,
+            r_comb,
+        });
+        commitment = &round_proof.commitment;
+        r_fold = challenger.sample_algebra_element();
+    }
+
+    let last_log_folding_factor = *log_folding_factors.last().unwrap();
+    let last_log_height = log_height - proof.round_proofs.len();
+    if proof.final_poly.len() != 1 << (log_degree - last_log_folding_factor) {
+        return Err(StirError::InvalidProofShape);
+    }
+    proof
+        .final_poly
+        .iter()
+        .for_each(|&coeff| challenger.observe_algebra_element(coeff));
+
+    if !challenger.check_witness(config.proof_of_work_bits, proof.final_pow_witness) {
+        return Err(StirError::InvalidPowWitness);
+    }
+
+    let queries = verify_queries(
+        config,
+        challenger,
+        commitment,
+        constraint.as_ref(),
+        last_log_height,
+        last_log_folding_factor,
+        r_fold,
+        config.num_queries_for_log_inv_rate(last_log_height - log_degree),
+        &proof.final_query_proofs,
+    )?;
+    for query in &queries {
+        if query.fold != eval_poly(&proof.final_poly, query.point) {
+            return Err(StirError::FinalPolyMismatch);
+        }
+    }
+    if proof.round_proofs.is_empty() {
+        input_indices = queries.iter().map(|query| query.index).collect();
+    }
+
+    // Check that the initial codeword agrees with the input at every index queried from it.
+    let initial_query_proofs = proof
+        .round_proofs
+        .first()
+        .map_or(&proof.final_query_proofs, |round_proof| {
+            &round_proof.query_proofs
+        });
+    if proof.input_proofs.len() != input_indices.len() {
+        return Err(StirError::InvalidProofShape);
+    }
+    let mask = (1 << log_folding_factors[0]) - 1;
+    for (&index, input_proof, query_proof) in
+        izip!(&input_indices, &proof.input_proofs, initial_query_proofs)
+    {
+        let value = open_input(index, input_proof).map_err(StirError::InputError)?;
+        if value != query_proof.row[index & mask] {
+            return Err(StirError::InputMismatch);
+        }
+    }
+
+    Ok(())
+}
+
+struct QueryResult<F> {
+    /// The queried index of the codeword.
+    index: usize,
+    /// The point of the folded domain corresponding to the queried row.
+    point: F,
+    /// The value of the folded virtual function at `point`.
+    fold: F,
+}
+
+/// Samples the query indices of a codeword, checks the openings of the corresponding rows, and
+/// folds the virtual function defined from each row.
+#[allow(clippy::too_many_arguments)]
+fn verify_queries<Val, Challenge, M, Challenger, InputError>(
+    config: &StirConfig<M>,
+    challenger: &mut Challenger,
+    commitment: &M::Commitment,
+    constraint: Option<&RoundConstraint<Challenge>>,
+    log_height: usize,
+    log_folding_factor: usize,
+    r_fold: Challenge,
+    num_queries: usize,
+    query_proofs: &[StirQueryProof<Challenge, M>],
+) -> Result<Vec<QueryResult<Challenge>>, StirError<M::Error, InputError>>
+where
+    Val: TwoAdicField,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val>,
+{
+    if query_proofs.len() != num_queries {
+        return Err(StirError::InvalidProofShape);
+    }
+    let shift = Val::GENERATOR;
+    let dims = [Dimensions {
+        width: 1 << log_folding_factor,
+        height: 1 << (log_height - log_folding_factor),
+    }];
+
+    query_proofs
+        .iter()
+        .map(|query_proof| {
+            let index = challenger.sample_bits(log_height);
+            let row = index >> log_folding_factor;
+            if query_proof.row.len() != 1 << log_folding_factor {
+                return Err(StirError::InvalidProofShape);
+            }
+            config
+                .mmcs
+                .verify_batch(
+                    commitment,
+                    &dims,
+                    row,
+                    slice::from_ref(&query_proof.row),
+                    &query_proof.opening_proof,
+                )
+                .map_err(StirError::MmcsError)?;
+
+            let xs = fiber_points(shift, log_height, log_folding_factor, row);
+            let values = match constraint {
+                Some(constraint) => constraint.evaluate(&xs, &query_proof.row),
+                None => query_proof.row.clone(),
+            };
+            Ok(QueryResult {
+                index,
+                point: folded_point(shift, log_height, log_folding_factor, row),
+                fold: lagrange_eval(&xs, &values, r_fold),
+            })
+        })
+        .collect()
+}
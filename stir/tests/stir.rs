@@ -0,0 +1,119 @@
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::{CanSampleBits, DuplexChallenger};
+use p3_commit::{ExtensionMmcs, Mmcs};
+use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
+use p3_field::Field;
+use p3_field::extension::BinomialExtensionField;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_stir::verifier::StirError;
+use p3_stir::{StirConfig, prover, verifier};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_util::reverse_slice_index_bits;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+
+fn get_ldt_for_testing<R: Rng>(
+    rng: &mut R,
+    log_folding_factor: usize,
+    log_final_poly_len: usize,
+) -> (Perm, StirConfig<ChallengeMmcs>) {
+    let perm = Perm::new_from_rng_128(rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let mmcs = ChallengeMmcs::new(ValMmcs::new(hash, compress));
+    let config = StirConfig {
+        log_blowup: 1,
+        log_folding_factor,
+        log_final_poly_len,
+        num_queries: 10,
+        num_ood_samples: 2,
+        proof_of_work_bits: 8,
+        mmcs,
+    };
+    (perm, config)
+}
+
+/// Returns the bit-reversed evaluations over `Val::GENERATOR * K` of a random polynomial with
+/// `2^log_degree` coefficients, on a domain `2^log_blowup` times larger.
+fn random_codeword<R: Rng>(rng: &mut R, log_degree: usize, log_blowup: usize) -> Vec<Challenge> {
+    let coeffs: Vec<Challenge> = (0..1 << log_degree).map(|_| rng.random()).collect();
+    let mut padded = coeffs;
+    padded.resize(1 << (log_degree + log_blowup), Challenge::default());
+    let mut evals = Radix2Dit::default().coset_dft(padded, Challenge::from(Val::GENERATOR));
+    reverse_slice_index_bits(&mut evals);
+    evals
+}
+
+/// Runs the prover on `input` and the verifier against `log_height`, passing the queried input
+/// values through as the input proofs.
+fn prove_and_verify(
+    perm: Perm,
+    config: &StirConfig<ChallengeMmcs>,
+    input: Vec<Challenge>,
+    log_height: usize,
+) -> Result<(), StirError<<ChallengeMmcs as Mmcs<Challenge>>::Error, ()>> {
+    let mut p_challenger = Challenger::new(perm.clone());
+    let proof = prover::prove(config, input.clone(), &mut p_challenger, |index| {
+        input[index]
+    });
+
+    let mut v_challenger = Challenger::new(perm);
+    verifier::verify(
+        config,
+        log_height,
+        &proof,
+        &mut v_challenger,
+        |_index, &value| Ok(value),
+    )?;
+
+    assert_eq!(
+        p_challenger.sample_bits(8),
+        v_challenger.sample_bits(8),
+        "prover and verifier transcript have same state after STIR"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_stir_ldt() {
+    for log_folding_factor in 1..=4 {
+        for log_final_poly_len in 0..3 {
+            for log_degree in 0..10 {
+                let mut rng = ChaCha20Rng::seed_from_u64(log_degree as u64);
+                let (perm, config) =
+                    get_ldt_for_testing(&mut rng, log_folding_factor, log_final_poly_len);
+                let input = random_codeword(&mut rng, log_degree, config.log_blowup);
+                prove_and_verify(perm, &config, input, log_degree + config.log_blowup).unwrap();
+            }
+        }
+    }
+}
+
+#[test]
+fn test_stir_ldt_rejects_high_degree() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let (perm, config) = get_ldt_for_testing(&mut rng, 2, 0);
+    // A random codeword is, with overwhelming probability, far from any low-degree codeword.
+    let log_height = 10;
+    let input: Vec<Challenge> = (0..1 << log_height).map(|_| rng.random()).collect();
+    assert!(prove_and_verify(perm, &config, input, log_height).is_err());
+}
+
+#[test]
+fn test_stir_ldt_rejects_wrong_height() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let (perm, config) = get_ldt_for_testing(&mut rng, 2, 0);
+    let input = random_codeword(&mut rng, 8, config.log_blowup);
+    assert!(prove_and_verify(perm, &config, input, 8).is_err());
+}
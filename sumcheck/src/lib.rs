@@ -0,0 +1,13 @@
+//! An implementation of the sumcheck protocol for products of multilinear polynomials.
+
+#![no_std]
+
+extern crate alloc;
+
+mod proof;
+mod prover;
+mod verifier;
+
+pub use proof::*;
+pub use prover::*;
+pub use verifier::*;
@@ -0,0 +1,11 @@
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SumcheckProof<EF> {
+    /// For each round, the round polynomial evaluated at `0, 1, ..., degree`.
+    pub round_polys: Vec<Vec<EF>>,
+    /// The evaluation of each factor at the point defined by the round challenges.
+    pub final_evals: Vec<EF>,
+}
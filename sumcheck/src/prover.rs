@@ -0,0 +1,371 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::{Itertools, izip};
+use p3_challenger::FieldChallenger;
+use p3_field::{
+    Algebra, BasedVectorSpace, ExtensionField, Field, PackedField, PackedFieldExtension,
+    PackedValue, PrimeCharacteristicRing,
+};
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+use tracing::{info_span, instrument};
+
+use crate::SumcheckProof;
+
+/// Proves that `sum_{b in {0, 1}^n} prod_j f_j(b)` equals the (implicit) claimed sum, where each
+/// factor `f_j` is a multilinear polynomial over the base field, given by its evaluations over the
+/// boolean hypercube. Entry `b` of a factor is its evaluation at the point whose `i`-th coordinate
+/// is bit `i` of `b`.
+///
+/// The first `num_base_field_rounds` rounds are computed without binding any variable, so that
+/// their inner loops only involve (packed) base field arithmetic. The cost of such a round grows
+/// like `2^(k * degree)` in the number `k` of previous rounds, so this only pays off for the first
+/// one or two rounds.
+///
+/// Returns the proof and the point, in the same coordinate order as the hypercube, at which the
+/// verifier will need to check `final_evals`.
+#[instrument(name = "sumcheck prover", skip_all)]
+pub fn prove<F, EF, Challenger>(
+    factors: Vec<Vec<F>>,
+    num_base_field_rounds: usize,
+    challenger: &mut Challenger,
+) -> (SumcheckProof<EF>, Vec<EF>)
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Challenger: FieldChallenger<F>,
+{
+    let num_vars = num_vars(&factors);
+    let mut round_polys = vec![];
+    let mut challenges = vec![];
+
+    // Equality weights eq(r, s) for the already-sampled challenges r, indexed by the bound bits s.
+    let mut weights = vec![EF::ONE];
+    for _ in 0..num_base_field_rounds.min(num_vars) {
+        let round_poly =
+            info_span!("base field round").in_scope(|| base_field_round_poly(&factors, &weights));
+        let r = observe_and_sample::<F, EF, _>(&round_poly, challenger);
+        weights = weights
+            .into_iter()
+            .flat_map(|w| {
+                let hi = w * r;
+                [w - hi, hi]
+            })
+            .collect();
+        round_polys.push(round_poly);
+        challenges.push(r);
+    }
+
+    let factors = info_span!("bind base field rounds").in_scope(|| {
+        factors
+            .iter()
+            .map(|f| bind_prefix(f, &weights))
+            .collect_vec()
+    });
+    let final_evals =
+        extension_field_rounds(factors, &mut round_polys, &mut challenges, challenger);
+
+    finish(round_polys, challenges, final_evals, challenger)
+}
+
+/// Same as `prove`, but for factors defined over the extension field.
+#[instrument(name = "sumcheck prover", skip_all)]
+pub fn prove_extension<F, EF, Challenger>(
+    factors: Vec<Vec<EF>>,
+    challenger: &mut Challenger,
+) -> (SumcheckProof<EF>, Vec<EF>)
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Challenger: FieldChallenger<F>,
+{
+    num_vars(&factors);
+    let mut round_polys = vec![];
+    let mut challenges = vec![];
+    let final_evals =
+        extension_field_rounds(factors, &mut round_polys, &mut challenges, challenger);
+    finish(round_polys, challenges, final_evals, challenger)
+}
+
+fn num_vars<T>(factors: &[Vec<T>]) -> usize {
+    assert!(!factors.is_empty(), "at least one factor is required");
+    let len = factors[0].len();
+    assert!(
+        factors.iter().all(|f| f.len() == len),
+        "all factors must have the same number of variables"
+    );
+    log2_strict_usize(len)
+}
+
+fn finish<F, EF, Challenger>(
+    round_polys: Vec<Vec<EF>>,
+    mut challenges: Vec<EF>,
+    final_evals: Vec<EF>,
+    challenger: &mut Challenger,
+) -> (SumcheckProof<EF>, Vec<EF>)
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Challenger: FieldChallenger<F>,
+{
+    final_evals
+        .iter()
+        .for_each(|&y| challenger.observe_algebra_element(y));
+
+    // Variables are bound from the most significant one down.
+    challenges.reverse();
+    (
+        SumcheckProof {
+            round_polys,
+            final_evals,
+        },
+        challenges,
+    )
+}
+
+fn observe_and_sample<F, EF, Challenger>(round_poly: &[EF], challenger: &mut Challenger) -> EF
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Challenger: FieldChallenger<F>,
+{
+    round_poly
+        .iter()
+        .for_each(|&y| challenger.observe_algebra_element(y));
+    challenger.sample_algebra_element()
+}
+
+/// Runs the remaining rounds on factors which are already defined over the extension field, and
+/// returns the evaluation of each factor at the final point.
+fn extension_field_rounds<F, EF, Challenger>(
+    mut factors: Vec<Vec<EF>>,
+    round_polys: &mut Vec<Vec<EF>>,
+    challenges: &mut Vec<EF>,
+    challenger: &mut Challenger,
+) -> Vec<EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Challenger: FieldChallenger<F>,
+{
+    let width = F::Packing::WIDTH;
+
+    // Work on packed extension elements while each half of a table holds at least one of them.
+    if factors[0].len() >= 2 * width {
+        let mut packed = factors
+            .iter()
+            .map(|f| {
+                f.chunks_exact(width)
+                    .map(EF::ExtensionPacking::from_ext_slice)
+                    .collect_vec()
+            })
+            .collect_vec();
+        while packed[0].len() >= 2 {
+            let round_poly = info_span!("packed extension field round").in_scope(|| {
+                round_sums(&packed)
+                    .into_iter()
+                    .map(|s| {
+                        EF::from_basis_coefficients_fn(|i| {
+                            s.as_basis_coefficients_slice()[i]
+                                .as_slice()
+                                .iter()
+                                .copied()
+                                .sum()
+                        })
+                    })
+                    .collect_vec()
+            });
+            let r = observe_and_sample::<F, EF, _>(&round_poly, challenger);
+            packed.iter_mut().for_each(|t| bind(t, r));
+            round_polys.push(round_poly);
+            challenges.push(r);
+        }
+        factors = packed
+            .iter()
+            .map(|t| {
+                t.iter()
+                    .flat_map(|p| {
+                        (0..width).map(move |lane| {
+                            EF::from_basis_coefficients_fn(|i| {
+                                p.as_basis_coefficients_slice()[i].as_slice()[lane]
+                            })
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    while factors[0].len() >= 2 {
+        let round_poly = round_sums(&factors);
+        let r = observe_and_sample::<F, EF, _>(&round_poly, challenger);
+        factors.iter_mut().for_each(|t| bind(t, r));
+        round_polys.push(round_poly);
+        challenges.push(r);
+    }
+
+    factors.iter().map(|f| f[0]).collect()
+}
+
+/// Evaluates, at `X = 0, 1, ..., degree`, the sum over `i` of `prod_j (lo_j[i] + X (hi_j[i] - lo_j[i]))`,
+/// where `lo_j` and `hi_j` are the two halves of the `j`-th table.
+fn round_sums<A>(tables: &[Vec<A>]) -> Vec<A>
+where
+    A: PrimeCharacteristicRing + Copy + Send + Sync,
+{
+    let degree = tables.len();
+    let half = tables[0].len() / 2;
+    let (sums, _, _) = (0..half).into_par_iter().par_fold_reduce(
+        || {
+            (
+                A::zero_vec(degree + 1),
+                A::zero_vec(degree),
+                A::zero_vec(degree),
+            )
+        },
+        |(mut sums, mut evals, mut diffs), i| {
+            for (t, e, d) in izip!(tables, &mut evals, &mut diffs) {
+                *e = t[i];
+                *d = t[half + i] - t[i];
+            }
+            sums[0] += evals.iter().copied().product();
+            for sum in &mut sums[1..] {
+                izip!(&mut evals, &diffs).for_each(|(e, &d)| *e += d);
+                *sum += evals.iter().copied().product();
+            }
+            (sums, evals, diffs)
+        },
+        |(mut sums, evals, diffs), (other, _, _)| {
+            izip!(&mut sums, other).for_each(|(l, r)| *l += r);
+            (sums, evals, diffs)
+        },
+    );
+    sums
+}
+
+/// Binds the most significant variable of `table` to `r`.
+fn bind<EF, A>(table: &mut Vec<A>, r: EF)
+where
+    EF: Field,
+    A: Algebra<EF> + Copy + Send + Sync,
+{
+    let half = table.len() / 2;
+    let (lo, hi) = table.split_at_mut(half);
+    lo.par_iter_mut()
+        .zip(hi.par_iter())
+        .for_each(|(l, &h)| *l += (h - *l) * r);
+    table.truncate(half);
+}
+
+/// Binds the most significant `log2(weights.len())` variables of `f`, where `weights[s]` is the
+/// equality polynomial between the challenges and the bits of `s`.
+fn bind_prefix<F: Field, EF: ExtensionField<F>>(f: &[F], weights: &[EF]) -> Vec<EF> {
+    let block = f.len() / weights.len();
+    (0..block)
+        .into_par_iter()
+        .map(|i| {
+            weights
+                .iter()
+                .enumerate()
+                .map(|(s, &w)| w * f[s * block + i])
+                .sum()
+        })
+        .collect()
+}
+
+/// Computes the round polynomial for unbound base field factors, where the variables already
+/// fixed by previous rounds are accounted for by `weights` (see `bind_prefix`).
+///
+/// Writing `f_j(r, X, b) = sum_s w(s) f_j(s, X, b)`, the round polynomial expands to
+/// `sum_{s_1, ..., s_d} prod_j w(s_j) sum_b prod_j f_j(s_j, X, b)`, in which the inner sums only
+/// involve base field values.
+fn base_field_round_poly<F: Field, EF: ExtensionField<F>>(
+    factors: &[Vec<F>],
+    weights: &[EF],
+) -> Vec<EF> {
+    let degree = factors.len();
+    let num_prefix_bits = log2_strict_usize(weights.len());
+    let half = factors[0].len() >> (num_prefix_bits + 1);
+
+    let sums = if half >= F::Packing::WIDTH {
+        let packed = factors
+            .iter()
+            .map(|f| F::Packing::pack_slice(f))
+            .collect_vec();
+        base_field_round_sums(&packed, num_prefix_bits)
+    } else {
+        let unpacked = factors.iter().map(|f| f.as_slice()).collect_vec();
+        base_field_round_sums(&unpacked, num_prefix_bits)
+    };
+
+    let mask = weights.len() - 1;
+    let mut round_poly = EF::zero_vec(degree + 1);
+    for (t, sums_for_t) in sums.iter().enumerate() {
+        let weight: EF = (0..degree)
+            .map(|j| weights[(t >> (num_prefix_bits * j)) & mask])
+            .product();
+        izip!(&mut round_poly, sums_for_t).for_each(|(acc, &s)| *acc += weight * s);
+    }
+    round_poly
+}
+
+/// For each choice `t = (s_1, ..., s_d)` of prefix bits and each `X = 0, 1, ..., degree`, computes
+/// `sum_b prod_j f_j(s_j, X, b)`.
+fn base_field_round_sums<P: PackedField>(
+    tables: &[&[P]],
+    num_prefix_bits: usize,
+) -> Vec<Vec<P::Scalar>> {
+    let degree = tables.len();
+    let num_prefixes = 1 << num_prefix_bits;
+    let num_tuples = 1 << (num_prefix_bits * degree);
+    let mask = num_prefixes - 1;
+    let block = tables[0].len() >> num_prefix_bits;
+    let half = block / 2;
+
+    let (sums, _, _) = (0..half).into_par_iter().par_fold_reduce(
+        || {
+            (
+                P::zero_vec(num_tuples * (degree + 1)),
+                P::zero_vec(degree * num_prefixes),
+                P::zero_vec(degree * num_prefixes),
+            )
+        },
+        |(mut sums, mut evals, mut diffs), i| {
+            for (t, evals, diffs) in izip!(
+                tables,
+                evals.chunks_exact_mut(num_prefixes),
+                diffs.chunks_exact_mut(num_prefixes)
+            ) {
+                for (s, (e, d)) in izip!(evals, diffs).enumerate() {
+                    *e = t[s * block + i];
+                    *d = t[s * block + half + i] - *e;
+                }
+            }
+            for x in 0..=degree {
+                if x > 0 {
+                    izip!(&mut evals, &diffs).for_each(|(e, &d)| *e += d);
+                }
+                for t in 0..num_tuples {
+                    sums[t * (degree + 1) + x] += (0..degree)
+                        .map(|j| evals[j * num_prefixes + ((t >> (num_prefix_bits * j)) & mask)])
+                        .product::<P>();
+                }
+            }
+            (sums, evals, diffs)
+        },
+        |(mut sums, evals, diffs), (other, _, _)| {
+            izip!(&mut sums, other).for_each(|(l, r)| *l += r);
+            (sums, evals, diffs)
+        },
+    );
+
+    sums.chunks_exact(degree + 1)
+        .map(|sums_for_t| {
+            sums_for_t
+                .iter()
+                .map(|s| s.as_slice().iter().copied().sum())
+                .collect()
+        })
+        .collect()
+}
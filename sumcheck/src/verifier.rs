@@ -0,0 +1,268 @@
+use alloc::vec::Vec;
+
+use p3_challenger::FieldChallenger;
+use p3_field::{ExtensionField, Field};
+
+use crate::SumcheckProof;
+
+#[derive(Debug)]
+pub enum SumcheckError {
+    InvalidProofShape,
+    /// A round polynomial does not sum to the running claim over `{0, 1}`.
+    RoundSumMismatch,
+    /// The product of the final evaluations does not match the final claim.
+    FinalEvaluationMismatch,
+}
+
+/// What remains to be checked after a successful sumcheck verification: factor `j` must evaluate
+/// to `evals[j]` at `point`, typically by opening a polynomial commitment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SumcheckSubclaim<EF> {
+    pub point: Vec<EF>,
+    pub evals: Vec<EF>,
+}
+
+/// Verifies a proof produced by `prove` or `prove_extension` that the product of `degree`
+/// multilinear polynomials in `num_vars` variables sums to `claimed_sum` over the boolean hypercube.
+pub fn verify<F, EF, Challenger>(
+    num_vars: usize,
+    degree: usize,
+    claimed_sum: EF,
+    proof: &SumcheckProof<EF>,
+    challenger: &mut Challenger,
+) -> Result<SumcheckSubclaim<EF>, SumcheckError>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Challenger: FieldChallenger<F>,
+{
+    if proof.round_polys.len() != num_vars
+        || proof.round_polys.iter().any(|p| p.len() != degree + 1)
+        || proof.final_evals.len() != degree
+    {
+        return Err(SumcheckError::InvalidProofShape);
+    }
+
+    let mut claim = claimed_sum;
+    let mut point = Vec::with_capacity(num_vars);
+    for round_poly in &proof.round_polys {
+        if round_poly[0] + round_poly[1] != claim {
+            return Err(SumcheckError::RoundSumMismatch);
+        }
+        round_poly
+            .iter()
+            .for_each(|&y| challenger.observe_algebra_element(y));
+        let r: EF = challenger.sample_algebra_element();
+        claim = interpolate_at_integers::<F, EF>(round_poly, r);
+        point.push(r);
+    }
+
+    proof
+        .final_evals
+        .iter()
+        .for_each(|&y| challenger.observe_algebra_element(y));
+    if proof.final_evals.iter().copied().product::<EF>() != claim {
+        return Err(SumcheckError::FinalEvaluationMismatch);
+    }
+
+    // Variables are bound from the most significant one down.
+    point.reverse();
+    Ok(SumcheckSubclaim {
+        point,
+        evals: proof.final_evals.clone(),
+    })
+}
+
+/// Evaluates at `x` the polynomial of degree `< evals.len()` whose value at `i` is `evals[i]`.
+fn interpolate_at_integers<F: Field, EF: ExtensionField<F>>(evals: &[EF], x: EF) -> EF {
+    let nodes = (0..evals.len()).map(F::from_usize);
+    nodes
+        .clone()
+        .zip(evals)
+        .map(|(xi, &yi)| {
+            let (num, denom) = nodes
+                .clone()
+                .filter(|&xj| xj != xi)
+                .fold((EF::ONE, F::ONE), |(num, denom), xj| {
+                    (num * (x - xj), denom * (xi - xj))
+                });
+            num * denom.inverse() * yi
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use itertools::Itertools;
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_challenger::DuplexChallenger;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+    use p3_koala_bear::{KoalaBear, Poseidon2KoalaBear};
+    use rand::distr::{Distribution, StandardUniform};
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::{prove, prove_extension};
+
+    /// Evaluates the multilinear polynomial with hypercube evaluations `evals` at `point`.
+    fn evaluate<F: Field, EF: ExtensionField<F>>(evals: &[F], point: &[EF]) -> EF {
+        let mut evals = evals.iter().map(|&x| EF::from(x)).collect_vec();
+        for &z in point {
+            evals = evals
+                .chunks_exact(2)
+                .map(|e| e[0] + z * (e[1] - e[0]))
+                .collect();
+        }
+        evals[0]
+    }
+
+    fn naive_sum<F: Field>(factors: &[Vec<F>]) -> F {
+        (0..factors[0].len())
+            .map(|b| factors.iter().map(|f| f[b]).product::<F>())
+            .sum()
+    }
+
+    fn do_test<F, EF, Challenger>(challenger: Challenger, num_vars: usize, degree: usize)
+    where
+        F: Field,
+        EF: ExtensionField<F>,
+        StandardUniform: Distribution<F> + Distribution<EF>,
+        Challenger: FieldChallenger<F> + Clone,
+    {
+        let mut rng = SmallRng::seed_from_u64(num_vars as u64);
+        let factors = (0..degree)
+            .map(|_| {
+                (&mut rng)
+                    .sample_iter(StandardUniform)
+                    .take(1 << num_vars)
+                    .collect_vec()
+            })
+            .collect_vec();
+        let claimed_sum = EF::from(naive_sum(&factors));
+
+        for num_base_field_rounds in 0..=2 {
+            let mut p_challenger = challenger.clone();
+            let (proof, point) =
+                prove::<F, EF, _>(factors.clone(), num_base_field_rounds, &mut p_challenger);
+
+            let mut v_challenger = challenger.clone();
+            let subclaim =
+                verify::<F, EF, _>(num_vars, degree, claimed_sum, &proof, &mut v_challenger)
+                    .unwrap();
+            assert_eq!(subclaim.point, point);
+            for (f, &y) in factors.iter().zip(&subclaim.evals) {
+                assert_eq!(evaluate(f, &point), y);
+            }
+            assert_eq!(
+                p_challenger.sample_algebra_element::<EF>(),
+                v_challenger.sample_algebra_element::<EF>()
+            );
+
+            // A wrong claimed sum must be rejected.
+            let mut v_challenger = challenger.clone();
+            assert!(matches!(
+                verify::<F, EF, _>(
+                    num_vars,
+                    degree,
+                    claimed_sum + EF::ONE,
+                    &proof,
+                    &mut v_challenger
+                ),
+                Err(SumcheckError::RoundSumMismatch | SumcheckError::FinalEvaluationMismatch)
+            ));
+        }
+
+        // The same polynomials, lifted to the extension field.
+        let ext_factors = factors
+            .iter()
+            .map(|f| f.iter().map(|&x| EF::from(x)).collect_vec())
+            .collect_vec();
+        let mut p_challenger = challenger.clone();
+        let (proof, point) = prove_extension::<F, EF, _>(ext_factors, &mut p_challenger);
+        let subclaim = verify::<F, EF, _>(
+            num_vars,
+            degree,
+            claimed_sum,
+            &proof,
+            &mut challenger.clone(),
+        )
+        .unwrap();
+        assert_eq!(subclaim.point, point);
+
+        // Extension field factors with a random sum.
+        let ext_factors: Vec<Vec<EF>> = (0..degree)
+            .map(|_| {
+                (&mut rng)
+                    .sample_iter(StandardUniform)
+                    .take(1 << num_vars)
+                    .collect()
+            })
+            .collect();
+        let claimed_sum = naive_sum(&ext_factors);
+        let (proof, point) =
+            prove_extension::<F, EF, _>(ext_factors.clone(), &mut challenger.clone());
+        let subclaim = verify::<F, EF, _>(
+            num_vars,
+            degree,
+            claimed_sum,
+            &proof,
+            &mut challenger.clone(),
+        )
+        .unwrap();
+        assert_eq!(subclaim.point, point);
+        for (f, &y) in ext_factors.iter().zip(&subclaim.evals) {
+            assert_eq!(evaluate(f, &point), y);
+        }
+    }
+
+    #[test]
+    fn test_sumcheck_baby_bear() {
+        type F = BabyBear;
+        type EF = BinomialExtensionField<F, 4>;
+        let perm = Poseidon2BabyBear::<16>::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+        let challenger = DuplexChallenger::<F, _, 16, 8>::new(perm);
+        for num_vars in [0, 1, 3, 6, 10] {
+            for degree in 1..=4 {
+                do_test::<F, EF, _>(challenger.clone(), num_vars, degree);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sumcheck_koala_bear() {
+        type F = KoalaBear;
+        type EF = BinomialExtensionField<F, 4>;
+        let perm = Poseidon2KoalaBear::<16>::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+        let challenger = DuplexChallenger::<F, _, 16, 8>::new(perm);
+        for num_vars in [0, 2, 5, 9] {
+            for degree in 1..=3 {
+                do_test::<F, EF, _>(challenger.clone(), num_vars, degree);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_proof_shape() {
+        type F = BabyBear;
+        type EF = BinomialExtensionField<F, 4>;
+        let perm = Poseidon2BabyBear::<16>::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+        let challenger = DuplexChallenger::<F, _, 16, 8>::new(perm);
+        let factors = vec![vec![F::ONE; 8], vec![F::TWO; 8]];
+        let (proof, _) = prove::<F, EF, _>(factors, 1, &mut challenger.clone());
+        let claimed_sum = EF::from_u8(16);
+        assert!(matches!(
+            verify::<F, EF, _>(2, 2, claimed_sum, &proof, &mut challenger.clone()),
+            Err(SumcheckError::InvalidProofShape)
+        ));
+        assert!(matches!(
+            verify::<F, EF, _>(3, 3, claimed_sum, &proof, &mut challenger.clone()),
+            Err(SumcheckError::InvalidProofShape)
+        ));
+        assert!(verify::<F, EF, _>(3, 2, claimed_sum, &proof, &mut challenger.clone()).is_ok());
+    }
+}
@@ -1,3 +1,5 @@
+use alloc::vec;
+use alloc::vec::Vec;
 use core::borrow::Borrow;
 use core::marker::PhantomData;
 
@@ -56,3 +58,53 @@ impl<F, W, const DIGEST_ELEMS: usize> AsRef<[W; DIGEST_ELEMS]> for Hash<F, W, DI
         &self.value
     }
 }
+
+/// The digests of all nodes at some height of a Merkle tree, which can serve as a commitment in
+/// place of the root. Openings then only need to authenticate their path up to that height.
+///
+/// A cap of height 0 consists of the root alone.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(serialize = "[W; DIGEST_ELEMS]: Serialize"))]
+#[serde(bound(deserialize = "[W; DIGEST_ELEMS]: Deserialize<'de>"))]
+pub struct MerkleCap<F, W, const DIGEST_ELEMS: usize> {
+    digests: Vec<[W; DIGEST_ELEMS]>,
+    _marker: PhantomData<F>,
+}
+
+impl<F, W, const DIGEST_ELEMS: usize> MerkleCap<F, W, DIGEST_ELEMS> {
+    /// The digests of the nodes in the cap, from left to right.
+    pub fn digests(&self) -> &[[W; DIGEST_ELEMS]] {
+        &self.digests
+    }
+}
+
+impl<F, W, const DIGEST_ELEMS: usize> From<Vec<[W; DIGEST_ELEMS]>>
+    for MerkleCap<F, W, DIGEST_ELEMS>
+{
+    fn from(digests: Vec<[W; DIGEST_ELEMS]>) -> Self {
+        Self {
+            digests,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F, W, const DIGEST_ELEMS: usize> From<Hash<F, W, DIGEST_ELEMS>>
+    for MerkleCap<F, W, DIGEST_ELEMS>
+{
+    fn from(root: Hash<F, W, DIGEST_ELEMS>) -> Self {
+        vec![root.value].into()
+    }
+}
+
+impl<F, W, const DIGEST_ELEMS: usize> IntoIterator for MerkleCap<F, W, DIGEST_ELEMS> {
+    type Item = Hash<F, W, DIGEST_ELEMS>;
+    type IntoIter = core::iter::Map<
+        vec::IntoIter<[W; DIGEST_ELEMS]>,
+        fn([W; DIGEST_ELEMS]) -> Hash<F, W, DIGEST_ELEMS>,
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.digests.into_iter().map(Hash::from)
+    }
+}
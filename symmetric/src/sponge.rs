@@ -2,7 +2,7 @@ use alloc::string::String;
 use core::marker::PhantomData;
 
 use itertools::Itertools;
-use p3_field::{Field, PrimeField, PrimeField32, reduce_32};
+use p3_field::{Field, PrimeField, PrimeField32, PrimeField64, reduce_32, reduce_64};
 
 use crate::hasher::CryptographicHasher;
 use crate::permutation::CryptographicPermutation;
@@ -121,6 +121,76 @@ where
     }
 }
 
+/// A padding-free, overwrite-mode sponge function that operates natively over PF but accepts elements
+/// of F: PrimeField64.
+///
+/// Each PF element of the rate absorbs as many F elements as fit injectively in a 2^64-base
+/// decomposition, e.g. three Goldilocks elements per `Bn254Fr`.
+///
+/// `WIDTH` is the sponge's rate plus the sponge's capacity.
+#[derive(Clone, Debug)]
+pub struct MultiField64PaddingFreeSponge<
+    F,
+    PF,
+    P,
+    const WIDTH: usize,
+    const RATE: usize,
+    const OUT: usize,
+> {
+    permutation: P,
+    num_f_elms: usize,
+    _phantom: PhantomData<(F, PF)>,
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize, const OUT: usize>
+    MultiField64PaddingFreeSponge<F, PF, P, WIDTH, RATE, OUT>
+where
+    F: PrimeField64,
+    PF: Field,
+{
+    pub fn new(permutation: P) -> Result<Self, String> {
+        if F::order() >= PF::order() {
+            return Err(String::from("F::order() must be less than PF::order()"));
+        }
+
+        let num_f_elms = (PF::bits() - 1) / 64;
+        if num_f_elms == 0 {
+            return Err(String::from("PF must have more than 64 bits"));
+        }
+        Ok(Self {
+            permutation,
+            num_f_elms,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize, const OUT: usize>
+    CryptographicHasher<F, [PF; OUT]> for MultiField64PaddingFreeSponge<F, PF, P, WIDTH, RATE, OUT>
+where
+    F: PrimeField64,
+    PF: PrimeField + Default + Copy,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn hash_iter<I>(&self, input: I) -> [PF; OUT]
+    where
+        I: IntoIterator<Item = F>,
+    {
+        let mut state = [PF::default(); WIDTH];
+        for block_chunk in &input.into_iter().chunks(RATE * self.num_f_elms) {
+            for (chunk_id, chunk) in (&block_chunk.chunks(self.num_f_elms))
+                .into_iter()
+                .enumerate()
+            {
+                state[chunk_id] = reduce_64(&chunk.collect_vec());
+            }
+            state = self.permutation.permute(state);
+        }
+
+        state[..OUT].try_into().unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,5 +1,7 @@
 use alloc::vec;
 use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
 
 use itertools::Itertools;
 use p3_air::{Air, BaseAir};
@@ -11,7 +13,7 @@ use p3_matrix::stack::VerticalPair;
 use tracing::instrument;
 
 use crate::symbolic_builder::{SymbolicAirBuilder, get_log_quotient_degree};
-use crate::{PcsError, Proof, StarkGenericConfig, Val, VerifierConstraintFolder};
+use crate::{OpenedValues, PcsError, Proof, StarkGenericConfig, Val, VerifierConstraintFolder};
 
 #[instrument(skip_all)]
 pub fn verify<SC, A>(
@@ -43,16 +45,8 @@ where
     let quotient_chunks_domains = quotient_domain.split_domains(quotient_degree);
 
     let air_width = <A as BaseAir<Val<SC>>>::width(air);
-    let valid_shape = opened_values.trace_local.len() == air_width
-        && opened_values.trace_next.len() == air_width
-        && opened_values.quotient_chunks.len() == quotient_degree
-        && opened_values
-            .quotient_chunks
-            .iter()
-            .all(|qc| qc.len() == <SC::Challenge as BasedVectorSpace<Val<SC>>>::DIMENSION);
-    if !valid_shape {
-        return Err(VerificationError::InvalidProofShape);
-    }
+    check_opened_shape::<SC>(opened_values, air_width, quotient_degree)
+        .map_err(VerificationError::InvalidProofShape)?;
 
     // Observe the instance.
     challenger.observe(Val::<SC>::from_usize(proof.degree_bits));
@@ -155,12 +149,116 @@ where
     Ok(())
 }
 
+fn check_opened_shape<SC: StarkGenericConfig>(
+    opened_values: &OpenedValues<SC::Challenge>,
+    air_width: usize,
+    quotient_degree: usize,
+) -> Result<(), ProofShapeError> {
+    if opened_values.trace_local.len() != air_width {
+        return Err(ProofShapeError::TraceLocalWidth {
+            expected: air_width,
+            actual: opened_values.trace_local.len(),
+        });
+    }
+    if opened_values.trace_next.len() != air_width {
+        return Err(ProofShapeError::TraceNextWidth {
+            expected: air_width,
+            actual: opened_values.trace_next.len(),
+        });
+    }
+    if opened_values.quotient_chunks.len() != quotient_degree {
+        return Err(ProofShapeError::NumQuotientChunks {
+            expected: quotient_degree,
+            actual: opened_values.quotient_chunks.len(),
+        });
+    }
+    let dimension = <SC::Challenge as BasedVectorSpace<Val<SC>>>::DIMENSION;
+    match opened_values
+        .quotient_chunks
+        .iter()
+        .position(|qc| qc.len() != dimension)
+    {
+        Some(chunk) => Err(ProofShapeError::QuotientChunkWidth {
+            chunk,
+            expected: dimension,
+            actual: opened_values.quotient_chunks[chunk].len(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The part of a proof whose shape doesn't match the AIR.
+#[derive(Debug)]
+pub enum ProofShapeError {
+    /// The number of trace values opened at `zeta` doesn't match the AIR width.
+    TraceLocalWidth { expected: usize, actual: usize },
+    /// The number of trace values opened at `g * zeta` doesn't match the AIR width.
+    TraceNextWidth { expected: usize, actual: usize },
+    /// The number of opened quotient chunks doesn't match the quotient degree.
+    NumQuotientChunks { expected: usize, actual: usize },
+    /// The given quotient chunk doesn't have one value per extension coordinate.
+    QuotientChunkWidth {
+        chunk: usize,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl Display for ProofShapeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TraceLocalWidth { expected, actual } => {
+                write!(f, "expected {expected} local trace values, got {actual}")
+            }
+            Self::TraceNextWidth { expected, actual } => {
+                write!(f, "expected {expected} next trace values, got {actual}")
+            }
+            Self::NumQuotientChunks { expected, actual } => {
+                write!(f, "expected {expected} quotient chunks, got {actual}")
+            }
+            Self::QuotientChunkWidth {
+                chunk,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "expected {expected} values in quotient chunk {chunk}, got {actual}"
+            ),
+        }
+    }
+}
+
+impl Error for ProofShapeError {}
+
 #[derive(Debug)]
 pub enum VerificationError<PcsErr> {
-    InvalidProofShape,
+    /// The opened values don't have the shape the AIR requires.
+    InvalidProofShape(ProofShapeError),
     /// An error occurred while verifying the claimed openings.
     InvalidOpeningArgument(PcsErr),
     /// Out-of-domain evaluation mismatch, i.e. `constraints(zeta)` did not match
     /// `quotient(zeta) Z_H(zeta)`.
     OodEvaluationMismatch,
 }
+
+impl<PcsErr: Display> Display for VerificationError<PcsErr> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidProofShape(e) => write!(f, "invalid proof shape: {e}"),
+            Self::InvalidOpeningArgument(e) => write!(f, "invalid opening argument: {e}"),
+            Self::OodEvaluationMismatch => {
+                write!(f, "out-of-domain evaluation doesn't match the quotient")
+            }
+        }
+    }
+}
+
+impl<PcsErr: Error + 'static> Error for VerificationError<PcsErr> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidProofShape(e) => Some(e),
+            Self::InvalidOpeningArgument(e) => Some(e),
+            Self::OodEvaluationMismatch => None,
+        }
+    }
+}
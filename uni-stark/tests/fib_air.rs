@@ -11,6 +11,7 @@ use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_merkle_tree::MerkleTreeMmcs;
+use p3_stir::{StirConfig, StirPcs};
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
 use p3_uni_stark::{StarkConfig, prove, verify};
 use rand::rng;
@@ -120,7 +121,7 @@ fn test_public_value_impl(n: usize, x: u64) {
     let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
     let dft = Dft::default();
     let trace = generate_trace_rows::<Val>(0, 1, n);
-    let fri_config = create_test_fri_config(challenge_mmcs);
+    let fri_config = create_test_fri_config(challenge_mmcs, 0);
     let pcs = Pcs::new(dft, val_mmcs, fri_config);
     let config = MyConfig::new(pcs);
     let mut challenger = Challenger::new(perm.clone());
@@ -140,6 +141,35 @@ fn test_public_value() {
     test_public_value_impl(1 << 3, 21);
 }
 
+#[test]
+fn test_public_value_stir() {
+    type StirPcsForTest = StirPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+    let perm = Perm::new_from_rng_128(&mut rng());
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let stir_config = StirConfig {
+        log_blowup: 2,
+        log_folding_factor: 2,
+        log_final_poly_len: 0,
+        num_queries: 2,
+        num_ood_samples: 1,
+        proof_of_work_bits: 1,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = StirPcsForTest::new(dft, val_mmcs, stir_config);
+    let config = StarkConfig::<_, Challenge, Challenger>::new(pcs);
+    let mut challenger = Challenger::new(perm.clone());
+    let pis = vec![BabyBear::ZERO, BabyBear::ONE, BabyBear::from_u64(21)];
+    let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis);
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis).expect("verification failed");
+}
+
 #[cfg(debug_assertions)]
 #[test]
 #[should_panic(expected = "assertion `left == right` failed: constraints had nonzero value")]
@@ -150,7 +180,7 @@ fn test_incorrect_public_value() {
     let val_mmcs = ValMmcs::new(hash, compress);
     let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
     let dft = Dft::default();
-    let fri_config = create_test_fri_config(challenge_mmcs);
+    let fri_config = create_test_fri_config(challenge_mmcs, 0);
     let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
     let pcs = Pcs::new(dft, val_mmcs, fri_config);
     let config = MyConfig::new(pcs);
@@ -4,7 +4,10 @@ use std::marker::PhantomData;
 use itertools::Itertools;
 use p3_air::{Air, AirBuilder, BaseAir};
 use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
-use p3_challenger::{DuplexChallenger, HashChallenger, SerializingChallenger32};
+use p3_challenger::{
+    DuplexChallenger, HashChallenger, LabeledChallenger, RecordingChallenger,
+    SerializingChallenger32, diff_transcripts,
+};
 use p3_circle::CirclePcs;
 use p3_commit::ExtensionMmcs;
 use p3_commit::testing::TrivialPcs;
@@ -188,6 +191,72 @@ fn prove_bb_trivial_deg4() -> Result<(), impl Debug> {
     do_test_bb_trivial(4, 8)
 }
 
+#[test]
+fn prove_bb_trivial_labeled() -> Result<(), impl Debug> {
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+
+    type Perm = Poseidon2BabyBear<16>;
+    let perm = Perm::new_from_rng_128(&mut rng());
+
+    type Challenger = LabeledChallenger<DuplexChallenger<Val, Perm, 16, 8>>;
+
+    type Pcs = TrivialPcs<Val, Radix2DitParallel<Val>>;
+    let pcs = TrivialPcs {
+        dft: Radix2DitParallel::default(),
+        log_n: 8,
+        _phantom: PhantomData,
+    };
+
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+    let config = MyConfig::new(pcs);
+
+    let air = MulAir {
+        degree: 3,
+        ..Default::default()
+    };
+
+    let challenger = LabeledChallenger::new(DuplexChallenger::new(perm), b"mul_air");
+    do_test(config, air, 1 << 8, challenger)
+}
+
+#[test]
+fn prover_and_verifier_transcripts_match() {
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+
+    type Perm = Poseidon2BabyBear<16>;
+    let perm = Perm::new_from_rng_128(&mut rng());
+
+    type Challenger = RecordingChallenger<DuplexChallenger<Val, Perm, 16, 8>>;
+
+    type Pcs = TrivialPcs<Val, Radix2DitParallel<Val>>;
+    let pcs = TrivialPcs {
+        dft: Radix2DitParallel::default(),
+        log_n: 8,
+        _phantom: PhantomData,
+    };
+
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+    let config = MyConfig::new(pcs);
+
+    let air = MulAir {
+        degree: 3,
+        ..Default::default()
+    };
+    let trace = air.random_valid_trace(1 << 8, true);
+
+    let mut p_challenger = Challenger::new(DuplexChallenger::new(perm.clone()));
+    let proof = prove(&config, &air, &mut p_challenger, trace, &vec![]);
+
+    let mut v_challenger = Challenger::new(DuplexChallenger::new(perm));
+    verify(&config, &air, &mut v_challenger, &proof, &vec![]).expect("verification failed");
+
+    if let Some(divergence) = diff_transcripts(p_challenger.log(), v_challenger.log()) {
+        panic!("{divergence}");
+    }
+}
+
 fn do_test_bb_twoadic(log_blowup: usize, degree: u64, log_n: usize) -> Result<(), impl Debug> {
     type Val = BabyBear;
     type Challenge = BinomialExtensionField<Val, 4>;
@@ -218,6 +287,9 @@ fn do_test_bb_twoadic(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
         log_final_poly_len: 5,
         num_queries: 40,
         proof_of_work_bits: 8,
+        commit_proof_of_work_bits: 0,
+        log_folding_arity: 1,
+        use_multiproofs: false,
         mmcs: challenge_mmcs,
     };
     type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
@@ -279,6 +351,9 @@ fn do_test_m31_circle(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
         log_final_poly_len: 0,
         num_queries: 40,
         proof_of_work_bits: 8,
+        commit_proof_of_work_bits: 0,
+        log_folding_arity: 1,
+        use_multiproofs: false,
         mmcs: challenge_mmcs,
     };
 